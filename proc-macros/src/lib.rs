@@ -58,6 +58,7 @@ pub fn derive_sfnt_table(input: proc_macro::TokenStream) -> proc_macro::TokenStr
         };
         assert!(ty.qself.is_none());
         let size = match &*ty.path.segments.last().unwrap().value().ident.to_string() {
+            "u8" | "i8" => 1,
             "u16" | "i16" | "FWord" | "UFWord" | "FontDesignUnitsPerEmFactorU16" => 2,
             "u32" | "FixedPoint" | "Tag" => 4,
             "LongDateTime" => 8,
@@ -6,7 +6,9 @@ pub fn derive_computed_as_specified(input: proc_macro::TokenStream) -> proc_macr
     let tokens = quote! {
         impl crate::style::values::ToComputedValue for #name {
             type Computed = Self;
-            fn to_computed(&self) -> Self::Computed {
+            fn to_computed(&self, _context: &crate::style::values::CascadeContext)
+                -> Self::Computed
+            {
                 std::clone::Clone::clone(self)
             }
         }
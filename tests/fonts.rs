@@ -0,0 +1,508 @@
+use std::sync::Arc;
+use victor::fonts::{Em, Font, FontCollection, FontError, FontFeatures, GlyphId, VariationAxis};
+use victor::fonts::{PathSegment, BITSTREAM_VERA_SANS};
+use victor::primitives::{point, rect, Length, Point};
+use victor::text::{Direction, NaiveShaper, OpenTypeShaper, ShapedSegment, Shaper};
+
+#[test]
+fn collection() {
+    let mut fonts = FontCollection::new();
+    assert_eq!(fonts.add_directory("../victor/fonts/vera").unwrap(), 10);
+    // Noto Sans Linear B is there as TrueType, WOFF, and WOFF2,
+    // next to Ahem, DejaVu Sans Mono, and a CFF-based OpenType font.
+    assert_eq!(fonts.add_directory("fonts").unwrap(), 6);
+    assert_eq!(fonts.len(), 16);
+
+    let names = |family: &str| -> Vec<String> {
+        fonts
+            .family(family)
+            .iter()
+            .map(|face| face.font.subfamily_name().to_owned())
+            .collect()
+    };
+    // Files are added in order of their name: Vera.ttf, VeraBI.ttf, VeraBd.ttf, VeraIt.ttf
+    assert_eq!(
+        names("Bitstream Vera Sans"),
+        ["Roman", "Bold Oblique", "Bold", "Oblique"]
+    );
+    assert_eq!(names("bitstream vera serif"), ["Roman", "Bold"]);
+    assert_eq!(
+        names("Noto Sans Linear B"),
+        ["Regular", "Regular", "Regular"]
+    );
+    assert!(names("Nope").is_empty());
+
+    let bold = fonts.get("Bitstream Vera Sans Mono", "bold").unwrap();
+    assert_eq!(bold.family_name(), "Bitstream Vera Sans Mono");
+    assert!(fonts.get("Bitstream Vera Sans Mono", "Black").is_none());
+}
+
+#[test]
+fn collection_file() {
+    let bytes = vera_and_noto_collection();
+    let faces = Font::parse_collection(bytes.clone()).unwrap();
+    let names: Vec<_> = faces.iter().map(|font| font.family_name()).collect();
+    assert_eq!(names, ["Bitstream Vera Sans", "Noto Sans Linear B"]);
+
+    let noto = Font::parse_face(bytes.clone(), 1).unwrap();
+    assert_eq!(noto.family_name(), "Noto Sans Linear B");
+    assert!(matches!(
+        Font::parse_face(bytes, 2),
+        Err(FontError::FaceIndexOutOfRange)
+    ));
+    assert!(matches!(
+        Font::parse_face(BITSTREAM_VERA_SANS.bytes, 1),
+        Err(FontError::FaceIndexOutOfRange)
+    ));
+}
+
+#[test]
+fn web_fonts() {
+    let ttf = Font::parse(&include_bytes!("fonts/noto/NotoSansLinearB-Regular.ttf")[..]).unwrap();
+    let woff: &[u8] = include_bytes!("fonts/noto/NotoSansLinearB-Regular.woff");
+    let woff2: &[u8] = include_bytes!("fonts/noto/NotoSansLinearB-Regular.woff2");
+    let text = "𐁉 𐁁𐀓";
+    let font_size = Length::new(15.);
+    let expected = ShapedSegment::naive_shape(text, ttf.clone()).unwrap();
+    for &bytes in &[woff, woff2] {
+        let font = Font::parse(bytes).unwrap();
+        assert_eq!(font.family_name(), ttf.family_name());
+        let segment = ShapedSegment::naive_shape(text, font).unwrap();
+        assert_eq!(
+            segment.advance_width(font_size),
+            expected.advance_width(font_size)
+        );
+    }
+    assert!(matches!(
+        Font::parse(&woff2[..woff2.len() / 2]),
+        Err(FontError::InvalidWoff)
+    ));
+}
+
+#[test]
+fn variations() {
+    let font = vera_with_weight_axis();
+    let wght = VariationAxis {
+        tag: *b"wght",
+        min: 100.,
+        default: 400.,
+        max: 900.,
+    };
+    assert_eq!(font.variation_axes(), [wght]);
+    assert_eq!(font.variation_coordinates(), [400.]);
+    assert!(BITSTREAM_VERA_SANS
+        .get()
+        .unwrap()
+        .variation_axes()
+        .is_empty());
+
+    // Instances are cached, and the default instance is the font itself.
+    assert!(Arc::ptr_eq(&font.instance(&[]).unwrap(), &font));
+    assert!(Arc::ptr_eq(
+        &font.instance(&[(*b"wght", 400.), (*b"wdth", 50.)]).unwrap(),
+        &font
+    ));
+    let black = font.instance(&[(*b"wght", 900.)]).unwrap();
+    assert_eq!(black.variation_coordinates(), [900.]);
+    assert_eq!(black.family_name(), font.family_name());
+    assert!(Arc::ptr_eq(
+        &font
+            .instance(&[(*b"wght", 100.), (*b"wght", 1000.)])
+            .unwrap(),
+        &black
+    ));
+
+    // With a font size of 2048px, one font design unit is one pixel.
+    let width = |font: &Arc<Font>| {
+        ShapedSegment::naive_shape("Vera", font.clone())
+            .unwrap()
+            .advance_width(Length::new(2048.))
+    };
+    let semibold = font.instance(&[(*b"wght", 650.)]).unwrap();
+    let thin = font.instance(&[(*b"wght", 100.)]).unwrap();
+    assert_eq!(width(&black), width(&font) + Length::new(4. * 100.));
+    assert_eq!(width(&semibold), width(&font) + Length::new(4. * 50.));
+    assert_eq!(width(&thin), width(&font));
+
+    // Instances of fonts with CFF outlines can’t be embedded with their variations applied.
+    let cff = include_bytes!("fonts/adobe/FDArrayTest257.otf");
+    let cff = Font::parse(with_table(cff, *b"fvar", &to_bytes(WEIGHT_AXIS))).unwrap();
+    assert_eq!(cff.variation_axes(), [wght]);
+    assert!(Arc::ptr_eq(
+        &cff.instance(&[(*b"wght", 400.)]).unwrap(),
+        &cff
+    ));
+    assert!(matches!(
+        cff.instance(&[(*b"wght", 900.)]),
+        Err(FontError::UnsupportedCffVariations)
+    ));
+}
+
+#[test]
+fn glyph_outlines() {
+    use PathSegment::*;
+    let vera = BITSTREAM_VERA_SANS.get().unwrap();
+    let outline = |ch| vera.glyph_outline(vera.glyph_id(ch).unwrap()).unwrap();
+    let bounding_box = |ch| vera.glyph_bounding_box(vera.glyph_id(ch).unwrap()).unwrap();
+
+    // Vera has 2048 font design units per em.
+    let units = |x: f32, y: f32| point(x / 2048., y / 2048.);
+    assert_eq!(
+        outline('l').segments,
+        [
+            MoveTo(units(193., 1556.)),
+            LineTo(units(377., 1556.)),
+            LineTo(units(377., 0.)),
+            LineTo(units(193., 0.)),
+            Close,
+        ]
+    );
+    assert_eq!(
+        bounding_box('l'),
+        Some(rect(193. / 2048., 0., 184. / 2048., 1556. / 2048.))
+    );
+
+    // A curve, where two consecutive control points imply an on-curve point between them
+    let o = outline('o');
+    assert_eq!(o.segments[0], MoveTo(units(627., 991.)));
+    assert!(matches!(o.segments[1], QuadTo(_, _)));
+    assert_eq!(bounding_box('o'), o.bounding_box());
+
+    // A composite glyph: “e” and an acute accent above it
+    let e = outline('e');
+    let e_acute = outline('é');
+    assert_eq!(e_acute.segments[..e.segments.len()], e.segments[..]);
+    assert!(e_acute.segments.len() > e.segments.len());
+    let accent_top = e_acute.bounding_box().unwrap().max_y();
+    assert!(accent_top > e.bounding_box().unwrap().max_y());
+    assert_eq!(bounding_box('é'), e_acute.bounding_box());
+
+    assert!(outline(' ').segments.is_empty());
+    assert_eq!(bounding_box(' '), None);
+    let missing = GlyphId(vera.glyph_count());
+    assert!(vera.glyph_outline(missing).unwrap().segments.is_empty());
+}
+
+#[test]
+fn cff_glyph_outlines() {
+    use PathSegment::*;
+    let font = Font::parse(&include_bytes!("fonts/adobe/FDArrayTest257.otf")[..]).unwrap();
+    let outline = |ch| font.glyph_outline(font.glyph_id(ch).unwrap()).unwrap();
+    let bounding_box = |ch| font.glyph_bounding_box(font.glyph_id(ch).unwrap()).unwrap();
+
+    // FDArray Test 257 has 1000 font design units per em.
+    // Round to whole units to ignore floating point errors.
+    let round = |p: Point<Em>| point((p.x * 1000.).round(), (p.y * 1000.).round());
+    let in_units = |segments: &[PathSegment]| -> Vec<PathSegment> {
+        segments
+            .iter()
+            .map(|&segment| match segment {
+                MoveTo(p) => MoveTo(round(p)),
+                LineTo(p) => LineTo(round(p)),
+                QuadTo(c, p) => QuadTo(round(c), round(p)),
+                CubicTo(c1, c2, p) => CubicTo(round(c1), round(c2), round(p)),
+                Close => Close,
+            })
+            .collect()
+    };
+
+    // Each glyph shows the hexadecimal code point of its character, “41” for “A”.
+    // Digits are drawn by global subroutines, with hint masks,
+    // and each glyph uses a different font dictionary.
+    let a = outline('A');
+    let one = [
+        MoveTo(point(582., 0.)),
+        LineTo(point(940., 0.)),
+        LineTo(point(940., 32.)),
+        LineTo(point(784., 32.)),
+        LineTo(point(784., 704.)),
+        LineTo(point(754., 704.)),
+        CubicTo(point(722., 686.), point(678., 670.), point(622., 662.)),
+        LineTo(point(622., 636.)),
+        LineTo(point(748., 636.)),
+        LineTo(point(748., 32.)),
+        LineTo(point(582., 32.)),
+        Close,
+    ];
+    assert_eq!(in_units(&a.segments[..one.len()]), one);
+    assert!(a.segments.len() > one.len());
+    assert_eq!(outline('a').segments[..one.len()], a.segments[..one.len()]);
+    let a_box = bounding_box('A').unwrap();
+    assert_eq!(round(a_box.origin), point(34., 0.));
+    assert_eq!(round(a_box.bottom_right()), point(940., 704.));
+    assert_eq!(bounding_box('A'), a.bounding_box());
+
+    // Cubic Bézier curves, in the “0” of “30”
+    let zero = outline('0');
+    assert_eq!(
+        in_units(&zero.segments[..2]),
+        [
+            MoveTo(point(750., -12.)),
+            CubicTo(point(857., -12.), point(934., 113.), point(934., 356.)),
+        ]
+    );
+
+    // .notdef is a box with four triangles in it, all straight lines.
+    let notdef = font.glyph_outline(GlyphId(0)).unwrap();
+    assert_eq!(
+        in_units(&notdef.segments[..5]),
+        [
+            MoveTo(point(100., -120.)),
+            LineTo(point(900., -120.)),
+            LineTo(point(900., 880.)),
+            LineTo(point(100., 880.)),
+            Close,
+        ]
+    );
+    assert_eq!(notdef.segments.len(), 21);
+    let notdef_box = font.glyph_bounding_box(GlyphId(0)).unwrap().unwrap();
+    assert_eq!(round(notdef_box.origin), point(100., -120.));
+    assert_eq!(round(notdef_box.bottom_right()), point(900., 880.));
+
+    let missing = GlyphId(font.glyph_count());
+    assert!(font.glyph_outline(missing).unwrap().segments.is_empty());
+    assert_eq!(font.glyph_bounding_box(missing).unwrap(), None);
+}
+
+#[test]
+fn vertical_metrics() {
+    // With a font size of 2048px, one font design unit is one pixel.
+    let height = |shaper: &dyn Shaper, font: &Arc<Font>| {
+        shaper
+            .shape(
+                "iij",
+                font.clone(),
+                &FontFeatures::default(),
+                Direction::TopToBottom,
+            )
+            .unwrap()
+            .advance_width(Length::new(2048.))
+    };
+    let font = vera_with_vertical_metrics();
+    assert_eq!(height(&NaiveShaper, &font), Length::new(3. * 3072.));
+    assert_eq!(height(&OpenTypeShaper, &font), Length::new(3. * 3072.));
+
+    // Without a `vmtx` table, each glyph advances by one em.
+    let vera = BITSTREAM_VERA_SANS.get().unwrap();
+    assert_eq!(height(&NaiveShaper, &vera), Length::new(3. * 2048.));
+}
+
+#[test]
+fn malformed_optional_tables() {
+    // Only a version number, cut short by the end of the file
+    let truncated = [0, 1, 0, 0];
+    for tag in &[*b"GPOS", *b"GSUB", *b"fvar"] {
+        let font = with_table(BITSTREAM_VERA_SANS.bytes, *tag, &truncated);
+        let font = Font::parse(font).unwrap();
+        assert_eq!(font.family_name(), "Bitstream Vera Sans");
+    }
+}
+
+/// A TrueType collection file with Bitstream Vera Sans and Noto Sans Linear B
+pub(crate) fn vera_and_noto_collection() -> Vec<u8> {
+    let fonts: [&[u8]; 2] = [
+        BITSTREAM_VERA_SANS.bytes,
+        include_bytes!("fonts/noto/NotoSansLinearB-Regular.ttf"),
+    ];
+    // Header: tag, version 1.0, number of fonts, then the offset of each font
+    const HEADER_SIZE: usize = 12;
+    let mut result = b"ttcf".to_vec();
+    for &x in &[0x_0001_0000, fonts.len() as u32] {
+        result.extend_from_slice(&x.to_be_bytes())
+    }
+    let mut font_offset = HEADER_SIZE + 4 * fonts.len();
+    let mut files = Vec::new();
+    for font in &fonts {
+        result.extend_from_slice(&(font_offset as u32).to_be_bytes());
+        // Table offsets are from the start of the collection file.
+        let mut file = font.to_vec();
+        let table_count = u16::from_be_bytes([file[4], file[5]]) as usize;
+        for i in 0..table_count {
+            let offset = HEADER_SIZE + 16 * i + 8;
+            let mut table_offset = [0; 4];
+            table_offset.copy_from_slice(&file[offset..offset + 4]);
+            let table_offset = u32::from_be_bytes(table_offset) + font_offset as u32;
+            file[offset..offset + 4].copy_from_slice(&table_offset.to_be_bytes())
+        }
+        file.resize((file.len() + 3) / 4 * 4, 0);
+        font_offset += file.len();
+        files.push(file)
+    }
+    for file in files {
+        result.extend(file)
+    }
+    result
+}
+
+/// Bitstream Vera Sans with a synthetic `GSUB` table, since none of the test fonts have one:
+///
+/// * `liga`: "fi" and "fl" ligatures, with Vera’s existing U+FB01 and U+FB02 glyphs
+/// * `ss01`: "a" is replaced with the glyph of "A"
+pub(crate) fn vera_with_substitutions() -> Arc<Font> {
+    // Glyph IDs in Vera
+    const UPPER_A: u16 = 36;
+    const A: u16 = 68;
+    const F: u16 = 73;
+    const I: u16 = 76;
+    const L: u16 = 79;
+    const FI: u16 = 192;
+    const FL: u16 = 193;
+    #[rustfmt::skip]
+    let gsub: &[u16] = &[
+        // Header: version 1.0, then offsets to the script, feature, and lookup lists
+        1, 0, 10, 32, 58,
+        // Script list: one record of a tag and an offset
+        1, 0x4446, 0x4C54, 8,
+        // "DFLT" script: offset to the default language system, no other language system
+        4, 0,
+        // Default language system: no required feature, both features
+        0, 0xFFFF, 2, 0, 1,
+        // Feature list: two records of a tag and an offset
+        2, 0x6C69, 0x6761, 14, 0x7373, 0x3031, 20,
+        // Features: no parameters, one lookup index
+        0, 1, 0,
+        0, 1, 1,
+        // Lookup list
+        2, 6, 46,
+        // Lookup 0: ligature substitution, no flags, one subtable
+        4, 0, 1, 8,
+        // Ligature substitution subtable: coverage offset, one ligature set at offset 14
+        1, 8, 1, 14,
+        // Coverage: format 1, one glyph
+        1, 1, F,
+        // Ligature set: two ligatures of 2 components each
+        2, 6, 12,
+        FI, 2, I,
+        FL, 2, L,
+        // Lookup 1: single substitution, no flags, one subtable
+        1, 0, 1, 8,
+        // Single substitution subtable format 1: coverage offset, delta
+        1, 6, UPPER_A.wrapping_sub(A),
+        1, 1, A,
+    ];
+    let gsub: Vec<u8> = gsub.iter().flat_map(|x| x.to_be_bytes().to_vec()).collect();
+    let vera = BITSTREAM_VERA_SANS.get().unwrap();
+    Font::parse(with_table(vera.bytes(), *b"GSUB", &gsub)).unwrap()
+}
+
+/// An `fvar` table with a `wght` axis from 100 to 900, whose default is 400
+#[rustfmt::skip]
+const WEIGHT_AXIS: &[u16] = &[
+    // Header: version 1.0, axes offset, reserved, one axis of 20 bytes, no named instance
+    1, 0, 16, 2, 1, 20, 0, 8,
+    // Axis: "wght", 16.16 fixed-point minimum, default, and maximum, flags, name ID
+    0x7767, 0x6874, 100, 0, 400, 0, 900, 0, 0, 256,
+];
+
+/// Bitstream Vera Sans as a synthetic variable font, since none of the test fonts is one.
+///
+/// It has a `wght` axis from 100 to 900 where only advance widths vary:
+/// each glyph is 100 font design units wider at 900 than at 400 and below.
+pub(crate) fn vera_with_weight_axis() -> Arc<Font> {
+    #[rustfmt::skip]
+    let mut hvar: Vec<u16> = vec![
+        // Header: version 1.0, 32-bit offsets to the item variation store and three mappings
+        1, 0, 0, 20, 0, 0, 0, 0, 0, 0,
+        // Item variation store: format, 32-bit offset to the region list, one data subtable
+        1, 0, 12, 1, 0, 22,
+        // Region list: one axis, one region that peaks at the maximum of the axis
+        1, 1, 0, 0x4000, 0x4000,
+        // Item variation data: one item per glyph, bytes deltas for one region
+        VERA_GLYPH_COUNT, 0, 1, 0,
+    ];
+    hvar.extend((0..VERA_GLYPH_COUNT / 2).map(|_| u16::from_be_bytes([100, 100])));
+    let font = with_table(BITSTREAM_VERA_SANS.bytes, *b"fvar", &to_bytes(WEIGHT_AXIS));
+    let font = with_table(&font, *b"HVAR", &to_bytes(&hvar));
+    Font::parse(font).unwrap()
+}
+
+/// Bitstream Vera Sans with synthetic `vhea` and `vmtx` tables, since none of the test fonts
+/// has vertical metrics.
+///
+/// All glyphs have an advance height of 3072 font design units (1.5 em),
+/// and all top side bearings are zero except 100 for "i".
+pub(crate) fn vera_with_vertical_metrics() -> Arc<Font> {
+    const I: u16 = 76;
+    #[rustfmt::skip]
+    let vhea: &[u16] = &[
+        // Version 1.0, ascent, descent, line gap, maximum advance height,
+        // minimum top and bottom side bearings, maximum extent
+        1, 0, 1024, (-1024_i16) as u16, 0, 3072, 0, 0, 0,
+        // Caret slope rise and run, caret offset, reserved, metric data format
+        0, 1, 0, 0, 0, 0, 0, 0,
+        // One long vertical metrics record
+        1,
+    ];
+    // The only long record is followed by the top side bearings of the other glyphs.
+    let mut vmtx: Vec<u16> = vec![3072, 0];
+    vmtx.extend((1..VERA_GLYPH_COUNT).map(|id| if id == I { 100 } else { 0 }));
+    let font = with_table(BITSTREAM_VERA_SANS.bytes, *b"vhea", &to_bytes(vhea));
+    let font = with_table(&font, *b"vmtx", &to_bytes(&vmtx));
+    Font::parse(font).unwrap()
+}
+
+const VERA_GLYPH_COUNT: u16 = 268;
+
+/// Serialize a font table made of 16-bit big-endian numbers.
+fn to_bytes(table: &[u16]) -> Vec<u8> {
+    table
+        .iter()
+        .flat_map(|x| x.to_be_bytes().to_vec())
+        .collect()
+}
+
+/// Return a copy of a TrueType font file with an additional table at the end.
+fn with_table(font: &[u8], tag: [u8; 4], table: &[u8]) -> Vec<u8> {
+    let u16_at = |offset: usize| u16::from_be_bytes([font[offset], font[offset + 1]]);
+    let u32_at = |offset: usize| {
+        let bytes = [
+            font[offset],
+            font[offset + 1],
+            font[offset + 2],
+            font[offset + 3],
+        ];
+        u32::from_be_bytes(bytes)
+    };
+    const HEADER_SIZE: usize = 12;
+    const ENTRY_SIZE: usize = 16;
+    let table_count = u16_at(4) as usize;
+    let directory_end = HEADER_SIZE + ENTRY_SIZE * table_count;
+    let table_offset = (font.len() + ENTRY_SIZE + 3) / 4 * 4;
+
+    // Each entry is a tag, a checksum, an offset, and a length.
+    let mut entries: Vec<([u8; 4], u32, u32, u32)> = (0..table_count)
+        .map(|i| {
+            let entry = HEADER_SIZE + ENTRY_SIZE * i;
+            let mut tag = [0; 4];
+            tag.copy_from_slice(&font[entry..entry + 4]);
+            let offset = u32_at(entry + 8) + ENTRY_SIZE as u32;
+            (tag, u32_at(entry + 4), offset, u32_at(entry + 12))
+        })
+        .collect();
+    // The checksum is not verified.
+    entries.push((tag, 0, table_offset as u32, table.len() as u32));
+    entries.sort();
+
+    let table_count = entries.len() as u16;
+    let entry_selector = 15 - table_count.leading_zeros() as u16;
+    let search_range = (1 << entry_selector) * ENTRY_SIZE as u16;
+    let mut result = font[..4].to_vec();
+    for &x in &[
+        table_count,
+        search_range,
+        entry_selector,
+        table_count * ENTRY_SIZE as u16 - search_range,
+    ] {
+        result.extend_from_slice(&x.to_be_bytes())
+    }
+    for &(tag, checksum, offset, length) in &entries {
+        result.extend_from_slice(&tag);
+        for &x in &[checksum, offset, length] {
+            result.extend_from_slice(&x.to_be_bytes())
+        }
+    }
+    result.extend_from_slice(&font[directory_end..]);
+    result.resize(table_offset, 0);
+    result.extend_from_slice(table);
+    result
+}
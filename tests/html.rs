@@ -0,0 +1,524 @@
+use lester::PdfDocument;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use victor::dom::{Document, PageSettings};
+use victor::fonts::{Font, FontCollection};
+use victor::primitives::{Length, Size};
+use victor::text::ShapedSegment;
+
+static HTML: &'static str = r#"<!doctype html>
+<style>
+    p { margin-bottom: 10px }
+</style>
+<p>Hello, <em>world</em>!</p>
+<p>Second paragraph</p>
+"#;
+
+/// Pages are A4 with 20mm margins, unless a test overrides that with `@page` rules.
+fn page_settings() -> PageSettings {
+    PageSettings {
+        page_size: Size::new(210., 297.),
+        page_margin: Length::new(20.),
+    }
+}
+
+/// Render a document with Bitstream Vera Sans and return the bytes of the PDF file.
+fn render(html: &str) -> Vec<u8> {
+    Document::parse_html(html.as_bytes())
+        .render_to_pdf(&page_settings())
+        .unwrap()
+        .write_to_pdf_bytes()
+}
+
+fn render_with_fonts(html: &str, fonts: &FontCollection) -> Vec<u8> {
+    Document::parse_html(html.as_bytes())
+        .render_to_pdf_with_fonts(&page_settings(), fonts)
+        .unwrap()
+        .write_to_pdf_bytes()
+}
+
+/// The text of each page
+fn page_texts(pdf_bytes: &[u8]) -> Vec<String> {
+    let doc = PdfDocument::from_bytes(pdf_bytes).unwrap();
+    let texts = doc
+        .pages()
+        .map(|page| page.text().to_str().unwrap().to_owned())
+        .collect();
+    texts
+}
+
+fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+    bytes.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn render_to_pdf() {
+    let pdf_bytes = render(HTML);
+    if env::var("VICTOR_WRITE_TO_TMP").is_ok() {
+        File::create("/tmp/html.pdf")
+            .unwrap()
+            .write_all(&pdf_bytes)
+            .unwrap();
+    }
+    assert_eq!(page_texts(&pdf_bytes), ["Hello, world!\nSecond paragraph"]);
+}
+
+#[test]
+fn backgrounds() {
+    let html = r#"
+        <style>
+            @page { size: 300px 200px; margin: 10px }
+            body { margin: 0 }
+            p { margin: 0; height: 20px; background-color: #00f }
+        </style>
+        <p></p>
+    "#;
+    let pdf_bytes = render(html);
+    // A blue rectangle filled over the border box, in CSS px from the top-left of the page
+    assert!(contains(
+        &pdf_bytes,
+        b"0.0 0.0 1.0 rg\n10.0 10.0 280.0 20.0 re\nf\n"
+    ));
+}
+
+#[test]
+fn block_layout() {
+    let html = r#"
+        <style>
+            @page { size: 300px 200px; margin: 0 }
+            body { margin: 0 }
+            div { height: 10px; background-color: #00f }
+            .first { margin: 5px 20px 30px 10px }
+            .second { margin: 20px 0 0 40px; width: 100px }
+            .third { margin-top: -10px }
+            .fourth { margin-left: 20px; padding: 0 200px }
+        </style>
+        <div class=first></div>
+        <div class=second></div>
+        <div class=third></div>
+        <div class=fourth></div>
+    "#;
+    let pdf_bytes = render(html);
+    // Each block is below the previous one. Adjoining vertical margins collapse:
+    // 30px and 20px into 30px, then 0 and -10px into -10px.
+    // Padding wider than the containing block makes the content width zero, not negative.
+    let rectangles: &[&[u8]] = &[
+        b"10.0 5.0 270.0 10.0 re\nf\n",
+        b"40.0 45.0 100.0 10.0 re\nf\n",
+        b"0.0 45.0 300.0 10.0 re\nf\n",
+        b"20.0 55.0 400.0 10.0 re\nf\n",
+    ];
+    for rectangle in rectangles {
+        assert!(contains(&pdf_bytes, rectangle));
+    }
+}
+
+#[test]
+fn inline_layout() {
+    // At 16px, “aaa bbb” is about 65px wide and “aaa bbb ccc” about 97px.
+    let html = r#"
+        <style>
+            @page { size: 80px 200px; margin: 0 }
+            body { margin: 0 }
+        </style>
+        <p>aaa <em>bbb ccc</em> ddd</p>
+        <p>
+            a
+            b   <em> c</em></p>
+    "#;
+    // Lines break between words, including inside an inline box.
+    // Consecutive white space collapses into one space, even across inline boxes,
+    // and is removed at the start of a paragraph.
+    assert_eq!(page_texts(&render(html)), ["aaa bbb\nccc ddd\na b c"]);
+}
+
+#[test]
+fn pages() {
+    let html = r#"
+        <style>
+            @page { size: 300px 150px; margin: 25px }
+            p { margin-bottom: 30px }
+            h1 { display: block; break-before: page }
+        </style>
+        <p>First</p>
+        <p>Second</p>
+        <p>Third</p>
+        <h1>Title</h1>
+        <p>Fourth</p>
+    "#;
+    let pdf_bytes = render(html);
+    assert_eq!(
+        page_texts(&pdf_bytes),
+        ["First\nSecond", "Third", "Title\nFourth"]
+    );
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].size_in_css_px(), (300., 150.));
+}
+
+#[test]
+fn margin_boxes() {
+    let html = r#"
+        <style>
+            @page {
+                size: 300px 150px;
+                margin: 25px;
+                @top-center { content: "Report" }
+                @bottom-right { content: "Page " counter(page) " of " counter(pages) }
+            }
+            h1 { display: block; break-before: page }
+        </style>
+        <p>First</p>
+        <h1>Second</h1>
+    "#;
+    let texts = page_texts(&render(html));
+    assert_eq!(texts.len(), 2);
+    assert!(texts[0].contains("Report"));
+    assert!(texts[0].contains("First"));
+    assert!(texts[0].contains("Page 1 of 2"));
+    assert!(texts[1].contains("Report"));
+    assert!(texts[1].contains("Second"));
+    assert!(texts[1].contains("Page 2 of 2"));
+}
+
+#[test]
+fn relative_units() {
+    // With `font-size: 2em` (32px), “Hello world” is about 170px wide.
+    // It fits in the 200px page area, but not in the 100px left by `margin-right: 50%`.
+    let html = r#"
+        <style>
+            @page { size: 200px 400px; margin: 0 }
+            p { font-size: 2em; margin: 0 50% 0 0 }
+        </style>
+        <p>Hello world</p>
+    "#;
+    assert_eq!(page_texts(&render(html)), ["Hello\nworld"]);
+}
+
+#[test]
+fn calc() {
+    // `calc(1em + 100%)` is 32px, as in `relative_units`.
+    // `clamp()` resolves to 100px, the 150px maximum is larger than 50% of the page width.
+    let html = r#"
+        <style>
+            @page { size: 200px 400px; margin: 0 }
+            p { font-size: calc(1em + 100%); margin: 0 clamp(0px, 50%, 150px) 0 0 }
+        </style>
+        <p>Hello world</p>
+    "#;
+    assert_eq!(page_texts(&render(html)), ["Hello\nworld"]);
+}
+
+#[test]
+fn colors() {
+    // The text is painted in the same color as the background, so it doesn’t change pixels.
+    let html = r#"
+        <style>
+            @page { size: 4px 8px; margin: 0 }
+            body { color: hsl(240, 100%, 50%) }
+            p { font-size: 2px; background-color: currentColor }
+            em { color: transparent }
+        </style>
+        <p>x<em>y</em></p>
+    "#;
+    let pdf_bytes = render(html);
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages.len(), 1);
+    let mut surface = pages[0].render().unwrap();
+    let pixels = surface.pixels();
+    const BLUE: u32 = 0xFF00_00FF;
+    const ____: u32 = 0x0000_0000;
+    // The paragraph is about 2.3px tall.
+    assert_pixels_eq!(&pixels.buffer[..4], &[BLUE, BLUE, BLUE, BLUE][..]);
+    assert_pixels_eq!(&pixels.buffer[28..], &[____, ____, ____, ____][..]);
+}
+
+#[test]
+fn borders_and_padding() {
+    let html = r#"
+        <style>
+            @page { size: 4px 4px; margin: 0 }
+            body { margin: 0 }
+            p { margin: 0; padding: 1px 0; border: 1px solid #00f; border-left-style: none }
+        </style>
+        <p></p>
+    "#;
+    let pdf_bytes = render(html);
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages.len(), 1);
+    let mut surface = pages[0].render().unwrap();
+    const BLUE: u32 = 0xFF00_00FF;
+    const ____: u32 = 0x0000_0000;
+    #[rustfmt::skip]
+    assert_pixels_eq!(
+        surface.pixels().buffer,
+        &[
+            BLUE, BLUE, BLUE, BLUE,
+            ____, ____, ____, BLUE,
+            ____, ____, ____, BLUE,
+            BLUE, BLUE, BLUE, BLUE,
+        ]
+    );
+}
+
+#[test]
+fn sizing() {
+    // With `box-sizing: content-box`, the border box would be 4px wide and fill the page.
+    let html = r#"
+        <style>
+            @page { size: 4px 2px; margin: 0 }
+            body { margin: 0 }
+            p {
+                box-sizing: border-box; width: 50%; height: 2px;
+                padding: 0 1px; margin: 0 auto; background-color: #00f;
+            }
+        </style>
+        <p></p>
+    "#;
+    let pdf_bytes = render(html);
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages.len(), 1);
+    let mut surface = pages[0].render().unwrap();
+    const BLUE: u32 = 0xFF00_00FF;
+    const ____: u32 = 0x0000_0000;
+    #[rustfmt::skip]
+    assert_pixels_eq!(
+        surface.pixels().buffer,
+        &[
+            ____, BLUE, BLUE, ____,
+            ____, BLUE, BLUE, ____,
+        ]
+    );
+}
+
+#[test]
+fn font_matching() {
+    let html = r#"
+        <style>
+            @page { size: 600px 100px; margin: 0 }
+            .light { font-weight: 300 }
+        </style>
+        <p>Regular <b>bold</b> <em>oblique <strong>bold oblique</strong></em>
+        <span class=light>light</span>
+    "#;
+    let pdf_bytes = render(html);
+    assert_eq!(
+        page_texts(&pdf_bytes),
+        ["Regular bold oblique bold oblique light"]
+    );
+    for face in &["Roman", "Bold", "Oblique", "BoldOblique"] {
+        let base_font = format!("+BitstreamVeraSans-{} ", face);
+        assert!(contains(&pdf_bytes, base_font.as_bytes()));
+    }
+}
+
+#[test]
+fn font_collection() {
+    let html = r#"
+        <style>
+            @page { size: 300px 100px; margin: 0 }
+            .ahem { font-family: Ahem, sans-serif }
+            .fallback { font-family: "Not a font", sans-serif }
+        </style>
+        <p class=ahem>Ahem</p>
+        <p class=fallback>Vera</p>
+    "#;
+    let mut fonts = FontCollection::bitstream_vera_sans().unwrap();
+    fonts.add_directory("fonts/ahem").unwrap();
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    for postscript_name in &["Ahem", "BitstreamVeraSans-Roman"] {
+        let base_font = format!("+{} ", postscript_name);
+        assert!(contains(&pdf_bytes, base_font.as_bytes()));
+    }
+}
+
+#[test]
+fn font_face() {
+    // The second face is used for bold text because of its `font-weight` descriptor,
+    // even though the font file itself is not bold.
+    let html = r#"
+        <style>
+            @page { size: 300px 100px; margin: 0 }
+            @font-face { font-family: Brand; src: url(missing.ttf), url(ahem/ahem.ttf) }
+            @font-face {
+                font-family: Brand;
+                src: url(noto/NotoSansLinearB-Regular.ttf) format("truetype");
+                font-weight: bold;
+            }
+            p { font-family: Brand }
+        </style>
+        <p>Regular <b>Bold</b></p>
+    "#;
+    let mut document = Document::parse_html(html.as_bytes());
+    document.set_base_path("fonts");
+    let pdf_bytes = document
+        .render_to_pdf(&page_settings())
+        .unwrap()
+        .write_to_pdf_bytes();
+    for postscript_name in &["Ahem", "NotoSansLinearB-Regular"] {
+        let base_font = format!("+{} ", postscript_name);
+        assert!(contains(&pdf_bytes, base_font.as_bytes()));
+    }
+    assert!(!contains(&pdf_bytes, b"BitstreamVeraSans"));
+}
+
+#[test]
+fn font_fallback() {
+    // Bitstream Vera Sans does not support Linear B, so Noto Sans Linear B is used for it
+    // even though it is not in `font-family`.
+    let html = r#"
+        <style>
+            @page { size: 300px 100px; margin: 0 }
+        </style>
+        <p>Linear B: 𐁉 𐁁𐀓</p>
+    "#;
+    let mut fonts = FontCollection::bitstream_vera_sans().unwrap();
+    fonts.add_directory("fonts/noto").unwrap();
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    assert_eq!(page_texts(&pdf_bytes), ["Linear B: 𐁉 𐁁𐀓"]);
+    for postscript_name in &["BitstreamVeraSans-Roman", "NotoSansLinearB-Regular"] {
+        let base_font = format!("+{} ", postscript_name);
+        assert!(contains(&pdf_bytes, base_font.as_bytes()));
+    }
+}
+
+#[test]
+fn font_features() {
+    let html = r#"
+        <style>
+            @page { size: 300px 200px; margin: 0 }
+            .none { font-variant-ligatures: none }
+            .settings { font-feature-settings: "ss01", "liga" off }
+        </style>
+        <p>fit a</p>
+        <p class=none>fit a</p>
+        <p class=settings>fit a</p>
+    "#;
+    let mut fonts = FontCollection::new();
+    fonts.add(crate::fonts::vera_with_substitutions());
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    assert_eq!(page_texts(&pdf_bytes), ["fit a\nfit a\nfit a"]);
+    // Glyph IDs for "fi", "f", "i", and "A"
+    for glyph_codes in &[&b"<00C0"[..], b"<0049004C", b"0024>"] {
+        assert!(contains(&pdf_bytes, glyph_codes));
+    }
+}
+
+#[test]
+fn font_variations() {
+    let html = r#"
+        <style>
+            @page { size: 300px 200px; margin: 0 }
+            .settings { font-variation-settings: "wght" 900 }
+        </style>
+        <p>Vera <b>Vera</b> <span class=settings>Vera</span> <b class=settings>Vera</b>
+    "#;
+    let mut fonts = FontCollection::new();
+    fonts.add(crate::fonts::vera_with_weight_axis());
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    assert_eq!(page_texts(&pdf_bytes), ["Vera Vera Vera Vera"]);
+
+    // One instance each for weights 400 and 700, and one for 900 from `font-variation-settings`
+    // which takes precedence over `font-weight`.
+    let needle = b"+BitstreamVeraSans-Roman ";
+    let mut subset_tags: Vec<&[u8]> = pdf_bytes
+        .windows(needle.len())
+        .enumerate()
+        .filter(|&(_, window)| window == needle)
+        .map(|(position, _)| &pdf_bytes[position - 6..position])
+        .collect();
+    subset_tags.sort();
+    subset_tags.dedup();
+    assert_eq!(subset_tags.len(), 3);
+}
+
+#[test]
+fn bidi() {
+    let html = r#"
+        <style>
+            @page { size: 300px 200px; margin: 0 }
+            p { margin: 0 }
+        </style>
+        <p>abc بيت def</p>
+        <p dir=rtl>بيت abc</p>
+        <p dir=rtl>abc <span dir=ltr>def</span></p>
+        <p>abc <bdo dir=rtl>def</bdo></p>
+    "#;
+    let mut fonts = FontCollection::new();
+    fonts.add_directory("fonts/dejavu").unwrap();
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    // Runs of glyphs in visual order, from left to right
+    let runs: &[&[u8]] = &[
+        b"<0044004500460003> Tj",
+        b"<0C510CAF0C4C> Tj",
+        b"<0003004700480049> Tj",
+        b"<004400450046> Tj",
+        b"<00030C510CAF0C4C> Tj",
+        // The isolated inline box is reordered as a whole
+        b"<004700480049> Tj",
+        b"<0003> Tj",
+        b"<004400450046> Tj",
+        // `<bdo>` overrides the direction of its content
+        b"<0044004500460003> Tj",
+        b"<004900480047> Tj",
+    ];
+    let mut rest = &pdf_bytes[..];
+    for run in runs {
+        let position = rest
+            .windows(run.len())
+            .position(|window| window == *run)
+            .unwrap();
+        rest = &rest[position + run.len()..]
+    }
+
+    // Right-to-left paragraphs are aligned to the right:
+    // the second line starts at the page width minus its own width.
+    let dejavu = Font::parse(&include_bytes!("fonts/dejavu/DejaVuSansMono.ttf")[..]).unwrap();
+    let width = ShapedSegment::naive_shape("بيت abc", dejavu)
+        .unwrap()
+        .advance_width(Length::new(16.))
+        .get();
+    let origins = crate::pdf::text_origins(&pdf_bytes);
+    let &(x, _) = origins.iter().find(|&&(_, y)| y != origins[0].1).unwrap();
+    assert!((x - (300. - width)).abs() < 0.001);
+}
+
+#[test]
+fn vertical_rl_lines() {
+    // `writing-mode: vertical-rl` turns the lines of a paragraph into columns,
+    // while the paragraph itself is still stacked below the previous one.
+    let html = r#"
+        <style>
+            @page { size: 300px 240px; margin: 0 }
+            p { margin: 0 }
+            .vertical { writing-mode: vertical-rl }
+        </style>
+        <p>Horizontal</p>
+        <p class=vertical>Ver<em>tical </em>text</p>
+    "#;
+    let mut fonts = FontCollection::new();
+    fonts.add(crate::fonts::vera_with_vertical_metrics());
+    let pdf_bytes = render_with_fonts(html, &fonts);
+    // The font is embedded once for each writing mode.
+    for encoding in &[&b"/Encoding /Identity-H"[..], b"/Encoding /Identity-V"] {
+        assert!(contains(&pdf_bytes, encoding));
+    }
+    assert_eq!(page_texts(&pdf_bytes).len(), 1);
+
+    // Glyphs advance by 1.5 em, so a 240px column fits "Vertical " but not "Vertical text".
+    // Each text fragment starts with a text matrix. Its origin is in CSS px,
+    // so moving down the page increases y.
+    let origins = crate::pdf::text_origins(&pdf_bytes);
+    assert_eq!(origins.len(), 4);
+    let (ver, tical, text) = (origins[1], origins[2], origins[3]);
+    // "tical " continues "Ver" further down the same column.
+    assert_eq!(tical.0, ver.0);
+    assert!(tical.1 > ver.1);
+    // "text" starts the next column at the top, to the left of the first one.
+    assert!(text.0 < ver.0);
+    assert_eq!(text.1, ver.1);
+}
@@ -8,4 +8,8 @@ extern crate victor;
 #[cfg(test)]
 mod alice;
 #[cfg(test)]
+mod fonts;
+#[cfg(test)]
+mod html;
+#[cfg(test)]
 mod pdf;
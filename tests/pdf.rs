@@ -2,13 +2,16 @@ use lester::{Backdrop, PdfDocument, RenderOptions};
 use std::env;
 use std::fs::File;
 use std::io::Write;
-use victor::fonts::{FontError, LazyStaticFont, BITSTREAM_VERA_SANS};
+use victor::fonts::{Font, FontError, FontFeatures, LazyStaticFont, BITSTREAM_VERA_SANS};
 use victor::pdf::Document;
 use victor::primitives::{point, rect, Length, Size, TextRun, RGBA};
-use victor::text::ShapedSegment;
+use victor::text::{Direction, NaiveShaper, OpenTypeShaper, ShapedSegment, Shaper};
+use victor::text_plain;
 
 static AHEM: LazyStaticFont = include_font!("fonts/ahem/ahem.ttf");
 static NOTO: LazyStaticFont = include_font!("fonts/noto/NotoSansLinearB-Regular.ttf");
+static DEJAVU_MONO: LazyStaticFont = include_font!("fonts/dejavu/DejaVuSansMono.ttf");
+static FDARRAY_TEST: LazyStaticFont = include_font!("fonts/adobe/FDArrayTest257.otf");
 
 fn doc() -> Result<Vec<u8>, FontError> {
     let vera = BITSTREAM_VERA_SANS.get()?;
@@ -128,3 +131,398 @@ fn pdf() {
 
     assert!(pdf_bytes == include_bytes!("expected.pdf").as_ref());
 }
+
+#[test]
+fn font_fallback() {
+    let fonts = [BITSTREAM_VERA_SANS.get().unwrap(), NOTO.get().unwrap()];
+    let segments = ShapedSegment::naive_shape_with_fallback("Linear B: 𐁉 𐁁𐀓", &fonts).unwrap();
+    assert_eq!(segments.len(), 4);
+
+    let mut doc = Document::new();
+    {
+        let mut page = doc.add_page(Size::new(200., 50.));
+        let font_size = Length::new(15.);
+        let mut origin = point(10., 20.);
+        for segment in segments {
+            let advance = segment.advance_width(font_size);
+            page.show_text(&TextRun {
+                segment,
+                font_size,
+                origin,
+            })
+            .unwrap();
+            origin.x += advance.get();
+        }
+    }
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "Linear B: 𐁉 𐁁𐀓");
+}
+
+#[test]
+fn subset() {
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("Ångström", BITSTREAM_VERA_SANS.get().unwrap())
+                .unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let find = |needle: &[u8]| {
+        pdf_bytes
+            .windows(needle.len())
+            .position(|window| window == needle)
+            .unwrap()
+            + needle.len()
+    };
+
+    // The name is prefixed with a tag of six upper-case letters that identifies the subset.
+    let name = &pdf_bytes[find(b"/BaseFont /")..];
+    assert!(name[..6].iter().all(u8::is_ascii_uppercase));
+    assert!(name[6..].starts_with(b"+BitstreamVeraSans-Roman "));
+
+    let length = &pdf_bytes[find(b"/Length1 ")..];
+    let length = &length[..length.iter().position(|&b| b == b' ').unwrap()];
+    let length: usize = std::str::from_utf8(length).unwrap().parse().unwrap();
+    let whole_font = include_bytes!("../victor/fonts/vera/Vera.ttf");
+    assert!(length < whole_font.len() / 4);
+
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "Ångström");
+}
+
+#[test]
+fn font_collection_face() {
+    let noto = Font::parse_face(crate::fonts::vera_and_noto_collection(), 1).unwrap();
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("𐁉 𐁁𐀓", noto).unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    // TrueType outlines don’t require a newer PDF version.
+    let version = b"/Version";
+    assert!(!pdf_bytes
+        .windows(version.len())
+        .any(|window| window == &version[..]));
+    // The embedded font is a subset of the face, not the collection file.
+    let collection = b"ttcf";
+    assert!(!pdf_bytes
+        .windows(collection.len())
+        .any(|window| window == &collection[..]));
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "𐁉 𐁁𐀓");
+}
+
+#[test]
+fn cff_outlines() {
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("Aa", FDARRAY_TEST.get().unwrap()).unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let contains = |bytes: &[u8]| pdf_bytes.windows(bytes.len()).any(|window| window == bytes);
+    // The OpenType font file is embedded as-is, which requires PDF 1.6.
+    assert!(pdf_bytes.starts_with(b"%PDF-1.5\n"));
+    assert!(contains(b"/Version /1.6"));
+    assert!(contains(b"/FontFile3"));
+    assert!(contains(b"/Subtype /OpenType"));
+    assert!(contains(b"/Subtype /CIDFontType0"));
+    assert!(!contains(b"/FontFile2"));
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "Aa");
+}
+
+#[test]
+fn cff_cids() {
+    // In this CID-keyed font each glyph ID is equal to its CID.
+    // Change its charset so that CIDs start at 1001 instead of 1.
+    let mut bytes = include_bytes!("fonts/adobe/FDArrayTest257.otf").to_vec();
+    let u32_at = |bytes: &[u8], offset: usize| {
+        u32::from_be_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]) as usize
+    };
+    let table_count = u16::from_be_bytes([bytes[4], bytes[5]]) as usize;
+    let cff = (0..table_count)
+        .map(|index| 12 + 16 * index)
+        .find(|&record| &bytes[record..record + 4] == b"CFF ")
+        .map(|record| u32_at(&bytes, record + 8))
+        .unwrap();
+    // Format 1: one range of CIDs that starts at 1, with 255 more glyphs
+    let charset = cff + 7900;
+    assert_eq!(bytes[charset..charset + 4], [1, 0, 1, 255]);
+    bytes[charset + 1..charset + 3].copy_from_slice(&1001_u16.to_be_bytes());
+    let font = Font::parse(bytes).unwrap();
+
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("Aa", font).unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let contains = |bytes: &[u8]| pdf_bytes.windows(bytes.len()).any(|window| window == bytes);
+    // Glyph IDs 66 and 98 are shown as CIDs 1066 and 1098,
+    // both in the content stream and in the `ToUnicode` map.
+    assert!(contains(b"<042A044A> Tj"));
+    assert!(contains(b"<042a> <0041>\n<044a> <0061>\n"));
+    assert!(!contains(b"<00420062> Tj"));
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "Aa");
+}
+
+#[test]
+fn web_font() {
+    let woff2 = include_bytes!("fonts/noto/NotoSansLinearB-Regular.woff2");
+    let noto = Font::parse(&woff2[..]).unwrap();
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("𐁉 𐁁𐀓", noto).unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "𐁉 𐁁𐀓");
+}
+
+#[test]
+fn glyph_widths() {
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("iiij() mn", BITSTREAM_VERA_SANS.get().unwrap())
+                .unwrap(),
+            font_size: Length::new(15.),
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    // "i" and "j" have the default width. Parentheses are a range with the same width,
+    // the space is alone, and "m" and "n" are a list.
+    let widths = b"/DW 277 /W [3 [317] 11 12 390 80 [974 633]]";
+    assert!(pdf_bytes
+        .windows(widths.len())
+        .any(|window| window == &widths[..]));
+}
+
+#[test]
+fn vertical_text() {
+    let font = crate::fonts::vera_with_vertical_metrics();
+    let mut doc = Document::new();
+    doc.add_page(Size::new(50., 100.))
+        .show_text(&TextRun {
+            segment: NaiveShaper
+                .shape(
+                    "iij",
+                    font,
+                    &FontFeatures::default(),
+                    Direction::TopToBottom,
+                )
+                .unwrap(),
+            font_size: Length::new(15.),
+            origin: point(25., 10.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    let contains = |bytes: &[u8]| pdf_bytes.windows(bytes.len()).any(|window| window == bytes);
+    assert!(contains(b"/Encoding /Identity-V"));
+    // The vertical origin of "i" and "j" is at half their width, and above the top of
+    // their bounding box by their top side bearing.
+    // "i" has the default metrics, and "j" is in a list.
+    assert!(contains(b"/DW2 [808 -1500] /W2 [77 [-1500 138 759]]"));
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "iij");
+}
+
+#[test]
+fn kerning() {
+    let vera = BITSTREAM_VERA_SANS.get().unwrap();
+    let font_size = Length::new(15.);
+    let width = |text| {
+        ShapedSegment::naive_shape(text, vera.clone())
+            .unwrap()
+            .advance_width(font_size)
+            .get()
+    };
+    // Vera’s `kern` table has a pair for "AV", but not for "AX".
+    assert!(width("AV") < width("A") + width("V"));
+    assert_eq!(width("AX"), width("A") + width("X"));
+
+    let mut doc = Document::new();
+    doc.add_page(Size::new(100., 50.))
+        .show_text(&TextRun {
+            segment: ShapedSegment::naive_shape("AVA", vera.clone()).unwrap(),
+            font_size,
+            origin: point(10., 20.),
+        })
+        .unwrap();
+    let pdf_bytes = doc.write_to_pdf_bytes();
+    assert!(pdf_bytes.windows(4).any(|window| window == b"] TJ"));
+    let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+    let pages: Vec<_> = doc.pages().collect();
+    assert_eq!(pages[0].text().to_str().unwrap(), "AVA");
+}
+
+#[test]
+fn substitutions() {
+    let font = crate::fonts::vera_with_substitutions();
+    for shaper in &[&NaiveShaper as &dyn Shaper, &OpenTypeShaper] {
+        let render = |features: &FontFeatures| {
+            let mut doc = Document::new();
+            doc.add_page(Size::new(100., 50.))
+                .show_text(&TextRun {
+                    segment: shaper
+                        .shape("fit a", font.clone(), features, Direction::LeftToRight)
+                        .unwrap(),
+                    font_size: Length::new(15.),
+                    origin: point(10., 20.),
+                })
+                .unwrap();
+            doc.write_to_pdf_bytes()
+        };
+        let check = |features: &FontFeatures, glyph_codes: &[u8]| {
+            let pdf_bytes = render(features);
+            assert!(pdf_bytes
+                .windows(glyph_codes.len())
+                .any(|window| window == glyph_codes));
+            // Text extraction uses the source text of substituted glyphs, through `ToUnicode`.
+            let doc = PdfDocument::from_bytes(&pdf_bytes).unwrap();
+            let pages: Vec<_> = doc.pages().collect();
+            assert_eq!(pages[0].text().to_str().unwrap(), "fit a");
+        };
+        // "fi" is one glyph, since `liga` is enabled by default.
+        check(&FontFeatures::default(), b"<00C0005700030044> Tj");
+
+        let mut features = FontFeatures::default();
+        features.set(*b"liga", 0);
+        check(&features, b"<0049004C005700030044> Tj");
+
+        // "a" is shown with the glyph of "A"
+        let mut features = FontFeatures::none();
+        features.set(*b"ss01", 1);
+        check(&features, b"<0049004C005700030024> Tj");
+    }
+}
+
+#[test]
+fn complex_script() {
+    let font = DEJAVU_MONO.get().unwrap();
+    let render = |shaper: &dyn Shaper| {
+        let mut doc = Document::new();
+        doc.add_page(Size::new(100., 50.))
+            .show_text(&TextRun {
+                // “House”, with the letters beh, yeh, and teh
+                segment: shaper
+                    .shape(
+                        "بيت",
+                        font.clone(),
+                        &FontFeatures::default(),
+                        Direction::RightToLeft,
+                    )
+                    .unwrap(),
+                font_size: Length::new(15.),
+                origin: point(10., 20.),
+            })
+            .unwrap();
+        doc.write_to_pdf_bytes()
+    };
+    let contains = |pdf_bytes: &[u8], glyph_codes: &[u8]| {
+        pdf_bytes
+            .windows(glyph_codes.len())
+            .any(|window| window == glyph_codes)
+    };
+    // The isolated forms of each letter from the character map, in reverse order
+    assert!(contains(&render(&NaiveShaper), b"<0460047B045E> Tj"));
+    // Final teh, medial yeh, and initial beh: the word reads from right to left.
+    assert!(contains(&render(&OpenTypeShaper), b"<0C510CAF0C4C> Tj"));
+}
+
+#[test]
+fn bidi() {
+    let style = text_plain::Style {
+        page_size: Size::new(100., 100.),
+        page_margin: Length::new(10.),
+        font: DEJAVU_MONO.get().unwrap(),
+        font_size: Length::new(12.),
+        line_height: 1.5,
+        justify: false,
+    };
+    let pdf_bytes = text_plain::layout("abc بيت def\nبيت abc\n", &style)
+        .unwrap()
+        .write_to_pdf_bytes();
+    // Runs of glyphs in visual order, from left to right
+    let runs: &[&[u8]] = &[
+        // Left-to-right paragraph: "abc ", "بيت", " ", and "def"
+        b"<0044004500460003> Tj",
+        b"<0C510CAF0C4C> Tj",
+        b"<0003> Tj",
+        b"<004700480049> Tj",
+        // Right-to-left paragraph: "abc", then " بيت"
+        b"<004400450046> Tj",
+        b"<00030C510CAF0C4C> Tj",
+    ];
+    let mut rest = &pdf_bytes[..];
+    for run in runs {
+        let position = rest
+            .windows(run.len())
+            .position(|window| window == *run)
+            .unwrap();
+        rest = &rest[position + run.len()..]
+    }
+
+    // The right-to-left paragraph is aligned to the right edge of the content area,
+    // 90mm from the left of the page: its line starts that far minus its own width.
+    let right = 90. * 96. / 25.4;
+    let width = ShapedSegment::naive_shape("abc بيت", style.font.clone())
+        .unwrap()
+        .advance_width(style.font_size)
+        .get();
+    let origins = text_origins(&pdf_bytes);
+    let &(x, _) = origins.iter().find(|&&(_, y)| y != origins[0].1).unwrap();
+    assert!((x - (right - width)).abs() < 0.001);
+}
+
+/// The origin of each run of glyphs, from the text matrix operators in content streams
+pub(crate) fn text_origins(pdf_bytes: &[u8]) -> Vec<(f32, f32)> {
+    pdf_bytes
+        .split(|&byte| byte == b'\n')
+        .filter_map(|line| {
+            let line = std::str::from_utf8(line).ok()?;
+            if !line.ends_with(" Tm") {
+                return None
+            }
+            let operands = line[..line.len() - " Tm".len()]
+                .split(' ')
+                .map(|operand| operand.parse())
+                .collect::<Result<Vec<f32>, _>>()
+                .ok()?;
+            Some((operands[4], operands[5]))
+        })
+        .collect()
+}
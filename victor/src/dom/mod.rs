@@ -2,15 +2,18 @@
 
 mod html;
 
+pub use crate::layout::PageSettings;
 use crate::style::StyleSetBuilder;
 use html5ever::tendril::StrTendril;
 use html5ever::{Attribute, ExpandedName, LocalName, QualName};
 use std::borrow::Cow;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 pub struct Document {
     nodes: Vec<Node>,
     style_elements: Vec<NodeId>,
+    base_path: Option<PathBuf>,
 }
 
 pub struct Node {
@@ -34,9 +37,21 @@ impl Document {
         Document {
             nodes: vec![dummy, document_node],
             style_elements: Vec::new(),
+            base_path: None,
         }
     }
 
+    /// The directory that relative URLs, such as in `@font-face` rules, are resolved against.
+    ///
+    /// `None` (the default) means the current directory.
+    pub fn base_path(&self) -> Option<&Path> {
+        self.base_path.as_ref().map(|path| &**path)
+    }
+
+    pub fn set_base_path<P: Into<PathBuf>>(&mut self, base_path: P) {
+        self.base_path = Some(base_path.into())
+    }
+
     fn document_node_id() -> NodeId {
         NodeId(std::num::NonZeroUsize::new(1).unwrap())
     }
@@ -0,0 +1,700 @@
+//! Glyph outlines in the `CFF ` table of OpenType fonts.
+//!
+//! <https://www.adobe.com/content/dam/acom/en/devnet/font/pdfs/5176.CFF.pdf>
+//! <https://www.adobe.com/content/dam/acom/en/devnet/font/pdfs/5177.Type2.pdf>
+
+use crate::fonts::outline::{PathBuilder, MAX_NESTING_DEPTH};
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::{Font, FontError, GlyphId};
+
+// DICT operators, with those after the escape byte 12 as `12 << 8 | b1`
+const CHARSET: u16 = 15;
+const CHAR_STRINGS: u16 = 17;
+const PRIVATE: u16 = 18;
+const SUBRS: u16 = 19;
+const ROS: u16 = 12 << 8 | 30;
+const FD_ARRAY: u16 = 12 << 8 | 36;
+const FD_SELECT: u16 = 12 << 8 | 37;
+
+// Charstring operators
+const HSTEM: u8 = 1;
+const VSTEM: u8 = 3;
+const VMOVETO: u8 = 4;
+const RLINETO: u8 = 5;
+const HLINETO: u8 = 6;
+const VLINETO: u8 = 7;
+const RRCURVETO: u8 = 8;
+const CALLSUBR: u8 = 10;
+const RETURN: u8 = 11;
+const ESCAPE: u8 = 12;
+const ENDCHAR: u8 = 14;
+const HSTEMHM: u8 = 18;
+const HINTMASK: u8 = 19;
+const CNTRMASK: u8 = 20;
+const RMOVETO: u8 = 21;
+const HMOVETO: u8 = 22;
+const VSTEMHM: u8 = 23;
+const RCURVELINE: u8 = 24;
+const RLINECURVE: u8 = 25;
+const VVCURVETO: u8 = 26;
+const HHCURVETO: u8 = 27;
+const SHORTINT: u8 = 28;
+const CALLGSUBR: u8 = 29;
+const VHCURVETO: u8 = 30;
+const HVCURVETO: u8 = 31;
+const FIXED: u8 = 255;
+
+// Charstring operators after the escape byte
+const HFLEX: u8 = 34;
+const FLEX: u8 = 35;
+const HFLEX1: u8 = 36;
+const FLEX1: u8 = 37;
+
+/// The maximum number of arguments on the charstring stack
+const MAX_STACK_SIZE: usize = 48;
+
+impl Font {
+    /// Add the outline of a glyph from its Type 2 charstring.
+    pub(in crate::fonts) fn cff_outline(
+        &self,
+        GlyphId(id): GlyphId,
+        builder: &mut PathBuilder,
+    ) -> Result<(), FontError> {
+        let bytes = self.bytes();
+        let (table, top_dict, global_subroutines) = self.cff_top_dict()?;
+        let char_strings = match top_dict.get(CHAR_STRINGS) {
+            Some(&[offset]) => Index::parse(bytes, table.offset(to_offset(bytes, offset)?))?,
+            _ => return Err(FontError::InvalidCff),
+        };
+
+        // Each glyph of a CID-keyed font uses the private DICT of one of several font DICTs
+        let font_dict = match top_dict.get(FD_ARRAY) {
+            Some(&[fd_array]) => {
+                let fd_select = match top_dict.get(FD_SELECT) {
+                    Some(&[offset]) => table.offset(to_offset(bytes, offset)?),
+                    _ => return Err(FontError::InvalidCff),
+                };
+                let fd_array = Index::parse(bytes, table.offset(to_offset(bytes, fd_array)?))?;
+                let index = font_dict_index(bytes, fd_select, id)?;
+                Dict::parse(fd_array.get(bytes, index)?.read_from(bytes)?)?
+            }
+            _ => top_dict,
+        };
+        let local_subroutines = match font_dict.get(PRIVATE) {
+            Some(&[size, offset]) => {
+                let private = table.offset(to_offset(bytes, offset)?);
+                let private_dict =
+                    Dict::parse(Slice::new(private, to_offset(bytes, size)?).read_from(bytes)?)?;
+                match private_dict.get(SUBRS) {
+                    // The offset to local subroutines is from the start of the private DICT
+                    Some(&[offset]) => Some(Index::parse(
+                        bytes,
+                        private.offset(to_offset(bytes, offset)?),
+                    )?),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        let mut interpreter = Interpreter {
+            bytes,
+            global_subroutines,
+            local_subroutines,
+            builder,
+            stack: Vec::with_capacity(MAX_STACK_SIZE),
+            stem_count: 0,
+            width_done: false,
+            contour_open: false,
+            x: 0.,
+            y: 0.,
+        };
+        interpreter.run(char_strings.get(bytes, id.into())?.read_from(bytes)?, 0)?;
+        interpreter.close_contour();
+        Ok(())
+    }
+
+    /// The CID of a glyph, from the charset of a CID-keyed font.
+    /// Other fonts don’t have CIDs, their glyph IDs are returned unchanged.
+    pub(crate) fn cff_cid(&self, GlyphId(id): GlyphId) -> Result<u16, FontError> {
+        let bytes = self.bytes();
+        let (table, top_dict, _) = self.cff_top_dict()?;
+        // The charset starts with the glyph after `.notdef`, whose CID is always 0
+        if top_dict.get(ROS).is_none() || id == 0 {
+            return Ok(id)
+        }
+        let glyph_count = match top_dict.get(CHAR_STRINGS) {
+            Some(&[offset]) => Index::parse(bytes, table.offset(to_offset(bytes, offset)?))?.count,
+            _ => return Err(FontError::InvalidCff),
+        };
+        if u32::from(id) >= glyph_count {
+            return Err(FontError::InvalidCff)
+        }
+        match top_dict.get(CHARSET) {
+            // Offsets 0 to 2 are predefined charsets of glyph names, not CIDs
+            Some(&[offset]) if offset > 2. => {
+                charset_cid(bytes, table.offset(to_offset(bytes, offset)?), id)
+            }
+            _ => Err(FontError::InvalidCff),
+        }
+    }
+
+    /// The position of the `CFF ` table, its top DICT, and its global subroutines
+    fn cff_top_dict(&self) -> Result<(Position<u8>, Dict, Index), FontError> {
+        let bytes = self.bytes();
+        let table = self.table_directory.find_table::<CffHeader>(bytes)?;
+        let name_index = Index::parse(
+            bytes,
+            table.offset_bytes(table.header_size().read_from(bytes)?),
+        )?;
+        let top_dicts = Index::parse(bytes, name_index.end)?;
+        let strings = Index::parse(bytes, top_dicts.end)?;
+        let global_subroutines = Index::parse(bytes, strings.end)?;
+
+        // The `CFF ` table of an OpenType font has exactly one font
+        let top_dict = Dict::parse(top_dicts.get(bytes, 0)?.read_from(bytes)?)?;
+        Ok((table.cast::<u8>(), top_dict, global_subroutines))
+    }
+}
+
+/// An array of objects of varying sizes, located by offsets
+/// that start at 1 from the byte before the first object.
+struct Index {
+    count: u32,
+    offset_size: u8,
+    offsets: Position<u8>,
+    data: Position<u8>,
+
+    /// The position just after the last object
+    end: Position<u8>,
+}
+
+impl Index {
+    fn parse(bytes: &[u8], start: Position<u8>) -> Result<Self, FontError> {
+        let count = u32::from(start.cast::<u16>().read_from(bytes)?);
+        if count == 0 {
+            // An empty INDEX has only its count
+            let end = start.offset(2_u32);
+            return Ok(Index {
+                count,
+                offset_size: 0,
+                offsets: end,
+                data: end,
+                end,
+            })
+        }
+        let offset_size = start.offset(2_u32).read_from(bytes)?;
+        if !(1..=4).contains(&offset_size) {
+            return Err(FontError::InvalidCff)
+        }
+        let offsets = start.offset(3_u32);
+        let mut index = Index {
+            count,
+            offset_size,
+            offsets,
+            data: offsets.offset((count + 1) * u32::from(offset_size)),
+            end: offsets,
+        };
+        index.end = index.data.offset(index.read_offset(bytes, count)?);
+        Ok(index)
+    }
+
+    /// The offset of an object from the start of the data
+    fn read_offset(&self, bytes: &[u8], index: u32) -> Result<u32, FontError> {
+        let offset_bytes = Slice::new(
+            self.offsets.offset(index * u32::from(self.offset_size)),
+            self.offset_size,
+        )
+        .read_from(bytes)?;
+        let offset = offset_bytes
+            .iter()
+            .fold(0, |offset, &byte| offset << 8 | u32::from(byte));
+        if offset < 1 || offset as usize > bytes.len() {
+            return Err(FontError::InvalidCff)
+        }
+        Ok(offset - 1)
+    }
+
+    fn get(&self, bytes: &[u8], index: u32) -> Result<Slice<u8>, FontError> {
+        if index >= self.count {
+            return Err(FontError::InvalidCff)
+        }
+        let start = self.read_offset(bytes, index)?;
+        let end = self.read_offset(bytes, index + 1)?;
+        if end < start {
+            return Err(FontError::InvalidCff)
+        }
+        Ok(Slice::new(self.data.offset(start), end - start))
+    }
+}
+
+/// The entries of a DICT: the operands of each operator
+struct Dict(Vec<(u16, Vec<f64>)>);
+
+impl Dict {
+    fn parse(mut data: &[u8]) -> Result<Self, FontError> {
+        let mut entries = Vec::new();
+        let mut operands = Vec::new();
+        while let Some((&b0, rest)) = data.split_first() {
+            data = rest;
+            let operand = match b0 {
+                0..=21 => {
+                    let operator = if b0 == ESCAPE {
+                        let (&b1, rest) = data.split_first().ok_or(FontError::InvalidCff)?;
+                        data = rest;
+                        u16::from(ESCAPE) << 8 | u16::from(b1)
+                    } else {
+                        u16::from(b0)
+                    };
+                    entries.push((operator, std::mem::take(&mut operands)));
+                    continue
+                }
+                28 => f64::from(i16::from_be_bytes(take_array(&mut data)?)),
+                29 => f64::from(i32::from_be_bytes(take_array(&mut data)?)),
+                30 => parse_real(&mut data)?,
+                32..=246 => f64::from(i32::from(b0) - 139),
+                247..=254 => {
+                    let [b1] = take_array(&mut data)?;
+                    f64::from(small_integer(b0, b1))
+                }
+                _ => return Err(FontError::InvalidCff),
+            };
+            operands.push(operand)
+        }
+        Ok(Dict(entries))
+    }
+
+    fn get(&self, operator: u16) -> Option<&[f64]> {
+        self.0
+            .iter()
+            .find(|&&(op, _)| op == operator)
+            .map(|(_, operands)| &**operands)
+    }
+}
+
+/// A number encoded as one byte in `247..=254` followed by another byte,
+/// in DICTs and charstrings
+fn small_integer(b0: u8, b1: u8) -> i32 {
+    if b0 < 251 {
+        (i32::from(b0) - 247) * 256 + i32::from(b1) + 108
+    } else {
+        -(i32::from(b0) - 251) * 256 - i32::from(b1) - 108
+    }
+}
+
+fn take_array<A: Default + AsMut<[u8]>>(data: &mut &[u8]) -> Result<A, FontError> {
+    let mut array = A::default();
+    let length = array.as_mut().len();
+    if data.len() < length {
+        return Err(FontError::InvalidCff)
+    }
+    array.as_mut().copy_from_slice(&data[..length]);
+    *data = &data[length..];
+    Ok(array)
+}
+
+/// A real number in a DICT, as decimal digits and other characters in 4-bit nibbles
+fn parse_real(data: &mut &[u8]) -> Result<f64, FontError> {
+    let mut string = String::new();
+    loop {
+        let [byte] = take_array(data)?;
+        for &nibble in &[byte >> 4, byte & 0xF] {
+            match nibble {
+                0..=9 => string.push((b'0' + nibble) as char),
+                0xA => string.push('.'),
+                0xB => string.push('E'),
+                0xC => string.push_str("E-"),
+                0xE => string.push('-'),
+                0xF => return string.parse().map_err(|_| FontError::InvalidCff),
+                _ => return Err(FontError::InvalidCff),
+            }
+        }
+    }
+}
+
+/// An offset or size in a DICT, which cannot be larger than the font file
+fn to_offset(bytes: &[u8], operand: f64) -> Result<u32, FontError> {
+    if operand >= 0. && operand <= bytes.len() as f64 && operand.fract() == 0. {
+        Ok(operand as u32)
+    } else {
+        Err(FontError::InvalidCff)
+    }
+}
+
+/// The number of the font DICT for a glyph, from the FDSelect structure
+fn font_dict_index(bytes: &[u8], fd_select: Position<u8>, glyph: u16) -> Result<u32, FontError> {
+    match fd_select.read_from(bytes)? {
+        0 => Ok(u32::from(
+            fd_select.offset(1 + u32::from(glyph)).read_from(bytes)?,
+        )),
+        3 => {
+            // Ranges of 3 bytes: the first glyph as `u16` and the font DICT number as `u8`,
+            // followed by a sentinel glyph number
+            let range_count = fd_select.offset(1_u32).cast::<u16>().read_from(bytes)?;
+            let ranges = fd_select.offset(3_u32);
+            for index in 0..u32::from(range_count) {
+                let range = ranges.offset(index * 3);
+                let first = range.cast::<u16>().read_from(bytes)?;
+                let next = range.offset(3_u32).cast::<u16>().read_from(bytes)?;
+                if first <= glyph && glyph < next {
+                    return Ok(u32::from(range.offset(2_u32).read_from(bytes)?))
+                }
+            }
+            Err(FontError::InvalidCff)
+        }
+        _ => Err(FontError::InvalidCff),
+    }
+}
+
+/// The CID of a glyph other than `.notdef`, from the charset of a CID-keyed font
+fn charset_cid(bytes: &[u8], charset: Position<u8>, glyph: u16) -> Result<u16, FontError> {
+    let data = charset.offset(1_u32);
+    match charset.read_from(bytes)? {
+        // The CID of each glyph as `u16`
+        0 => data.cast::<u16>().offset(glyph - 1).read_from(bytes),
+        format @ 1..=2 => {
+            // Ranges of consecutive CIDs: the first CID as `u16`,
+            // and the number of other glyphs in the range as `u8` or `u16`
+            let range_size: u32 = if format == 1 { 3 } else { 4 };
+            let mut first_glyph = 1;
+            let mut range = data;
+            loop {
+                let first = range.cast::<u16>().read_from(bytes)?;
+                let left = if format == 1 {
+                    u16::from(range.offset(2_u32).read_from(bytes)?)
+                } else {
+                    range.offset(2_u32).cast::<u16>().read_from(bytes)?
+                };
+                if glyph - first_glyph <= left {
+                    return first
+                        .checked_add(glyph - first_glyph)
+                        .ok_or(FontError::InvalidCff)
+                }
+                first_glyph = first_glyph
+                    .checked_add(left)
+                    .and_then(|g| g.checked_add(1))
+                    .ok_or(FontError::InvalidCff)?;
+                range = range.offset(range_size)
+            }
+        }
+        _ => Err(FontError::InvalidCff),
+    }
+}
+
+struct Interpreter<'a> {
+    bytes: &'a [u8],
+    global_subroutines: Index,
+    local_subroutines: Option<Index>,
+    builder: &'a mut PathBuilder,
+    stack: Vec<f32>,
+
+    /// The number of stem hints so far, which determines the size of masks
+    stem_count: u32,
+
+    /// Whether the first stack-clearing operator, which can have the advance width
+    /// as an extra first argument, was seen
+    width_done: bool,
+    contour_open: bool,
+
+    /// The current point
+    x: f32,
+    y: f32,
+}
+
+impl Interpreter<'_> {
+    /// Execute a charstring or subroutine. Returns whether `endchar` was reached.
+    fn run(&mut self, mut charstring: &[u8], depth: u32) -> Result<bool, FontError> {
+        if depth >= MAX_NESTING_DEPTH {
+            return Err(FontError::InvalidCff)
+        }
+        while let Some((&b0, rest)) = charstring.split_first() {
+            charstring = rest;
+            let number = match b0 {
+                SHORTINT => f32::from(i16::from_be_bytes(take_array(&mut charstring)?)),
+                32..=246 => f32::from(i16::from(b0) - 139),
+                247..=254 => {
+                    let [b1] = take_array(&mut charstring)?;
+                    small_integer(b0, b1) as f32
+                }
+                // A 16.16 fixed point number
+                FIXED => i32::from_be_bytes(take_array(&mut charstring)?) as f32 / 65536.,
+                _ => {
+                    if self.operator(b0, &mut charstring, depth)? {
+                        return Ok(true)
+                    }
+                    continue
+                }
+            };
+            if self.stack.len() >= MAX_STACK_SIZE {
+                return Err(FontError::InvalidCff)
+            }
+            self.stack.push(number)
+        }
+        Ok(false)
+    }
+
+    /// Execute one operator, reading its following bytes if any from `charstring`.
+    /// Returns whether `endchar` was reached.
+    fn operator(&mut self, b0: u8, charstring: &mut &[u8], depth: u32) -> Result<bool, FontError> {
+        match b0 {
+            HSTEM | VSTEM | HSTEMHM | VSTEMHM => self.stems(),
+            HINTMASK | CNTRMASK => {
+                // Arguments before a mask are an implied `vstem`
+                self.stems();
+                let mask_length = (self.stem_count as usize + 7) / 8;
+                if charstring.len() < mask_length {
+                    return Err(FontError::InvalidCff)
+                }
+                *charstring = &charstring[mask_length..]
+            }
+            RMOVETO => {
+                self.width(2);
+                let stack = self.take_stack(|n| n == 2)?;
+                self.move_by(stack[0], stack[1])
+            }
+            HMOVETO => {
+                self.width(1);
+                let stack = self.take_stack(|n| n == 1)?;
+                self.move_by(stack[0], 0.)
+            }
+            VMOVETO => {
+                self.width(1);
+                let stack = self.take_stack(|n| n == 1)?;
+                self.move_by(0., stack[0])
+            }
+            RLINETO => {
+                let stack = self.take_stack(|n| n >= 2 && n % 2 == 0)?;
+                for pair in stack.chunks(2) {
+                    self.line_by(pair[0], pair[1])?
+                }
+            }
+            HLINETO | VLINETO => {
+                let stack = self.take_stack(|n| n >= 1)?;
+                let mut horizontal = b0 == HLINETO;
+                for &d in &stack {
+                    if horizontal {
+                        self.line_by(d, 0.)?
+                    } else {
+                        self.line_by(0., d)?
+                    }
+                    horizontal = !horizontal
+                }
+            }
+            RRCURVETO => {
+                let stack = self.take_stack(|n| n >= 6 && n % 6 == 0)?;
+                for c in stack.chunks(6) {
+                    self.curve_by(c[0], c[1], c[2], c[3], c[4], c[5])?
+                }
+            }
+            RCURVELINE => {
+                let stack = self.take_stack(|n| n >= 8 && (n - 2) % 6 == 0)?;
+                let (curves, line) = stack.split_at(stack.len() - 2);
+                for c in curves.chunks(6) {
+                    self.curve_by(c[0], c[1], c[2], c[3], c[4], c[5])?
+                }
+                self.line_by(line[0], line[1])?
+            }
+            RLINECURVE => {
+                let stack = self.take_stack(|n| n >= 8 && n % 2 == 0)?;
+                let (lines, c) = stack.split_at(stack.len() - 6);
+                for pair in lines.chunks(2) {
+                    self.line_by(pair[0], pair[1])?
+                }
+                self.curve_by(c[0], c[1], c[2], c[3], c[4], c[5])?
+            }
+            VVCURVETO | HHCURVETO => {
+                let stack = self.take_stack(|n| n >= 4 && n % 4 <= 1)?;
+                // An odd first argument is the other coordinate of the first control point
+                let (mut first, curves) = if stack.len() % 4 == 1 {
+                    (stack[0], &stack[1..])
+                } else {
+                    (0., &stack[..])
+                };
+                for c in curves.chunks(4) {
+                    if b0 == VVCURVETO {
+                        self.curve_by(first, c[0], c[1], c[2], 0., c[3])?
+                    } else {
+                        self.curve_by(c[0], first, c[1], c[2], c[3], 0.)?
+                    }
+                    first = 0.
+                }
+            }
+            VHCURVETO | HVCURVETO => {
+                let stack = self.take_stack(|n| n >= 4 && n % 4 <= 1)?;
+                let mut vertical = b0 == VHCURVETO;
+                let mut curves = &stack[..];
+                while curves.len() >= 4 {
+                    let c = &curves[..4];
+                    curves = &curves[4..];
+                    // A last odd argument is the other coordinate of the last end point
+                    let last = if curves.len() == 1 { curves[0] } else { 0. };
+                    if vertical {
+                        self.curve_by(0., c[0], c[1], c[2], c[3], last)?
+                    } else {
+                        self.curve_by(c[0], 0., c[1], c[2], last, c[3])?
+                    }
+                    vertical = !vertical
+                }
+            }
+            CALLSUBR | CALLGSUBR => {
+                let subroutines = if b0 == CALLSUBR {
+                    self.local_subroutines.as_ref()
+                } else {
+                    Some(&self.global_subroutines)
+                };
+                let subroutines = subroutines.ok_or(FontError::InvalidCff)?;
+                let index = self.stack.pop().ok_or(FontError::InvalidCff)? as i32;
+                // Subroutine numbers are biased depending on how many there are,
+                // so that more of them fit in one byte
+                let bias = match subroutines.count {
+                    0..=1239 => 107,
+                    1240..=33899 => 1131,
+                    _ => 32768,
+                };
+                let index = index + bias;
+                if index < 0 {
+                    return Err(FontError::InvalidCff)
+                }
+                let subroutine = subroutines
+                    .get(self.bytes, index as u32)?
+                    .read_from(self.bytes)?;
+                return self.run(subroutine, depth + 1)
+            }
+            RETURN => return Ok(false),
+            ENDCHAR => {
+                self.width(0);
+                // FIXME: with four arguments this is the deprecated `seac`,
+                // an accented character made of two glyphs from the standard encoding.
+                self.stack.clear();
+                self.close_contour();
+                return Ok(true)
+            }
+            ESCAPE => {
+                let [b1] = take_array(charstring)?;
+                self.flex(b1)?
+            }
+            _ => return Err(FontError::InvalidCff),
+        }
+        Ok(false)
+    }
+
+    /// The flex operators: two curves that can be rendered as a line when they are very flat.
+    fn flex(&mut self, operator: u8) -> Result<(), FontError> {
+        match operator {
+            HFLEX => {
+                let stack = self.take_stack(|n| n == 7)?;
+                if let [dx1, dx2, dy2, dx3, dx4, dx5, dx6] = stack[..] {
+                    self.curve_by(dx1, 0., dx2, dy2, dx3, 0.)?;
+                    self.curve_by(dx4, 0., dx5, -dy2, dx6, 0.)?
+                }
+            }
+            FLEX => {
+                let c = self.take_stack(|n| n == 13)?;
+                self.curve_by(c[0], c[1], c[2], c[3], c[4], c[5])?;
+                self.curve_by(c[6], c[7], c[8], c[9], c[10], c[11])?
+            }
+            HFLEX1 => {
+                let stack = self.take_stack(|n| n == 9)?;
+                if let [dx1, dy1, dx2, dy2, dx3, dx4, dx5, dy5, dx6] = stack[..] {
+                    self.curve_by(dx1, dy1, dx2, dy2, dx3, 0.)?;
+                    self.curve_by(dx4, 0., dx5, dy5, dx6, -(dy1 + dy2 + dy5))?
+                }
+            }
+            FLEX1 => {
+                let c = self.take_stack(|n| n == 11)?;
+                // The last argument is the last end point’s coordinate on the axis
+                // where the curves move most, the other coordinate is back to the start.
+                let dx: f32 = c.iter().step_by(2).take(5).sum();
+                let dy: f32 = c.iter().skip(1).step_by(2).take(5).sum();
+                let (dx6, dy6) = if dx.abs() > dy.abs() {
+                    (c[10], -dy)
+                } else {
+                    (-dx, c[10])
+                };
+                self.curve_by(c[0], c[1], c[2], c[3], c[4], c[5])?;
+                self.curve_by(c[6], c[7], c[8], c[9], dx6, dy6)?
+            }
+            // Arithmetic and storage operators are rarely used
+            _ => return Err(FontError::InvalidCff),
+        }
+        Ok(())
+    }
+
+    /// Drop the advance width if it is an extra argument of the first stack-clearing operator.
+    /// Glyph advances are read from `hmtx` instead.
+    fn width(&mut self, argument_count: usize) {
+        if !self.width_done {
+            self.width_done = true;
+            if self.stack.len() > argument_count {
+                self.stack.remove(0);
+            }
+        }
+    }
+
+    fn stems(&mut self) {
+        // Stem hints are pairs of numbers
+        let even_length = self.stack.len() & !1;
+        self.width(even_length);
+        self.stem_count += self.stack.len() as u32 / 2;
+        self.stack.clear()
+    }
+
+    /// Take all arguments, after checking that their number is valid for the operator
+    fn take_stack(&mut self, valid_count: impl Fn(usize) -> bool) -> Result<Vec<f32>, FontError> {
+        if !valid_count(self.stack.len()) {
+            return Err(FontError::InvalidCff)
+        }
+        Ok(std::mem::replace(
+            &mut self.stack,
+            Vec::with_capacity(MAX_STACK_SIZE),
+        ))
+    }
+
+    fn close_contour(&mut self) {
+        if self.contour_open {
+            self.contour_open = false;
+            self.builder.close()
+        }
+    }
+
+    fn move_by(&mut self, dx: f32, dy: f32) {
+        self.close_contour();
+        self.x += dx;
+        self.y += dy;
+        self.contour_open = true;
+        self.builder.move_to(self.x, self.y)
+    }
+
+    fn line_by(&mut self, dx: f32, dy: f32) -> Result<(), FontError> {
+        if !self.contour_open {
+            return Err(FontError::InvalidCff)
+        }
+        self.x += dx;
+        self.y += dy;
+        self.builder.line_to(self.x, self.y);
+        Ok(())
+    }
+
+    /// A cubic curve, with each point relative to the previous one
+    fn curve_by(
+        &mut self,
+        dx1: f32,
+        dy1: f32,
+        dx2: f32,
+        dy2: f32,
+        dx3: f32,
+        dy3: f32,
+    ) -> Result<(), FontError> {
+        if !self.contour_open {
+            return Err(FontError::InvalidCff)
+        }
+        let control_1 = (self.x + dx1, self.y + dy1);
+        let control_2 = (control_1.0 + dx2, control_1.1 + dy2);
+        self.x = control_2.0 + dx3;
+        self.y = control_2.1 + dy3;
+        self.builder
+            .cubic_to(control_1, control_2, (self.x, self.y));
+        Ok(())
+    }
+}
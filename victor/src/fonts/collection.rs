@@ -0,0 +1,193 @@
+use super::{Font, FontError};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A set of fonts, indexed by family name.
+///
+/// Family names are matched ASCII case-insensitively.
+#[derive(Clone, Default)]
+pub struct FontCollection {
+    /// In the order they were added
+    faces: Vec<FontFace>,
+
+    /// Keys are ASCII-lowercase family names.
+    families: HashMap<String, Vec<FontFace>>,
+}
+
+/// A font in a collection, with the properties that font matching is based on.
+///
+/// These come from the font’s `name` and `OS/2` tables,
+/// unless overridden for example by descriptors in an `@font-face` rule.
+#[derive(Clone)]
+pub struct FontFace {
+    pub font: Arc<Font>,
+    pub family_name: String,
+
+    /// From 1 to 1000: 400 is normal, 700 is bold
+    pub weight: u16,
+
+    /// `1.` is normal width, `0.5` is ultra-condensed, and `2.` is ultra-expanded.
+    pub stretch: f32,
+
+    /// Whether this face is italic or oblique
+    pub is_italic: bool,
+}
+
+impl FontFace {
+    pub fn new(font: Arc<Font>) -> Self {
+        // https://docs.microsoft.com/en-us/typography/opentype/spec/os2#uswidthclass
+        let stretch = match font.width_class() {
+            1 => 0.5,
+            2 => 0.625,
+            3 => 0.75,
+            4 => 0.875,
+            6 => 1.125,
+            7 => 1.25,
+            8 => 1.5,
+            9 => 2.,
+            _ => 1.,
+        };
+        FontFace {
+            family_name: font.family_name().to_owned(),
+            weight: font.weight(),
+            stretch,
+            is_italic: font.is_italic(),
+            font,
+        }
+    }
+}
+
+impl FontCollection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The regular, bold, oblique, and bold oblique faces of Bitstream Vera Sans
+    pub fn bitstream_vera_sans() -> Result<Self, FontError> {
+        let mut collection = Self::new();
+        collection.add(super::BITSTREAM_VERA_SANS.get()?);
+        collection.add(super::BITSTREAM_VERA_SANS_BOLD.get()?);
+        collection.add(super::BITSTREAM_VERA_SANS_OBLIQUE.get()?);
+        collection.add(super::BITSTREAM_VERA_SANS_BOLD_OBLIQUE.get()?);
+        Ok(collection)
+    }
+
+    pub fn add(&mut self, font: Arc<Font>) {
+        self.add_face(FontFace::new(font))
+    }
+
+    pub fn add_face(&mut self, face: FontFace) {
+        self.families
+            .entry(face.family_name.to_ascii_lowercase())
+            .or_default()
+            .push(face.clone());
+        self.faces.push(face)
+    }
+
+    /// Parse a font file and add it to this collection.
+    pub fn add_bytes<B: Into<Cow<'static, [u8]>>>(
+        &mut self,
+        bytes: B,
+    ) -> Result<Arc<Font>, FontError> {
+        let font = Font::parse(bytes)?;
+        self.add(font.clone());
+        Ok(font)
+    }
+
+    /// Add every supported font file in this directory and its subdirectories,
+    /// and return how many faces were added.
+    ///
+    /// Files are visited in order of their path, and every face of a collection file is added.
+    /// Those that fail to parse (for example in an unsupported format) are skipped.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        entries.sort();
+        let mut count = 0;
+        for path in entries {
+            if path.is_dir() {
+                count += self.add_directory(&path)?
+            } else if has_font_extension(&path) {
+                if let Ok(fonts) = Font::parse_collection(fs::read(&path)?) {
+                    count += fonts.len();
+                    for font in fonts {
+                        self.add(font)
+                    }
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Add fonts from the usual system font directories that exist on this machine,
+    /// and return how many were added.
+    pub fn add_system_fonts(&mut self) -> io::Result<usize> {
+        let mut directories = vec![
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+            PathBuf::from("/Library/Fonts"),
+            PathBuf::from("/System/Library/Fonts"),
+            PathBuf::from(r"C:\Windows\Fonts"),
+        ];
+        if let Some(home) = std::env::var_os("HOME") {
+            let home = PathBuf::from(home);
+            directories.push(home.join(".fonts"));
+            directories.push(home.join(".local/share/fonts"));
+            directories.push(home.join("Library/Fonts"));
+        }
+        let mut count = 0;
+        for directory in directories {
+            if directory.is_dir() {
+                count += self.add_directory(&directory)?
+            }
+        }
+        Ok(count)
+    }
+
+    /// All faces in this collection, in the order they were added
+    pub fn faces(&self) -> &[FontFace] {
+        &self.faces
+    }
+
+    /// The faces of the given family, in the order they were added
+    pub fn family(&self, family_name: &str) -> &[FontFace] {
+        self.families
+            .get(&family_name.to_ascii_lowercase())
+            .map(|faces| &faces[..])
+            .unwrap_or(&[])
+    }
+
+    /// Find a face by family and subfamily names, such as `"Bitstream Vera Sans"` and `"Bold"`.
+    pub fn get(&self, family_name: &str, subfamily_name: &str) -> Option<&Arc<Font>> {
+        self.family(family_name)
+            .iter()
+            .find(|face| {
+                face.font
+                    .subfamily_name()
+                    .eq_ignore_ascii_case(subfamily_name)
+            })
+            .map(|face| &face.font)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+}
+
+fn has_font_extension(path: &Path) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => ["ttf", "otf", "ttc", "otc", "woff", "woff2"]
+            .iter()
+            .any(|font_extension| extension.eq_ignore_ascii_case(font_extension)),
+        None => false,
+    }
+}
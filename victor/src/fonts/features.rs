@@ -0,0 +1,49 @@
+/// The OpenType features to apply when shaping text, such as `liga` for standard ligatures
+///
+/// By default `ccmp`, `rlig`, `liga`, `clig`, `calt`, and `kern` are enabled.
+/// Only `kern` and the features of `GSUB` lookups with supported types have an effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFeatures {
+    /// Tags are unique
+    settings: Vec<([u8; 4], u32)>,
+}
+
+impl Default for FontFeatures {
+    fn default() -> Self {
+        let mut features = FontFeatures::none();
+        for &tag in &[b"ccmp", b"rlig", b"liga", b"clig", b"calt", b"kern"] {
+            features.set(*tag, 1)
+        }
+        features
+    }
+}
+
+impl FontFeatures {
+    /// No feature enabled
+    pub fn none() -> Self {
+        FontFeatures {
+            settings: Vec::new(),
+        }
+    }
+
+    /// Set the value of a feature: 0 disables it, and 1 enables it.
+    ///
+    /// FIXME: larger values select alternates, but alternate substitution is not supported.
+    pub fn set(&mut self, tag: [u8; 4], value: u32) {
+        match self.settings.iter_mut().find(|(t, _)| *t == tag) {
+            Some(setting) => setting.1 = value,
+            None => self.settings.push((tag, value)),
+        }
+    }
+
+    /// Tags and values of features that were set, in the order they were first set.
+    pub(crate) fn iter<'a>(&'a self) -> impl Iterator<Item = ([u8; 4], u32)> + 'a {
+        self.settings.iter().cloned()
+    }
+
+    pub fn is_enabled(&self, tag: [u8; 4]) -> bool {
+        self.settings
+            .iter()
+            .any(|&(t, value)| t == tag && value != 0)
+    }
+}
@@ -0,0 +1,486 @@
+//! Reading and writing glyph outlines in the `glyf` table
+
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::types::{f2dot14_to_f32, f32_to_f2dot14};
+use crate::fonts::{Font, FontError, GlyphId};
+
+// Flags of simple glyph points
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// Flags of composite glyph components
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+pub(in crate::fonts) const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+pub(in crate::fonts) const SCALED_COMPONENT_OFFSET: u16 = 0x0800;
+
+pub(in crate::fonts) enum Glyph {
+    /// A glyph without an outline, for example for a space
+    Empty,
+    Simple(SimpleGlyph),
+    Composite(CompositeGlyph),
+}
+
+/// A glyph made of contours of quadratic curves
+pub(in crate::fonts) struct SimpleGlyph {
+    /// The index in `points` of the last point of each contour
+    pub(in crate::fonts) end_points: Vec<u16>,
+    pub(in crate::fonts) points: Vec<GlyphPoint>,
+    pub(in crate::fonts) instructions: Slice<u8>,
+
+    /// Whether contours may overlap, from the flags of the first point
+    pub(in crate::fonts) overlap: bool,
+}
+
+/// A point of a simple glyph, in font design units
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(in crate::fonts) struct GlyphPoint {
+    pub(in crate::fonts) x: i32,
+    pub(in crate::fonts) y: i32,
+
+    /// Whether this point is on the curve, or a control point of a quadratic curve
+    pub(in crate::fonts) on_curve: bool,
+}
+
+/// A glyph made of other glyphs, possibly transformed
+pub(in crate::fonts) struct CompositeGlyph {
+    pub(in crate::fonts) components: Vec<Component>,
+
+    /// Empty if there are no instructions
+    pub(in crate::fonts) instructions: Slice<u8>,
+
+    /// `min_x`, `min_y`, `max_x`, and `max_y` from the glyph header
+    pub(in crate::fonts) bounding_box: [i16; 4],
+}
+
+pub(in crate::fonts) struct Component {
+    pub(in crate::fonts) flags: u16,
+    pub(in crate::fonts) glyph: GlyphId,
+
+    /// With `ARGS_ARE_XY_VALUES` in `flags`, the offset of the component.
+    /// Otherwise, the numbers of a point of the composite glyph
+    /// and of a point of the component that are aligned.
+    pub(in crate::fonts) arguments: (i32, i32),
+
+    /// The linear transform of the component as `[xx, xy, yx, yy]`,
+    /// where a point `(x, y)` is transformed to `(xx * x + yx * y, xy * x + yy * y)`.
+    pub(in crate::fonts) transform: [f32; 4],
+}
+
+impl Font {
+    /// The data of a glyph in the `glyf` table, empty for glyphs without an outline.
+    ///
+    /// This fails for fonts without `glyf` and `loca` tables.
+    pub(in crate::fonts) fn glyph_data(
+        &self,
+        GlyphId(id): GlyphId,
+    ) -> Result<Slice<u8>, FontError> {
+        let bytes = self.bytes();
+        let header = self.table_directory.find_table::<FontHeader>(bytes)?;
+        let glyph_data = self.table_directory.find_table::<GlyphHeader>(bytes)?;
+        let short_offsets = self.table_directory.find_table::<ShortGlyphOffset>(bytes)?;
+        let (start, end) = match header.index_to_loc_format().read_from(bytes)? {
+            0 => (
+                u32::from(short_offsets.offset(id).half_offset().read_from(bytes)?) * 2,
+                u32::from(
+                    short_offsets
+                        .offset(id + 1)
+                        .half_offset()
+                        .read_from(bytes)?,
+                ) * 2,
+            ),
+            _ => {
+                let long_offsets = short_offsets.cast::<LongGlyphOffset>();
+                (
+                    long_offsets.offset(id).byte_offset().read_from(bytes)?,
+                    long_offsets.offset(id + 1).byte_offset().read_from(bytes)?,
+                )
+            }
+        };
+        Ok(Slice::new(
+            glyph_data.offset_bytes(start),
+            end.saturating_sub(start),
+        ))
+    }
+
+    /// Parse a glyph of a font with TrueType outlines.
+    pub(in crate::fonts) fn glyph(&self, glyph: GlyphId) -> Result<Glyph, FontError> {
+        let bytes = self.bytes();
+        let data = self.glyph_data(glyph)?;
+        if data.count() == 0 {
+            return Ok(Glyph::Empty)
+        }
+        let header = data.get_unchecked(0).cast::<GlyphHeader>();
+        let contour_count = header.number_of_contours().read_from(bytes)?;
+        if contour_count < 0 {
+            CompositeGlyph::parse(bytes, header).map(Glyph::Composite)
+        } else {
+            SimpleGlyph::parse(bytes, header, contour_count as u16).map(Glyph::Simple)
+        }
+    }
+}
+
+impl SimpleGlyph {
+    fn parse(
+        bytes: &[u8],
+        header: Position<GlyphHeader>,
+        contour_count: u16,
+    ) -> Result<Self, FontError> {
+        let end_point_positions = Slice::new(header.followed_by::<u16>(), contour_count);
+        let mut end_points = Vec::with_capacity(usize::from(contour_count));
+        for position in end_point_positions {
+            end_points.push(position.read_from(bytes)?)
+        }
+        let point_count = end_points.last().map_or(0, |&last| usize::from(last) + 1);
+        let instructions_length = end_point_positions.get_unchecked(u32::from(contour_count));
+        let instructions = Slice::new(
+            instructions_length.followed_by::<u8>(),
+            instructions_length.read_from(bytes)?,
+        );
+
+        let mut position = instructions.get_unchecked(instructions.count());
+        let mut flags = Vec::with_capacity(point_count);
+        while flags.len() < point_count {
+            let flag = position.read_from(bytes)?;
+            position = position.offset(1_u32);
+            flags.push(flag);
+            if flag & REPEAT_FLAG != 0 {
+                let repeat = position.read_from(bytes)?;
+                position = position.offset(1_u32);
+                for _ in 0..repeat {
+                    flags.push(flag)
+                }
+            }
+        }
+        flags.truncate(point_count);
+
+        // All x coordinates, then all y coordinates, each relative to the previous point
+        let mut points: Vec<GlyphPoint> = flags
+            .iter()
+            .map(|&flag| GlyphPoint {
+                x: 0,
+                y: 0,
+                on_curve: flag & ON_CURVE_POINT != 0,
+            })
+            .collect();
+        let mut x = 0;
+        for (point, &flag) in points.iter_mut().zip(&flags) {
+            x += read_coordinate(
+                bytes,
+                &mut position,
+                flag,
+                X_SHORT_VECTOR,
+                X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+            )?;
+            point.x = x
+        }
+        let mut y = 0;
+        for (point, &flag) in points.iter_mut().zip(&flags) {
+            y += read_coordinate(
+                bytes,
+                &mut position,
+                flag,
+                Y_SHORT_VECTOR,
+                Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+            )?;
+            point.y = y
+        }
+        Ok(SimpleGlyph {
+            end_points,
+            points,
+            instructions,
+            overlap: flags
+                .first()
+                .map_or(false, |&flag| flag & OVERLAP_SIMPLE != 0),
+        })
+    }
+
+    /// Append this glyph to a `glyf` table, with a bounding box computed from its points,
+    /// and return that bounding box as `[min_x, min_y, max_x, max_y]`.
+    pub(in crate::fonts) fn write(
+        &self,
+        bytes: &[u8],
+        glyf: &mut Vec<u8>,
+    ) -> Result<[i16; 4], FontError> {
+        let points: Vec<(i16, i16, bool)> = self
+            .points
+            .iter()
+            .map(|point| (clamp_i16(point.x), clamp_i16(point.y), point.on_curve))
+            .collect();
+        let mut bounding_box = [0; 4];
+        if let Some(&(x, y, _)) = points.first() {
+            bounding_box = [x, y, x, y];
+            for &(x, y, _) in &points {
+                bounding_box[0] = bounding_box[0].min(x);
+                bounding_box[1] = bounding_box[1].min(y);
+                bounding_box[2] = bounding_box[2].max(x);
+                bounding_box[3] = bounding_box[3].max(y);
+            }
+        }
+        push_i16s(glyf, &[self.end_points.len() as i16]);
+        push_i16s(glyf, &bounding_box);
+        for &end_point in &self.end_points {
+            glyf.extend_from_slice(&end_point.to_be_bytes())
+        }
+        let instructions = self.instructions.read_from(bytes)?;
+        glyf.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+        glyf.extend_from_slice(instructions);
+
+        let mut flags = Vec::with_capacity(points.len());
+        let mut x_coordinates = Vec::new();
+        let mut y_coordinates = Vec::new();
+        let (mut previous_x, mut previous_y) = (0_i16, 0_i16);
+        for (index, &(x, y, on_curve)) in points.iter().enumerate() {
+            let mut flag = if on_curve { ON_CURVE_POINT } else { 0 };
+            if index == 0 && self.overlap {
+                flag |= OVERLAP_SIMPLE
+            }
+            flag |= write_coordinate(
+                x.wrapping_sub(previous_x),
+                &mut x_coordinates,
+                X_SHORT_VECTOR,
+                X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+            );
+            flag |= write_coordinate(
+                y.wrapping_sub(previous_y),
+                &mut y_coordinates,
+                Y_SHORT_VECTOR,
+                Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+            );
+            flags.push(flag);
+            previous_x = x;
+            previous_y = y;
+        }
+        let mut flags = flags.iter().peekable();
+        while let Some(&flag) = flags.next() {
+            let mut repeat = 0_u8;
+            while repeat < u8::max_value() && flags.peek() == Some(&&flag) {
+                flags.next();
+                repeat += 1
+            }
+            if repeat > 0 {
+                glyf.extend_from_slice(&[flag | REPEAT_FLAG, repeat])
+            } else {
+                glyf.push(flag)
+            }
+        }
+        glyf.extend_from_slice(&x_coordinates);
+        glyf.extend_from_slice(&y_coordinates);
+        Ok(bounding_box)
+    }
+}
+
+impl CompositeGlyph {
+    fn parse(bytes: &[u8], header: Position<GlyphHeader>) -> Result<Self, FontError> {
+        let bounding_box = [
+            header.min_x().read_from(bytes)?.get(),
+            header.min_y().read_from(bytes)?.get(),
+            header.max_x().read_from(bytes)?.get(),
+            header.max_y().read_from(bytes)?.get(),
+        ];
+        let mut components = Vec::new();
+        let mut have_instructions = false;
+        let mut component = header.followed_by::<CompositeGlyphComponent>();
+        loop {
+            let flags = component.flags().read_from(bytes)?;
+            let glyph = GlyphId(component.glyph_index().read_from(bytes)?);
+            let arguments = component.followed_by::<u8>();
+            let words = flags & ARG_1_AND_2_ARE_WORDS != 0;
+            let arguments_size: u32 = if words { 4 } else { 2 };
+            let arguments = match (words, flags & ARGS_ARE_XY_VALUES != 0) {
+                (true, true) => {
+                    let arguments = arguments.cast::<i16>();
+                    (
+                        i32::from(arguments.read_from(bytes)?),
+                        i32::from(arguments.offset(1_u32).read_from(bytes)?),
+                    )
+                }
+                (true, false) => {
+                    let arguments = arguments.cast::<u16>();
+                    (
+                        i32::from(arguments.read_from(bytes)?),
+                        i32::from(arguments.offset(1_u32).read_from(bytes)?),
+                    )
+                }
+                (false, true) => {
+                    let arguments = arguments.cast::<i8>();
+                    (
+                        i32::from(arguments.read_from(bytes)?),
+                        i32::from(arguments.offset(1_u32).read_from(bytes)?),
+                    )
+                }
+                (false, false) => (
+                    i32::from(arguments.read_from(bytes)?),
+                    i32::from(arguments.offset(1_u32).read_from(bytes)?),
+                ),
+            };
+
+            let transform = component
+                .followed_by::<u8>()
+                .offset_bytes::<i16, _>(arguments_size);
+            let value = |index: u32| -> Result<f32, FontError> {
+                Ok(f2dot14_to_f32(transform.offset(index).read_from(bytes)?))
+            };
+            let (transform_values, transform_size) = if flags & WE_HAVE_A_SCALE != 0 {
+                let scale = value(0)?;
+                ([scale, 0., 0., scale], 1_u32)
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                ([value(0)?, 0., 0., value(1)?], 2)
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                ([value(0)?, value(1)?, value(2)?, value(3)?], 4)
+            } else {
+                ([1., 0., 0., 1.], 0)
+            };
+            components.push(Component {
+                flags,
+                glyph,
+                arguments,
+                transform: transform_values,
+            });
+            have_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+
+            let next = transform.offset(transform_size);
+            if flags & MORE_COMPONENTS == 0 {
+                let instructions = if have_instructions {
+                    let length = next.cast::<u16>();
+                    Slice::new(length.followed_by::<u8>(), length.read_from(bytes)?)
+                } else {
+                    Slice::new(next.cast::<u8>(), 0_u32)
+                };
+                return Ok(CompositeGlyph {
+                    components,
+                    instructions,
+                    bounding_box,
+                });
+            }
+            component = next.cast()
+        }
+    }
+
+    /// Append this glyph to a `glyf` table, with the bounding box in `bounding_box`.
+    ///
+    /// Arguments are written as words or bytes depending on their values.
+    pub(in crate::fonts) fn write(
+        &self,
+        bytes: &[u8],
+        glyf: &mut Vec<u8>,
+    ) -> Result<(), FontError> {
+        push_i16s(glyf, &[-1]);
+        push_i16s(glyf, &self.bounding_box);
+        let instructions = self.instructions.read_from(bytes)?;
+        for (index, component) in self.components.iter().enumerate() {
+            let mut flags =
+                component.flags & !(ARG_1_AND_2_ARE_WORDS | MORE_COMPONENTS | WE_HAVE_INSTRUCTIONS);
+            let (a, b) = component.arguments;
+            let xy_values = flags & ARGS_ARE_XY_VALUES != 0;
+            let fit_in_bytes = if xy_values {
+                [a, b].iter().all(|&x| x as i8 as i32 == x)
+            } else {
+                [a, b].iter().all(|&x| x as u8 as i32 == x)
+            };
+            if !fit_in_bytes {
+                flags |= ARG_1_AND_2_ARE_WORDS
+            }
+            if index + 1 < self.components.len() {
+                flags |= MORE_COMPONENTS
+            } else if !instructions.is_empty() {
+                flags |= WE_HAVE_INSTRUCTIONS
+            }
+            glyf.extend_from_slice(&flags.to_be_bytes());
+            glyf.extend_from_slice(&component.glyph.0.to_be_bytes());
+            if fit_in_bytes {
+                glyf.extend_from_slice(&[a as u8, b as u8])
+            } else if xy_values {
+                push_i16s(glyf, &[clamp_i16(a), clamp_i16(b)])
+            } else {
+                glyf.extend_from_slice(&(a as u16).to_be_bytes());
+                glyf.extend_from_slice(&(b as u16).to_be_bytes());
+            }
+            let [xx, xy, yx, yy] = component.transform;
+            let (transform, transform_size) = if flags & WE_HAVE_A_SCALE != 0 {
+                ([xx, 0., 0., 0.], 1)
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                ([xx, yy, 0., 0.], 2)
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                ([xx, xy, yx, yy], 4)
+            } else {
+                ([0.; 4], 0)
+            };
+            for &value in &transform[..transform_size] {
+                push_i16s(glyf, &[f32_to_f2dot14(value)])
+            }
+        }
+        if !instructions.is_empty() {
+            glyf.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+            glyf.extend_from_slice(instructions);
+        }
+        Ok(())
+    }
+}
+
+/// Read the coordinate of a simple glyph point relative to the previous point,
+/// depending on the bits of the point’s flags for this axis.
+fn read_coordinate(
+    bytes: &[u8],
+    position: &mut Position<u8>,
+    flag: u8,
+    short_vector: u8,
+    is_same_or_positive: u8,
+) -> Result<i32, FontError> {
+    Ok(if flag & short_vector != 0 {
+        let value = i32::from(position.read_from(bytes)?);
+        *position = position.offset(1_u32);
+        if flag & is_same_or_positive != 0 {
+            value
+        } else {
+            -value
+        }
+    } else if flag & is_same_or_positive != 0 {
+        0
+    } else {
+        let value = position.cast::<i16>().read_from(bytes)?;
+        *position = position.offset(2_u32);
+        i32::from(value)
+    })
+}
+
+/// Append the coordinate of a simple glyph point relative to the previous point
+/// in the shortest encoding, and return the corresponding bits of the point’s flags.
+fn write_coordinate(
+    delta: i16,
+    coordinates: &mut Vec<u8>,
+    short_vector: u8,
+    is_same_or_positive: u8,
+) -> u8 {
+    if delta == 0 {
+        is_same_or_positive
+    } else if delta > -256 && delta < 256 {
+        coordinates.push(delta.abs() as u8);
+        short_vector | if delta > 0 { is_same_or_positive } else { 0 }
+    } else {
+        coordinates.extend_from_slice(&delta.to_be_bytes());
+        0
+    }
+}
+
+fn clamp_i16(value: i32) -> i16 {
+    value
+        .max(i32::from(i16::min_value()))
+        .min(i32::from(i16::max_value())) as i16
+}
+
+fn push_i16s(bytes: &mut Vec<u8>, values: &[i16]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_be_bytes())
+    }
+}
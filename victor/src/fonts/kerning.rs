@@ -0,0 +1,261 @@
+use crate::fonts::layout_common::*;
+use crate::fonts::parsing::{binary_search, Position, Slice};
+use crate::fonts::tables::*;
+use crate::fonts::types::Tag;
+use crate::fonts::{FontDesignUnit, FontError, GlyphId};
+use std::mem;
+
+/// Adjustments to the advance width of a glyph based on the glyph that follows it
+pub(in crate::fonts) enum Kerning {
+    None,
+
+    /// Format 0 subtables of the `kern` table for horizontal text, whose values add up.
+    Kern(Vec<Slice<KerningPair>>),
+
+    /// The pair adjustment subtables of each `GPOS` lookup for the `kern` feature,
+    /// in lookup list order.
+    Gpos(Vec<Vec<PairPositioning>>),
+}
+
+pub(in crate::fonts) enum PairPositioning {
+    Format1(Position<PairPositioningFormat1>),
+    Format2(Position<PairPositioningFormat2>),
+}
+
+impl Kerning {
+    pub(in crate::fonts) fn parse(
+        bytes: &[u8],
+        table_directory: Slice<TableDirectoryEntry>,
+    ) -> Result<Self, FontError> {
+        // Fonts that have both tables keep `kern` for software that doesn’t support `GPOS`.
+        if let Some(header) = table_directory.optional_table::<GlyphPositioningHeader>(bytes)? {
+            let lookups = parse_gpos(bytes, header)?;
+            if !lookups.is_empty() {
+                return Ok(Kerning::Gpos(lookups))
+            }
+        }
+        if let Some(header) = table_directory.optional_table::<KerningHeader>(bytes)? {
+            let subtables = parse_kern(bytes, header)?;
+            if !subtables.is_empty() {
+                return Ok(Kerning::Kern(subtables))
+            }
+        }
+        Ok(Kerning::None)
+    }
+
+    pub(in crate::fonts) fn get(
+        &self,
+        bytes: &[u8],
+        left: GlyphId,
+        right: GlyphId,
+    ) -> Result<euclid::Length<i32, FontDesignUnit>, FontError> {
+        let mut total = 0;
+        match *self {
+            Kerning::None => {}
+            Kerning::Kern(ref subtables) => {
+                for pairs in subtables {
+                    let search = pairs.binary_search_by_key(&(left.0, right.0), |pair| {
+                        Ok((
+                            pair.left().read_from(bytes)?,
+                            pair.right().read_from(bytes)?,
+                        ))
+                    })?;
+                    if let Some(pair) = search {
+                        total += i32::from(pair.value().read_from(bytes)?.get())
+                    }
+                }
+            }
+            Kerning::Gpos(ref lookups) => {
+                for subtables in lookups {
+                    // Only the first subtable that applies to a pair is used in each lookup.
+                    for subtable in subtables {
+                        if let Some(value) = subtable.get(bytes, left, right)? {
+                            total += i32::from(value);
+                            break
+                        }
+                    }
+                }
+            }
+        }
+        Ok(euclid::Length::new(total))
+    }
+}
+
+fn parse_kern(
+    bytes: &[u8],
+    header: Position<KerningHeader>,
+) -> Result<Vec<Slice<KerningPair>>, FontError> {
+    let mut subtables = Vec::new();
+    // Apple’s version 1 of this table has a different header, and is not supported.
+    if header.version().read_from(bytes)? != 0 {
+        return Ok(subtables)
+    }
+    let mut subtable = header.followed_by::<KerningSubtableHeader>();
+    for _ in 0..header.table_count().read_from(bytes)? {
+        const HORIZONTAL: u16 = 1 << 0;
+        const MINIMUM: u16 = 1 << 1;
+        const CROSS_STREAM: u16 = 1 << 2;
+        let coverage = subtable.coverage().read_from(bytes)?;
+        let format = coverage >> 8;
+        let length = if format == 0 {
+            let format_0 = subtable.followed_by::<KerningFormat0Header>();
+            let pairs = Slice::new(
+                format_0.followed_by::<KerningPair>(),
+                format_0.pair_count().read_from(bytes)?,
+            );
+            // FIXME: support the override bit, which replaces the value accumulated so far.
+            if coverage & (HORIZONTAL | MINIMUM | CROSS_STREAM) == HORIZONTAL {
+                subtables.push(pairs)
+            }
+            // The 16-bit `length` field overflows for subtables with more than ~10k pairs,
+            // so compute it instead.
+            (mem::size_of::<KerningSubtableHeader>() + mem::size_of::<KerningFormat0Header>())
+                as u32
+                + mem::size_of::<KerningPair>() as u32 * pairs.count()
+        } else {
+            u32::from(subtable.length().read_from(bytes)?)
+        };
+        subtable = subtable.offset_bytes(length)
+    }
+    Ok(subtables)
+}
+
+fn parse_gpos(
+    bytes: &[u8],
+    header: Position<GlyphPositioningHeader>,
+) -> Result<Vec<Vec<PairPositioning>>, FontError> {
+    const PAIR_ADJUSTMENT: u16 = 2;
+    const EXTENSION: u16 = 9;
+    // FIXME: only use the features of the script and language system of the text
+    let features = parse_features(
+        bytes,
+        header.offset_bytes(header.feature_list_offset().read_from(bytes)?),
+    )?;
+    let all_lookups = parse_lookups(
+        bytes,
+        header.offset_bytes(header.lookup_list_offset().read_from(bytes)?),
+        EXTENSION,
+    )?;
+    let mut lookups = Vec::new();
+    for index in lookup_indices(&features, |tag| tag == Tag(*b"kern")) {
+        let lookup = match all_lookups.get(usize::from(index)) {
+            Some(lookup) => lookup,
+            None => continue,
+        };
+        let mut subtables = Vec::new();
+        if lookup.lookup_type == PAIR_ADJUSTMENT {
+            for &subtable in &lookup.subtables {
+                match subtable.read_from(bytes)? {
+                    1 => subtables.push(PairPositioning::Format1(subtable.cast())),
+                    2 => subtables.push(PairPositioning::Format2(subtable.cast())),
+                    _ => {}
+                }
+            }
+        }
+        if !subtables.is_empty() {
+            lookups.push(subtables)
+        }
+    }
+    Ok(lookups)
+}
+
+impl PairPositioning {
+    /// Return the adjustment to the advance of the first glyph of this pair,
+    /// or `None` if this subtable does not apply to it.
+    ///
+    /// FIXME: apply the other fields of value records, such as the placement of the second glyph.
+    fn get(&self, bytes: &[u8], first: GlyphId, second: GlyphId) -> Result<Option<i16>, FontError> {
+        match *self {
+            PairPositioning::Format1(subtable) => {
+                let coverage = subtable.offset_bytes(subtable.coverage_offset().read_from(bytes)?);
+                let coverage_index = match coverage_index(bytes, coverage, first)? {
+                    Some(index) => index,
+                    None => return Ok(None),
+                };
+                let pair_set_offsets = Slice::new(
+                    subtable.followed_by::<u16>(),
+                    subtable.pair_set_count().read_from(bytes)?,
+                );
+                if coverage_index >= pair_set_offsets.count() {
+                    return Ok(None)
+                }
+                let pair_set: Position<u16> = subtable.offset_bytes(
+                    pair_set_offsets
+                        .get_unchecked(coverage_index)
+                        .read_from(bytes)?,
+                );
+                let value_format_1 = subtable.value_format_1().read_from(bytes)?;
+                let value_format_2 = subtable.value_format_2().read_from(bytes)?;
+                // Each record is the second glyph ID, followed by two value records
+                let record_size =
+                    2 + value_record_size(value_format_1) + value_record_size(value_format_2);
+                let records: Position<u16> = pair_set.followed_by();
+                let search = binary_search(u32::from(pair_set.read_from(bytes)?), |index| {
+                    let record: Position<u16> = records.offset_bytes(index * record_size);
+                    Ok(record.read_from(bytes)?.cmp(&second.0))
+                })?;
+                match search {
+                    Some(index) => {
+                        let value_record = records.offset_bytes(index * record_size + 2);
+                        Ok(Some(x_advance(bytes, value_record, value_format_1)?))
+                    }
+                    None => Ok(None),
+                }
+            }
+            PairPositioning::Format2(subtable) => {
+                let coverage = subtable.offset_bytes(subtable.coverage_offset().read_from(bytes)?);
+                if coverage_index(bytes, coverage, first)?.is_none() {
+                    return Ok(None)
+                }
+                let class_1 = class(
+                    bytes,
+                    subtable.offset_bytes(subtable.class_def_1_offset().read_from(bytes)?),
+                    first,
+                )?;
+                let class_2 = class(
+                    bytes,
+                    subtable.offset_bytes(subtable.class_def_2_offset().read_from(bytes)?),
+                    second,
+                )?;
+                let class_1_count = subtable.class_1_count().read_from(bytes)?;
+                let class_2_count = subtable.class_2_count().read_from(bytes)?;
+                if class_1 >= class_1_count || class_2 >= class_2_count {
+                    return Ok(None)
+                }
+                let value_format_1 = subtable.value_format_1().read_from(bytes)?;
+                let value_format_2 = subtable.value_format_2().read_from(bytes)?;
+                // A matrix of pairs of value records, indexed by the two classes
+                let record_size =
+                    value_record_size(value_format_1) + value_record_size(value_format_2);
+                let index = u32::from(class_1) * u32::from(class_2_count) + u32::from(class_2);
+                let value_record: Position<u16> = subtable
+                    .followed_by::<u16>()
+                    .offset_bytes(index * record_size);
+                Ok(Some(x_advance(bytes, value_record, value_format_1)?))
+            }
+        }
+    }
+}
+
+const X_PLACEMENT: u16 = 1 << 0;
+const Y_PLACEMENT: u16 = 1 << 1;
+const X_ADVANCE: u16 = 1 << 2;
+
+/// Each of the 8 defined bits of a value format adds a 16-bit field to value records.
+fn value_record_size(value_format: u16) -> u32 {
+    2 * (value_format & 0x00FF).count_ones()
+}
+
+fn x_advance(
+    bytes: &[u8],
+    value_record: Position<u16>,
+    value_format: u16,
+) -> Result<i16, FontError> {
+    if value_format & X_ADVANCE == 0 {
+        return Ok(0)
+    }
+    let field: Position<i16> = value_record.offset_bytes(value_record_size(
+        value_format & (X_PLACEMENT | Y_PLACEMENT),
+    ));
+    field.read_from(bytes)
+}
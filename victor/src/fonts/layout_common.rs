@@ -0,0 +1,195 @@
+//! Table formats shared by `GPOS` and `GSUB`
+//!
+//! <https://docs.microsoft.com/en-us/typography/opentype/spec/chapter2>
+
+use crate::fonts::parsing::{binary_search, Position, Slice};
+use crate::fonts::tables::*;
+use crate::fonts::types::Tag;
+use crate::fonts::{FontError, GlyphId};
+use std::cmp::Ordering;
+
+pub(in crate::fonts) struct Feature {
+    pub tag: Tag,
+    pub lookup_indices: Vec<u16>,
+}
+
+/// FIXME: support lookup flags, such as ignoring mark glyphs.
+pub(in crate::fonts) struct Lookup {
+    pub lookup_type: u16,
+
+    /// Subtables of extension lookups are replaced with the subtables they point to,
+    /// and `lookup_type` with their type.
+    pub subtables: Vec<Position<u16>>,
+}
+
+/// Return the features of a feature list, in order.
+///
+/// FIXME: only use the features of the script and language system of the text
+pub(in crate::fonts) fn parse_features(
+    bytes: &[u8],
+    feature_list: Position<u16>,
+) -> Result<Vec<Feature>, FontError> {
+    let records = Slice::new(
+        feature_list.followed_by::<FeatureRecord>(),
+        feature_list.read_from(bytes)?,
+    );
+    let mut features = Vec::new();
+    for record in records {
+        let feature: Position<FeatureHeader> =
+            feature_list.offset_bytes(record.feature_offset().read_from(bytes)?);
+        let indices = Slice::new(
+            feature.followed_by::<u16>(),
+            feature.lookup_index_count().read_from(bytes)?,
+        );
+        let mut lookup_indices = Vec::with_capacity(indices.count() as usize);
+        for index in indices {
+            lookup_indices.push(index.read_from(bytes)?)
+        }
+        features.push(Feature {
+            tag: record.tag().read_from(bytes)?,
+            lookup_indices,
+        })
+    }
+    Ok(features)
+}
+
+/// Return the indices of the lookups of features that match `enabled`.
+///
+/// Lookups are applied in the order of the lookup list, not of features,
+/// so the result is sorted.
+pub(in crate::fonts) fn lookup_indices<F>(features: &[Feature], enabled: F) -> Vec<u16>
+where
+    F: Fn(Tag) -> bool,
+{
+    let mut indices: Vec<u16> = features
+        .iter()
+        .filter(|feature| enabled(feature.tag))
+        .flat_map(|feature| feature.lookup_indices.iter().cloned())
+        .collect();
+    indices.sort();
+    indices.dedup();
+    indices
+}
+
+/// Return every lookup of a lookup list, in order.
+pub(in crate::fonts) fn parse_lookups(
+    bytes: &[u8],
+    lookup_list: Position<u16>,
+    extension_lookup_type: u16,
+) -> Result<Vec<Lookup>, FontError> {
+    let offsets = Slice::new(
+        lookup_list.followed_by::<u16>(),
+        lookup_list.read_from(bytes)?,
+    );
+    let mut lookups = Vec::with_capacity(offsets.count() as usize);
+    for offset in offsets {
+        let lookup: Position<LookupHeader> = lookup_list.offset_bytes(offset.read_from(bytes)?);
+        let declared_type = lookup.lookup_type().read_from(bytes)?;
+        let mut lookup_type = declared_type;
+        let subtable_offsets = Slice::new(
+            lookup.followed_by::<u16>(),
+            lookup.subtable_count().read_from(bytes)?,
+        );
+        let mut subtables = Vec::with_capacity(subtable_offsets.count() as usize);
+        for offset in subtable_offsets {
+            let subtable: Position<u16> = lookup.offset_bytes(offset.read_from(bytes)?);
+            if declared_type == extension_lookup_type {
+                // All subtables of an extension lookup have the same type.
+                let extension = subtable.cast::<ExtensionSubtable>();
+                lookup_type = extension.extension_lookup_type().read_from(bytes)?;
+                subtables
+                    .push(extension.offset_bytes(extension.extension_offset().read_from(bytes)?))
+            } else {
+                subtables.push(subtable)
+            }
+        }
+        lookups.push(Lookup {
+            lookup_type,
+            subtables,
+        })
+    }
+    Ok(lookups)
+}
+
+pub(in crate::fonts) fn coverage_index(
+    bytes: &[u8],
+    coverage: Position<CoverageHeader>,
+    glyph: GlyphId,
+) -> Result<Option<u32>, FontError> {
+    let count = coverage.count().read_from(bytes)?;
+    match coverage.format().read_from(bytes)? {
+        1 => {
+            let glyphs = Slice::new(coverage.followed_by::<u16>(), count);
+            binary_search(glyphs.count(), |index| {
+                Ok(glyphs.get_unchecked(index).read_from(bytes)?.cmp(&glyph.0))
+            })
+        }
+        2 => {
+            let ranges = Slice::new(coverage.followed_by::<RangeRecord>(), count);
+            match find_range(bytes, ranges, glyph)? {
+                Some(range) => {
+                    let start = range.start_glyph_id().read_from(bytes)?;
+                    let start_index = range.value().read_from(bytes)?;
+                    Ok(Some(u32::from(start_index) + u32::from(glyph.0 - start)))
+                }
+                None => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Glyphs not listed in a class definition table are in class 0.
+pub(in crate::fonts) fn class(
+    bytes: &[u8],
+    class_definition: Position<u16>,
+    glyph: GlyphId,
+) -> Result<u16, FontError> {
+    match class_definition.read_from(bytes)? {
+        1 => {
+            let header = class_definition.cast::<ClassDefinitionFormat1Header>();
+            let start = header.start_glyph_id().read_from(bytes)?;
+            let classes = Slice::new(
+                header.followed_by::<u16>(),
+                header.glyph_count().read_from(bytes)?,
+            );
+            match glyph.0.checked_sub(start) {
+                Some(index) if u32::from(index) < classes.count() => {
+                    classes.get_unchecked(index.into()).read_from(bytes)
+                }
+                _ => Ok(0),
+            }
+        }
+        2 => {
+            let header = class_definition.cast::<ClassDefinitionFormat2Header>();
+            let ranges = Slice::new(
+                header.followed_by::<RangeRecord>(),
+                header.range_count().read_from(bytes)?,
+            );
+            match find_range(bytes, ranges, glyph)? {
+                Some(range) => range.value().read_from(bytes),
+                None => Ok(0),
+            }
+        }
+        _ => Ok(0),
+    }
+}
+
+/// Ranges are sorted and don’t overlap.
+fn find_range(
+    bytes: &[u8],
+    ranges: Slice<RangeRecord>,
+    glyph: GlyphId,
+) -> Result<Option<Position<RangeRecord>>, FontError> {
+    let search = binary_search(ranges.count(), |index| {
+        let range = ranges.get_unchecked(index);
+        Ok(if range.end_glyph_id().read_from(bytes)? < glyph.0 {
+            Ordering::Less
+        } else if range.start_glyph_id().read_from(bytes)? > glyph.0 {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        })
+    })?;
+    Ok(search.map(|index| ranges.get_unchecked(index)))
+}
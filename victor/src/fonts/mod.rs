@@ -1,33 +1,85 @@
+mod cff;
 mod cmap;
+mod collection;
+mod features;
+mod glyf;
+mod kerning;
+mod layout_common;
+mod outline;
 mod parsing;
 mod static_;
+mod subset;
+mod substitution;
 mod tables;
 mod types;
+mod variations;
+mod woff;
+mod woff2;
+mod writing;
 
 use crate::fonts::cmap::Cmap;
+use crate::fonts::kerning::Kerning;
 use crate::fonts::parsing::*;
+use crate::fonts::substitution::Substitutions;
 use crate::fonts::tables::*;
-use crate::fonts::types::Tag;
+use crate::fonts::types::{FWord, Tag, UFWord};
+use crate::fonts::variations::Variations;
+use parking_lot::Mutex;
 use std::borrow::Cow;
 use std::cmp;
 use std::sync::Arc;
 
+pub use crate::fonts::collection::{FontCollection, FontFace};
+pub use crate::fonts::features::FontFeatures;
+pub use crate::fonts::outline::{Path, PathSegment};
 pub use crate::fonts::static_::*;
+pub use crate::fonts::variations::VariationAxis;
+pub use crate::fonts::woff::decode_woff;
+pub use crate::fonts::woff2::decode_woff2;
 
 /// The EM square unit
-pub(crate) struct Em;
+pub struct Em;
 
 /// The unit of FWord and UFWord
 struct FontDesignUnit;
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub(crate) struct GlyphId(pub(crate) u16);
+pub struct GlyphId(pub u16);
+
+/// The glyph for characters that are missing in a font
+const NOTDEF_GLYPH: u16 = 0;
+
+// Signatures at the start of a font file, or of each face in a collection file
+const TRUETYPE_SIGNATURE: u32 = 0x_0001_0000;
+const APPLE_TRUETYPE_SIGNATURE: u32 = 0x_7472_7565; // "true" in big-endian
+const CFF_SIGNATURE: u32 = 0x_4F54_544F; // "OTTO"
+const COLLECTION_SIGNATURE: u32 = 0x_7474_6366; // "ttcf"
+const WOFF_SIGNATURE: u32 = 0x_774F_4646; // "wOFF"
+const WOFF2_SIGNATURE: u32 = 0x_774F_4632; // "wOF2"
+
+/// How glyph outlines are stored in a font
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum OutlineFormat {
+    /// Quadratic curves in the `glyf` table
+    TrueType,
+
+    /// PostScript Type 2 charstrings in the `CFF ` table of an OpenType font
+    Cff,
+}
 
 #[derive(Debug)]
 pub enum FontError {
-    /// Victor only supports TrueType fonts at the moment.
+    /// Victor only supports TrueType and OpenType fonts and collections,
+    /// possibly compressed as WOFF or WOFF2, at the moment.
     UnsupportedFormat,
 
+    /// A WOFF or WOFF2 file is malformed, for example its compressed data fails to decompress.
+    InvalidWoff,
+
+    /// The face index is not less than the number of faces in a font collection,
+    /// or not zero for other font files.
+    FaceIndexOutOfRange,
+
     /// The font file contains an offset to beyond the end of the file.
     OffsetBeyondEof,
 
@@ -46,16 +98,48 @@ pub enum FontError {
 
     /// This font doesn’t have any horizontal metrics for glyphs.
     NoHorizontalGlyphMetrics,
+
+    /// The `CFF ` table is malformed, for example a glyph’s charstring overflows its stack.
+    InvalidCff,
+
+    /// Victor can’t embed instances of variable fonts with CFF outlines,
+    /// other than the default instance.
+    UnsupportedCffVariations,
 }
 
 pub struct Font {
-    bytes: Cow<'static, [u8]>,
+    /// The whole file, shared by the faces of a collection
+    bytes: Arc<Cow<'static, [u8]>>,
+
+    /// The index of this face in a collection, or zero
+    face_index: u32,
+    table_directory: Slice<TableDirectoryEntry>,
+    outline_format: OutlineFormat,
     cmap: Cmap,
     postscript_name: String,
+    family_name: String,
+    subfamily_name: String,
+
+    /// `usWeightClass` in the `OS/2` table
+    weight: u16,
+
+    /// `usWidthClass` in the `OS/2` table
+    width_class: u16,
+    is_italic: bool,
     glyph_count: u16,
     font_design_units_per_em: euclid::TypedScale<f32, Em, FontDesignUnit>,
     horizontal_metrics: Slice<LongHorizontalMetricsRecord>,
 
+    /// `None` for fonts without `vhea` and `vmtx` tables
+    vertical_metrics: Option<Slice<LongVerticalMetricsRecord>>,
+    vertical_origins: Option<Position<VerticalOriginHeader>>,
+    kerning: Kerning,
+    substitutions: Substitutions,
+    variations: Variations,
+
+    /// Instances of this variable font other than the default, created by `instance`
+    instances: Mutex<Vec<Arc<Font>>>,
+
     /// Distance from baseline of highest ascender
     ascender: euclid::Length<i16, FontDesignUnit>,
 
@@ -72,29 +156,49 @@ pub struct Font {
 #[cfg(target_pointer_width = "64")]
 fn _assert_size_of() {
     let _ = std::mem::transmute::<Cmap, [u8; 24]>;
-    let _ = std::mem::transmute::<Font, [u8; 112]>;
+    let _ = std::mem::transmute::<Font, [u8; 368]>;
 }
 
 impl Font {
+    /// Parse a TrueType or OpenType font file, possibly compressed as WOFF or WOFF2.
+    /// For a collection file such as `.ttc`, this is its first face.
     pub fn parse<B: Into<Cow<'static, [u8]>>>(bytes: B) -> Result<Arc<Self>, FontError> {
-        Self::parse_cow(bytes.into())
+        Self::parse_face(bytes, 0)
+    }
+
+    /// Parse the face at `index` in a TrueType or OpenType collection file.
+    /// For other font files, the index must be zero.
+    pub fn parse_face<B: Into<Cow<'static, [u8]>>>(
+        bytes: B,
+        index: u32,
+    ) -> Result<Arc<Self>, FontError> {
+        Self::parse_shared(Arc::new(decode_web_font(bytes.into())?), index)
     }
 
-    fn parse_cow(bytes: Cow<'static, [u8]>) -> Result<Arc<Self>, FontError> {
-        let mut font = Self::parse_without_cow_bytes_field(&bytes)?;
+    /// Parse every face of a font file: one, or any number for a collection file.
+    pub fn parse_collection<B: Into<Cow<'static, [u8]>>>(
+        bytes: B,
+    ) -> Result<Vec<Arc<Self>>, FontError> {
+        let bytes = Arc::new(decode_web_font(bytes.into())?);
+        (0..face_count(&bytes)?)
+            .map(|index| Self::parse_shared(bytes.clone(), index))
+            .collect()
+    }
+
+    fn parse_shared(bytes: Arc<Cow<'static, [u8]>>, index: u32) -> Result<Arc<Self>, FontError> {
+        let mut font = Self::parse_without_cow_bytes_field(&bytes, index)?;
         font.bytes = bytes;
         Ok(Arc::new(font))
     }
 
     #[inline]
-    fn parse_without_cow_bytes_field(bytes: &[u8]) -> Result<Self, FontError> {
-        let bytes: &[u8] = &*bytes;
-        let offset_table = Position::<OffsetSubtable>::initial();
-        let scaler_type = offset_table.scaler_type().read_from(bytes)?;
-        const TRUETYPE: u32 = 0x74727565; // "true" in big-endian
-        if scaler_type != TRUETYPE && scaler_type != 0x_0001_0000 {
-            Err(FontError::UnsupportedFormat)?
-        }
+    fn parse_without_cow_bytes_field(bytes: &[u8], index: u32) -> Result<Self, FontError> {
+        let offset_table = face_offset_table(bytes, index)?;
+        let outline_format = match offset_table.scaler_type().read_from(bytes)? {
+            TRUETYPE_SIGNATURE | APPLE_TRUETYPE_SIGNATURE => OutlineFormat::TrueType,
+            CFF_SIGNATURE => OutlineFormat::Cff,
+            _ => Err(FontError::UnsupportedFormat)?,
+        };
         let table_directory = Slice::new(
             offset_table.followed_by::<TableDirectoryEntry>(),
             offset_table.table_count().read_from(bytes)?,
@@ -105,9 +209,60 @@ impl Font {
         let glyph_count = maxp.num_glyphs().read_from(bytes)?;
         let horizontal_header = table_directory.find_table::<HorizontalHeader>(bytes)?;
 
+        let postscript_name = read_name(bytes, table_directory, POSTSCRIPT_NAME)?
+            .ok_or(FontError::NoSupportedPostscriptName)?;
+        let family_name = match read_name(bytes, table_directory, TYPOGRAPHIC_FAMILY_NAME)? {
+            Some(name) => name,
+            None => read_name(bytes, table_directory, FAMILY_NAME)?
+                .unwrap_or_else(|| postscript_name.clone()),
+        };
+        let subfamily_name = match read_name(bytes, table_directory, TYPOGRAPHIC_SUBFAMILY_NAME)? {
+            Some(name) => name,
+            None => read_name(bytes, table_directory, SUBFAMILY_NAME)?
+                .unwrap_or_else(|| "Regular".to_owned()),
+        };
+
+        // The `OS/2` table is required in OpenType but not in Apple’s TrueType.
+        let (weight, width_class, is_italic) = match table_directory.optional_table::<Os2>(bytes)? {
+            Some(os2) => {
+                const ITALIC: u16 = 1 << 0;
+                const OBLIQUE: u16 = 1 << 9;
+                let selection = os2.selection().read_from(bytes)?;
+                (
+                    os2.weight_class().read_from(bytes)?,
+                    os2.width_class().read_from(bytes)?,
+                    selection & (ITALIC | OBLIQUE) != 0,
+                )
+            }
+            None => {
+                const BOLD: u16 = 1 << 0;
+                const ITALIC: u16 = 1 << 1;
+                let mac_style = header.mac_style().read_from(bytes)?;
+                let weight = if mac_style & BOLD != 0 { 700 } else { 400 };
+                (weight, 5, mac_style & ITALIC != 0)
+            }
+        };
+
+        let vertical_header = table_directory.optional_table::<VerticalHeader>(bytes)?;
+        let vertical_metrics = match (vertical_header, table_directory.optional_table(bytes)?) {
+            (Some(header), Some(records)) => Some(Slice::new(
+                records,
+                header.number_of_long_vertical_metrics().read_from(bytes)?,
+            )),
+            _ => None,
+        };
+
         Ok(Font {
-            bytes: b""[..].into(),
-            postscript_name: read_postscript_name(&bytes, table_directory)?,
+            bytes: Arc::new(b""[..].into()),
+            face_index: index,
+            table_directory,
+            outline_format,
+            postscript_name,
+            family_name,
+            subfamily_name,
+            weight,
+            width_class,
+            is_italic,
             cmap: Cmap::parse(bytes, table_directory)?,
             glyph_count,
             horizontal_metrics: Slice::new(
@@ -116,6 +271,13 @@ impl Font {
                     .number_of_long_horizontal_metrics()
                     .read_from(bytes)?,
             ),
+            vertical_metrics,
+            vertical_origins: table_directory.optional_table(bytes)?,
+            // Layout and variation tables are optional: ignore a malformed one, not the whole font.
+            kerning: Kerning::parse(bytes, table_directory).unwrap_or(Kerning::None),
+            substitutions: Substitutions::parse(bytes, table_directory).unwrap_or_default(),
+            variations: Variations::parse(bytes, table_directory).unwrap_or_default(),
+            instances: Mutex::new(Vec::new()),
             font_design_units_per_em: header.units_per_em().read_from(bytes)?.cast(),
             ascender: horizontal_header.ascender().read_from(bytes)?,
             descender: horizontal_header.descender().read_from(bytes)?,
@@ -126,13 +288,51 @@ impl Font {
         })
     }
 
+    /// The whole font file, which for a collection includes other faces
     pub fn bytes(&self) -> &[u8] {
         &self.bytes
     }
+
+    /// The index of this face in a collection file, or zero for other font files
+    pub(crate) fn face_index(&self) -> u32 {
+        self.face_index
+    }
+
+    pub(crate) fn outline_format(&self) -> OutlineFormat {
+        self.outline_format
+    }
     pub(crate) fn postscript_name(&self) -> &str {
         &self.postscript_name
     }
-    pub(crate) fn glyph_count(&self) -> u16 {
+
+    /// The typographic family name if there is one, or the family name from the `name` table
+    pub fn family_name(&self) -> &str {
+        &self.family_name
+    }
+
+    /// The typographic subfamily name if there is one, or the subfamily name from the `name` table,
+    /// such as `"Bold"` or `"Italic"`
+    pub fn subfamily_name(&self) -> &str {
+        &self.subfamily_name
+    }
+
+    /// From 1 to 1000: 400 is normal, 700 is bold
+    pub(crate) fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    /// From 1 (ultra-condensed) to 9 (ultra-expanded): 5 is normal
+    pub(crate) fn width_class(&self) -> u16 {
+        self.width_class
+    }
+
+    /// Whether this face is italic or oblique
+    pub(crate) fn is_italic(&self) -> bool {
+        self.is_italic
+    }
+
+    /// The number of glyphs in this font. Glyph IDs are less than this.
+    pub fn glyph_count(&self) -> u16 {
         self.glyph_count
     }
 
@@ -143,16 +343,39 @@ impl Font {
         self.cmap.each_code_point(&self.bytes, f)
     }
 
-    pub(crate) fn glyph_id(&self, ch: char) -> Result<GlyphId, FontError> {
+    /// The glyph for a character in the `cmap` table, or `.notdef` if it is missing.
+    pub fn glyph_id(&self, ch: char) -> Result<GlyphId, FontError> {
         let ch = ch as u32;
         let result = match self.cmap {
             Cmap::Format4(ref table) => table.get(&self.bytes, ch),
             Cmap::Format12(ref table) => table.get(&self.bytes, ch),
         };
-        const NOTDEF_GLYPH: u16 = 0;
         Ok(GlyphId(result?.unwrap_or(NOTDEF_GLYPH)))
     }
 
+    /// Replace glyphs based on the `GSUB` lookups of enabled features, for example with ligatures.
+    ///
+    /// `clusters` has one entry per glyph. When several glyphs are replaced by one,
+    /// the result keeps the entry of the first.
+    pub(crate) fn substitute(
+        &self,
+        features: &FontFeatures,
+        glyphs: &mut Vec<GlyphId>,
+        clusters: &mut Vec<usize>,
+    ) -> Result<(), FontError> {
+        self.substitutions.apply(
+            &self.bytes,
+            |tag| features.is_enabled(tag.0),
+            glyphs,
+            clusters,
+        )
+    }
+
+    /// Whether the character map has a glyph other than `.notdef` for this character
+    pub(crate) fn has_glyph(&self, ch: char) -> Result<bool, FontError> {
+        Ok(self.glyph_id(ch)? != GlyphId(NOTDEF_GLYPH))
+    }
+
     pub(crate) fn glyph_width(
         &self,
         glyph_id: GlyphId,
@@ -168,7 +391,98 @@ impl Font {
             .get_unchecked(index)
             .advance_width()
             .read_from(&self.bytes)?;
-        Ok(self.to_ems(w))
+        let delta = self.advance_width_delta(glyph_id)?;
+        Ok(self.to_ems(w) + self.to_ems(euclid::Length::<_, FontDesignUnit>::new(delta)))
+    }
+
+    /// How much the pen moves down after a glyph in vertical text, from the `vmtx` table.
+    /// Without vertical metrics, this is one em for every glyph.
+    ///
+    /// FIXME: vertical metrics of variable font instances, from `VVAR` or `gvar`
+    pub(crate) fn glyph_vertical_advance(
+        &self,
+        glyph_id: GlyphId,
+    ) -> Result<euclid::Length<f32, Em>, FontError> {
+        Ok(match self.glyph_vertical_metrics(glyph_id)? {
+            Some((advance_height, _)) => self.to_ems(advance_height),
+            None => euclid::Length::new(1.),
+        })
+    }
+
+    /// The height above the baseline of a glyph’s vertical origin:
+    /// the point on its vertical center line that is at the pen position in vertical text.
+    ///
+    /// This is from the `VORG` table if the font has one, otherwise the top of the glyph’s
+    /// bounding box plus its top side bearing in `vmtx`.
+    /// Like in HarfBuzz, it falls back to the ascender.
+    pub(crate) fn glyph_vertical_origin(
+        &self,
+        glyph_id: GlyphId,
+    ) -> Result<euclid::Length<f32, Em>, FontError> {
+        let bytes = &**self.bytes;
+        if let Some(header) = self.vertical_origins {
+            let records = Slice::new(
+                header.followed_by::<VerticalOriginRecord>(),
+                header.vertical_origin_y_metrics_count().read_from(bytes)?,
+            );
+            let search = records.binary_search_by_key(&glyph_id.0, |record| {
+                record.glyph_index().read_from(bytes)
+            })?;
+            let y = match search {
+                Some(record) => record.vertical_origin_y().read_from(bytes)?,
+                None => header.default_vertical_origin_y().read_from(bytes)?,
+            };
+            return Ok(self.to_ems(y))
+        }
+        if let Some((_, top_side_bearing)) = self.glyph_vertical_metrics(glyph_id)? {
+            if let Some(bounding_box) = self.glyph_bounding_box(glyph_id)? {
+                return Ok(bounding_box.max_y_typed() + self.to_ems(top_side_bearing))
+            }
+        }
+        Ok(self.ascender())
+    }
+
+    /// The advance height and top side bearing of a glyph, if the font has vertical metrics
+    fn glyph_vertical_metrics(
+        &self,
+        glyph_id: GlyphId,
+    ) -> Result<Option<(UFWord, FWord)>, FontError> {
+        let metrics = match self.vertical_metrics {
+            Some(metrics) => metrics,
+            None => return Ok(None),
+        };
+        let last_index = match metrics.count().checked_sub(1) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let index = u32::from(glyph_id.0);
+        let record = metrics.get_unchecked(cmp::min(index, last_index));
+        let advance_height = record.advance_height().read_from(&self.bytes)?;
+        let top_side_bearing = if index <= last_index {
+            record.top_side_bearing().read_from(&self.bytes)?
+        } else {
+            // Glyphs after the last long record only have a top side bearing
+            record
+                .followed_by::<FWord>()
+                .offset(index - last_index - 1)
+                .read_from(&self.bytes)?
+        };
+        Ok(Some((advance_height, top_side_bearing)))
+    }
+
+    /// The adjustment to the advance width of `left` when it is followed by `right`,
+    /// from the `GPOS` or `kern` table. Negative values bring the two glyphs closer.
+    pub(crate) fn kerning(
+        &self,
+        left: GlyphId,
+        right: GlyphId,
+    ) -> Result<euclid::Length<f32, Em>, FontError> {
+        Ok(self.to_ems(self.kerning.get(&self.bytes, left, right)?))
+    }
+
+    /// Convert a length in the design units of this font, for example from a shaping engine.
+    pub(crate) fn design_units_to_ems(&self, length: i32) -> euclid::Length<f32, Em> {
+        self.to_ems(euclid::Length::<_, FontDesignUnit>::new(length))
     }
 
     fn to_ems<T>(&self, length: euclid::Length<T, FontDesignUnit>) -> euclid::Length<f32, Em>
@@ -196,30 +510,101 @@ impl Font {
     pub(crate) fn max_y(&self) -> euclid::Length<f32, Em> {
         self.to_ems(self.max_y)
     }
+
+    /// The bounding box of one glyph, or `None` for glyphs without an outline.
+    ///
+    /// For TrueType outlines this is the box stored in the `glyf` table, which for some fonts
+    /// is slightly larger than the outline. Otherwise, it contains every point of the outline
+    /// including the control points of curves.
+    pub fn glyph_bounding_box(
+        &self,
+        glyph: GlyphId,
+    ) -> Result<Option<euclid::TypedRect<f32, Em>>, FontError> {
+        if self.outline_format == OutlineFormat::TrueType
+            && !self.is_variation_instance()
+            && glyph.0 < self.glyph_count
+        {
+            let data = self.glyph_data(glyph)?;
+            if data.count() == 0 {
+                return Ok(None)
+            }
+            let header = data.get_unchecked(0).cast::<GlyphHeader>();
+            let min = euclid::TypedPoint2D::from_lengths(
+                self.to_ems(header.min_x().read_from(&self.bytes)?),
+                self.to_ems(header.min_y().read_from(&self.bytes)?),
+            );
+            let max = euclid::TypedPoint2D::from_lengths(
+                self.to_ems(header.max_x().read_from(&self.bytes)?),
+                self.to_ems(header.max_y().read_from(&self.bytes)?),
+            );
+            return Ok(Some(euclid::TypedRect::from_points([min, max])))
+        }
+        Ok(self.glyph_outline(glyph)?.bounding_box())
+    }
 }
 
-fn read_postscript_name(
+/// Decompress a WOFF or WOFF2 file, or return other font files unchanged.
+fn decode_web_font(bytes: Cow<'static, [u8]>) -> Result<Cow<'static, [u8]>, FontError> {
+    let offset_table = Position::<OffsetSubtable>::initial();
+    Ok(match offset_table.scaler_type().read_from(&bytes)? {
+        WOFF_SIGNATURE => Cow::Owned(decode_woff(&bytes)?),
+        WOFF2_SIGNATURE => Cow::Owned(decode_woff2(&bytes)?),
+        _ => bytes,
+    })
+}
+
+/// The number of faces in a font file: one, or any number for a collection file
+fn face_count(bytes: &[u8]) -> Result<u32, FontError> {
+    let header = Position::<OffsetSubtable>::initial().cast::<CollectionHeader>();
+    if header.signature().read_from(bytes)? == COLLECTION_SIGNATURE {
+        header.font_count().read_from(bytes)
+    } else {
+        Ok(1)
+    }
+}
+
+/// The start of the table directory of the face at `index` in a font file
+fn face_offset_table(bytes: &[u8], index: u32) -> Result<Position<OffsetSubtable>, FontError> {
+    let file_start = Position::<OffsetSubtable>::initial();
+    let header = file_start.cast::<CollectionHeader>();
+    if header.signature().read_from(bytes)? != COLLECTION_SIGNATURE {
+        if index != 0 {
+            Err(FontError::FaceIndexOutOfRange)?
+        }
+        return Ok(file_start)
+    }
+    if index >= header.font_count().read_from(bytes)? {
+        Err(FontError::FaceIndexOutOfRange)?
+    }
+    let offset = header.followed_by::<u32>().offset(index).read_from(bytes)?;
+    Ok(file_start.offset_bytes(offset))
+}
+
+const FAMILY_NAME: u16 = 1;
+const SUBFAMILY_NAME: u16 = 2;
+const POSTSCRIPT_NAME: u16 = 6;
+const TYPOGRAPHIC_FAMILY_NAME: u16 = 16;
+const TYPOGRAPHIC_SUBFAMILY_NAME: u16 = 17;
+
+/// Return the string for `name_id` in the `name` table, preferably in English.
+fn read_name(
     bytes: &[u8],
     table_directory: Slice<TableDirectoryEntry>,
-) -> Result<String, FontError> {
+    name_id: u16,
+) -> Result<Option<String>, FontError> {
     /// Macintosh encodings seem to be ASCII-compatible, and a PostScript name is within ASCII
     fn decode_macintosh(string_bytes: &[u8]) -> String {
         String::from_utf8_lossy(string_bytes).into_owned()
     }
 
-    /// Latin-1 range only
-    fn decode_ucs2(string_bytes: &[u8]) -> String {
-        string_bytes
+    fn decode_utf16_be(string_bytes: &[u8]) -> String {
+        let code_units = string_bytes
             .chunks(2)
-            .map(|chunk| {
-                if chunk.len() < 2 || chunk[0] != 0 {
-                    '\u{FFFD}'
-                } else {
-                    chunk[1] as char
-                }
-            })
+            .map(|chunk| u16::from(chunk[0]) << 8 | u16::from(*chunk.get(1).unwrap_or(&0)));
+        std::char::decode_utf16(code_units)
+            .map(|result| result.unwrap_or('\u{FFFD}'))
             .collect::<String>()
-    };
+    }
 
     let naming_table_header = table_directory.find_table::<NamingTableHeader>(bytes)?;
     let name_records = Slice::new(
@@ -236,27 +621,40 @@ fn read_postscript_name(
         .read_from(bytes)
     };
 
+    let mut found = None;
     for record in name_records {
-        const POSTSCRIPT_NAME: u16 = 6;
-        if record.name_id().read_from(bytes)? != POSTSCRIPT_NAME {
+        if record.name_id().read_from(bytes)? != name_id {
             continue
         }
 
         const MACINTOSH: u16 = 1;
         const MICROSOFT: u16 = 3;
         const UNICODE_BMP: u16 = 1;
-        let postscript_name = match (
+        const MACINTOSH_ENGLISH: u16 = 0;
+        const MICROSOFT_ENGLISH_US: u16 = 0x409;
+        let language = record.language_id().read_from(bytes)?;
+        let (string, is_english) = match (
             record.platform_id().read_from(bytes)?,
             record.encoding_id().read_from(bytes)?,
         ) {
-            (MACINTOSH, _) => decode_macintosh(string_bytes(record)?),
-            (MICROSOFT, UNICODE_BMP) => decode_ucs2(string_bytes(record)?),
+            (MACINTOSH, _) => (
+                decode_macintosh(string_bytes(record)?),
+                language == MACINTOSH_ENGLISH,
+            ),
+            (MICROSOFT, UNICODE_BMP) => (
+                decode_utf16_be(string_bytes(record)?),
+                language == MICROSOFT_ENGLISH_US,
+            ),
             _ => continue,
         };
-        return Ok(postscript_name)
+        if is_english {
+            return Ok(Some(string))
+        }
+        if found.is_none() {
+            found = Some(string)
+        }
     }
-
-    Err(FontError::NoSupportedPostscriptName)
+    Ok(found)
 }
 
 trait SfntTable {
@@ -270,4 +668,35 @@ impl Slice<TableDirectoryEntry> {
         let offset = entry.table_offset().read_from(bytes)?;
         Ok(Position::<OffsetSubtable>::initial().offset_bytes(offset))
     }
+
+    /// The position of a table, or `None` if the font doesn’t have it
+    fn optional_table<T: SfntTable>(&self, bytes: &[u8]) -> Result<Option<Position<T>>, FontError> {
+        match self.find_table::<T>(bytes) {
+            Ok(table) => Ok(Some(table)),
+            Err(FontError::MissingTable) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The contents of the table with this tag, if the font has it
+    fn find_table_bytes<'a>(
+        &self,
+        bytes: &'a [u8],
+        tag: Tag,
+    ) -> Result<Option<&'a [u8]>, FontError> {
+        let search = self.binary_search_by_key(&tag, |entry| entry.tag().read_from(bytes))?;
+        match search {
+            Some(entry) => Ok(Some(entry.table_bytes(bytes)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Position<TableDirectoryEntry> {
+    /// The contents of this entry’s table
+    fn table_bytes(self, bytes: &[u8]) -> Result<&[u8], FontError> {
+        let start = Position::<OffsetSubtable>::initial()
+            .offset_bytes(self.table_offset().read_from(bytes)?);
+        Slice::<u8>::new(start, self.length().read_from(bytes)?).read_from(bytes)
+    }
 }
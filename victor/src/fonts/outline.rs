@@ -0,0 +1,276 @@
+//! Glyph outlines as paths, for TrueType and CFF fonts
+
+use crate::fonts::glyf::{Glyph, ARGS_ARE_XY_VALUES, SCALED_COMPONENT_OFFSET};
+use crate::fonts::variations::PHANTOM_POINT_COUNT;
+use crate::fonts::{Em, Font, FontError, GlyphId, OutlineFormat};
+
+/// Composite glyphs nested deeper than this are ignored, in case of a cycle.
+pub(in crate::fonts) const MAX_NESTING_DEPTH: u32 = 10;
+
+/// The outline of a glyph, made of closed contours.
+///
+/// Coordinates are in ems relative to the glyph’s origin on the baseline,
+/// with the y axis pointing up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    pub segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Start a new contour at this point
+    MoveTo(euclid::TypedPoint2D<f32, Em>),
+
+    /// A straight line to this point
+    LineTo(euclid::TypedPoint2D<f32, Em>),
+
+    /// A quadratic Bézier curve with one control point, to the second point
+    QuadTo(euclid::TypedPoint2D<f32, Em>, euclid::TypedPoint2D<f32, Em>),
+
+    /// A cubic Bézier curve with two control points, to the third point
+    CubicTo(
+        euclid::TypedPoint2D<f32, Em>,
+        euclid::TypedPoint2D<f32, Em>,
+        euclid::TypedPoint2D<f32, Em>,
+    ),
+
+    /// A straight line back to the start of the current contour, which ends it
+    Close,
+}
+
+impl Path {
+    /// The smallest rectangle that contains every point of this path, including control points,
+    /// or `None` for an empty path.
+    ///
+    /// This contains the outline, but can be larger where control points are outside of it.
+    pub fn bounding_box(&self) -> Option<euclid::TypedRect<f32, Em>> {
+        let mut points = Vec::new();
+        for segment in &self.segments {
+            match *segment {
+                PathSegment::MoveTo(p) | PathSegment::LineTo(p) => points.push(p),
+                PathSegment::QuadTo(p1, p2) => points.extend_from_slice(&[p1, p2]),
+                PathSegment::CubicTo(p1, p2, p3) => points.extend_from_slice(&[p1, p2, p3]),
+                PathSegment::Close => {}
+            }
+        }
+        if points.is_empty() {
+            None
+        } else {
+            Some(euclid::TypedRect::from_points(points))
+        }
+    }
+}
+
+/// Building a path from coordinates in font design units
+pub(in crate::fonts) struct PathBuilder {
+    path: Path,
+    ems_per_design_unit: f32,
+}
+
+impl PathBuilder {
+    fn point(&self, x: f32, y: f32) -> euclid::TypedPoint2D<f32, Em> {
+        euclid::TypedPoint2D::new(x * self.ems_per_design_unit, y * self.ems_per_design_unit)
+    }
+
+    pub(in crate::fonts) fn move_to(&mut self, x: f32, y: f32) {
+        let segment = PathSegment::MoveTo(self.point(x, y));
+        self.path.segments.push(segment)
+    }
+
+    pub(in crate::fonts) fn line_to(&mut self, x: f32, y: f32) {
+        let segment = PathSegment::LineTo(self.point(x, y));
+        self.path.segments.push(segment)
+    }
+
+    pub(in crate::fonts) fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let segment = PathSegment::QuadTo(self.point(x1, y1), self.point(x, y));
+        self.path.segments.push(segment)
+    }
+
+    pub(in crate::fonts) fn cubic_to(
+        &mut self,
+        (x1, y1): (f32, f32),
+        (x2, y2): (f32, f32),
+        (x, y): (f32, f32),
+    ) {
+        let segment =
+            PathSegment::CubicTo(self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        self.path.segments.push(segment)
+    }
+
+    pub(in crate::fonts) fn close(&mut self) {
+        self.path.segments.push(PathSegment::Close)
+    }
+}
+
+/// The points of a TrueType outline in font design units
+#[derive(Default)]
+struct Contours {
+    points: Vec<ContourPoint>,
+
+    /// The index in `points` of the last point of each contour
+    end_points: Vec<usize>,
+}
+
+#[derive(Copy, Clone)]
+struct ContourPoint {
+    x: f32,
+    y: f32,
+    on_curve: bool,
+}
+
+impl Font {
+    /// The outline of a glyph, with the components of composite glyphs resolved.
+    ///
+    /// For an instance of a variable font, this is the outline of the instance.
+    /// The path is empty for glyphs without an outline, such as a space,
+    /// and for glyph IDs not in the font.
+    pub fn glyph_outline(&self, glyph: GlyphId) -> Result<Path, FontError> {
+        let mut builder = PathBuilder {
+            path: Path::default(),
+            ems_per_design_unit: 1. / self.font_design_units_per_em.get(),
+        };
+        if glyph.0 < self.glyph_count {
+            match self.outline_format {
+                OutlineFormat::TrueType => {
+                    let mut contours = Contours::default();
+                    self.truetype_contours(glyph, 0, &mut contours)?;
+                    let mut start = 0;
+                    for &end in &contours.end_points {
+                        if let Some(contour) = contours.points.get(start..=end) {
+                            add_quadratic_contour(&mut builder, contour)
+                        }
+                        start = end + 1
+                    }
+                }
+                OutlineFormat::Cff => self.cff_outline(glyph, &mut builder)?,
+            }
+        }
+        Ok(builder.path)
+    }
+
+    /// Append the points of a glyph to `contours`, recursively for the components
+    /// of a composite glyph.
+    fn truetype_contours(
+        &self,
+        glyph: GlyphId,
+        depth: u32,
+        contours: &mut Contours,
+    ) -> Result<(), FontError> {
+        match self.glyph(glyph)? {
+            Glyph::Empty => {}
+            Glyph::Simple(simple) => {
+                let deltas = self.glyph_deltas(
+                    glyph,
+                    &simple.points,
+                    &simple.end_points,
+                    simple.points.len() + PHANTOM_POINT_COUNT,
+                )?;
+                let first = contours.points.len();
+                contours
+                    .points
+                    .extend(simple.points.iter().zip(&deltas).map(|(point, &(dx, dy))| {
+                        ContourPoint {
+                            x: point.x as f32 + dx,
+                            y: point.y as f32 + dy,
+                            on_curve: point.on_curve,
+                        }
+                    }));
+                contours.end_points.extend(
+                    simple
+                        .end_points
+                        .iter()
+                        .map(|&end| first + usize::from(end)),
+                )
+            }
+            Glyph::Composite(composite) => {
+                if depth >= MAX_NESTING_DEPTH {
+                    return Ok(())
+                }
+                let deltas = self.glyph_deltas(
+                    glyph,
+                    &[],
+                    &[],
+                    composite.components.len() + PHANTOM_POINT_COUNT,
+                )?;
+                for (component, &(dx, dy)) in composite.components.iter().zip(&deltas) {
+                    let mut component_contours = Contours::default();
+                    self.truetype_contours(component.glyph, depth + 1, &mut component_contours)?;
+                    let [xx, xy, yx, yy] = component.transform;
+                    let transform = |x: f32, y: f32| (xx * x + yx * y, xy * x + yy * y);
+                    for point in &mut component_contours.points {
+                        let (x, y) = transform(point.x, point.y);
+                        point.x = x;
+                        point.y = y;
+                    }
+                    let (a, b) = component.arguments;
+                    let offset = if component.flags & ARGS_ARE_XY_VALUES != 0 {
+                        let (x, y) = (a as f32 + dx, b as f32 + dy);
+                        if component.flags & SCALED_COMPONENT_OFFSET != 0 {
+                            transform(x, y)
+                        } else {
+                            (x, y)
+                        }
+                    } else {
+                        // Align a point of the glyph so far with a point of the component
+                        let point = contours.points.get(a as usize);
+                        let component_point = component_contours.points.get(b as usize);
+                        match (point, component_point) {
+                            (Some(p), Some(c)) => (p.x - c.x, p.y - c.y),
+                            _ => (0., 0.),
+                        }
+                    };
+                    let first = contours.points.len();
+                    contours
+                        .points
+                        .extend(component_contours.points.iter().map(|point| ContourPoint {
+                            x: point.x + offset.0,
+                            y: point.y + offset.1,
+                            on_curve: point.on_curve,
+                        }));
+                    contours
+                        .end_points
+                        .extend(component_contours.end_points.iter().map(|&end| first + end))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add a TrueType contour, where two consecutive control points
+/// imply an on-curve point in the middle of them.
+fn add_quadratic_contour(builder: &mut PathBuilder, contour: &[ContourPoint]) {
+    let midpoint = |a: &ContourPoint, b: &ContourPoint| ((a.x + b.x) / 2., (a.y + b.y) / 2.);
+    let (first, last) = match (contour.first(), contour.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return,
+    };
+    // A contour whose first point is a control point starts at its last point,
+    // or between them if that is also a control point.
+    let (start, points) = if first.on_curve {
+        ((first.x, first.y), &contour[1..])
+    } else if last.on_curve {
+        ((last.x, last.y), &contour[..contour.len() - 1])
+    } else {
+        (midpoint(last, first), contour)
+    };
+    builder.move_to(start.0, start.1);
+    let mut control: Option<&ContourPoint> = None;
+    for point in points {
+        match (point.on_curve, control) {
+            (true, None) => builder.line_to(point.x, point.y),
+            (true, Some(c)) => builder.quad_to(c.x, c.y, point.x, point.y),
+            (false, Some(c)) => {
+                let (x, y) = midpoint(c, point);
+                builder.quad_to(c.x, c.y, x, y)
+            }
+            (false, None) => {}
+        }
+        control = if point.on_curve { None } else { Some(point) };
+    }
+    if let Some(c) = control {
+        builder.quad_to(c.x, c.y, start.0, start.1)
+    }
+    builder.close()
+}
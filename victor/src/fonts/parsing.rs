@@ -106,6 +106,21 @@ fn u32_from_bytes(bytes: [u8; 4]) -> u32 {
     unsafe { mem::transmute(bytes) }
 }
 
+impl ReadFromBytes for u8 {
+    fn read_from(bytes: &[u8]) -> Result<Self, FontError> {
+        bytes
+            .first()
+            .cloned()
+            .ok_or(FontError::OffsetPlusLengthBeyondEof)
+    }
+}
+
+impl ReadFromBytes for i8 {
+    fn read_from(bytes: &[u8]) -> Result<Self, FontError> {
+        u8::read_from(bytes).map(|byte| byte as i8)
+    }
+}
+
 impl ReadFromBytes for i16 {
     fn read_from(bytes: &[u8]) -> Result<Self, FontError> {
         Ok(i16::from_be(i16_from_bytes(ReadFromBytes::read_from(
@@ -130,6 +145,12 @@ impl ReadFromBytes for u32 {
     }
 }
 
+impl ReadFromBytes for i32 {
+    fn read_from(bytes: &[u8]) -> Result<Self, FontError> {
+        u32::read_from(bytes).map(|value| value as i32)
+    }
+}
+
 impl<T, Src, Dst> ReadFromBytes for euclid::TypedScale<T, Src, Dst>
 where
     T: ReadFromBytes,
@@ -24,6 +24,17 @@ macro_rules! include_font {
 /// The regular sans-serif face of the [Bitstream Vera](https://www.gnome.org/fonts/) font family.
 pub static BITSTREAM_VERA_SANS: LazyStaticFont = include_font!("../../fonts/vera/Vera.ttf");
 
+/// The bold sans-serif face of the Bitstream Vera font family.
+pub static BITSTREAM_VERA_SANS_BOLD: LazyStaticFont = include_font!("../../fonts/vera/VeraBd.ttf");
+
+/// The oblique sans-serif face of the Bitstream Vera font family.
+pub static BITSTREAM_VERA_SANS_OBLIQUE: LazyStaticFont =
+    include_font!("../../fonts/vera/VeraIt.ttf");
+
+/// The bold oblique sans-serif face of the Bitstream Vera font family.
+pub static BITSTREAM_VERA_SANS_BOLD_OBLIQUE: LazyStaticFont =
+    include_font!("../../fonts/vera/VeraBI.ttf");
+
 /// A lazily-parsed font backed by a static bytes slice.
 pub struct LazyStaticFont {
     /// The raw data for this font
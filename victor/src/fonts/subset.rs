@@ -0,0 +1,154 @@
+//! Font files for embedding in PDF: subsets with only the glyphs used in a document,
+//! or single faces extracted from collections
+
+use crate::fonts::glyf::Glyph;
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::types::Tag;
+use crate::fonts::writing::{set_u16, write_font_file};
+use crate::fonts::{Font, FontError, GlyphId, OutlineFormat, NOTDEF_GLYPH};
+use crate::fonts::{CFF_SIGNATURE, COLLECTION_SIGNATURE, TRUETYPE_SIGNATURE};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+/// Tables copied unchanged when they exist, for hinting.
+/// PDF requires `head`, `hhea`, `loca`, `maxp`, `cvt `, `prep`, `glyf`, `hmtx`, and `fpgm`:
+/// <https://www.adobe.com/content/dam/acom/en/devnet/pdf/PDF32000_2008.pdf#G8.1910662>
+const HINTING_TABLES: [Tag; 3] = [Tag(*b"cvt "), Tag(*b"fpgm"), Tag(*b"prep")];
+
+// Byte offsets of fields that are modified in copies of some tables
+const HEAD_INDEX_TO_LOC_FORMAT: usize = 50;
+const HHEA_NUMBER_OF_LONG_HORIZONTAL_METRICS: usize = 34;
+const MAXP_NUM_GLYPHS: usize = 4;
+
+impl Font {
+    /// Return a TrueType font file with only the tables that PDF needs,
+    /// where glyphs other than `.notdef`, those in `glyphs`, and their components are empty.
+    ///
+    /// Glyph IDs are unchanged, but glyphs after the last one used are removed.
+    /// For an instance of a variable font, outlines and advance widths are those of the instance
+    /// and the tables for variations are not included.
+    /// This fails for fonts without `glyf` and `loca` tables.
+    pub(crate) fn subset<I>(&self, glyphs: I) -> Result<Vec<u8>, FontError>
+    where
+        I: IntoIterator<Item = GlyphId>,
+    {
+        let bytes = self.bytes();
+        let table_directory = self.table_directory;
+
+        // Add the components of composite glyphs, recursively
+        let mut included = BTreeSet::new();
+        let mut pending: Vec<GlyphId> = glyphs.into_iter().collect();
+        pending.push(GlyphId(NOTDEF_GLYPH));
+        while let Some(glyph) = pending.pop() {
+            if glyph.0 >= self.glyph_count() || !included.insert(glyph.0) {
+                continue
+            }
+            if let Glyph::Composite(composite) = self.glyph(glyph)? {
+                pending.extend(composite.components.iter().map(|component| component.glyph))
+            }
+        }
+        let glyph_count = included.iter().next_back().map_or(1, |&last| last + 1);
+
+        // Glyph data is 4-byte aligned, with long offsets in `loca`
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity((glyph_count as usize + 1) * 4);
+        let mut hmtx = Vec::with_capacity(glyph_count as usize * 4);
+        let long_metrics_count = self.horizontal_metrics.count();
+        let last_long_metrics = long_metrics_count
+            .checked_sub(1)
+            .ok_or(FontError::NoHorizontalGlyphMetrics)?;
+        for id in 0..glyph_count {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            // Glyphs after the last long record use its advance width
+            // and have their own left side bearing in an array that follows.
+            let metrics = self
+                .horizontal_metrics
+                .get_unchecked(u32::from(id).min(last_long_metrics));
+            let mut advance_width = metrics.advance_width().read_from(bytes)?.get();
+            let mut left_side_bearing = match u32::from(id).checked_sub(long_metrics_count) {
+                None => metrics.left_side_bearing().read_from(bytes)?,
+                Some(index) => metrics
+                    .followed_by::<i16>()
+                    .offset(index)
+                    .read_from(bytes)?,
+            };
+            if included.contains(&id) {
+                if self.is_variation_instance() {
+                    // Outlines and metrics of this instance of a variable font,
+                    // for PDF readers that do not support font variations
+                    if let Some(min_x) = self.write_glyph_instance(GlyphId(id), &mut glyf)? {
+                        left_side_bearing = min_x
+                    }
+                    let delta = self.advance_width_delta(GlyphId(id))?;
+                    advance_width = (f32::from(advance_width) + delta)
+                        .round()
+                        .max(0.)
+                        .min(f32::from(u16::max_value())) as u16
+                } else {
+                    glyf.extend_from_slice(self.glyph_data(GlyphId(id))?.read_from(bytes)?)
+                }
+                while glyf.len() % 4 != 0 {
+                    glyf.push(0)
+                }
+            }
+            hmtx.extend_from_slice(&advance_width.to_be_bytes());
+            hmtx.extend_from_slice(&left_side_bearing.to_be_bytes());
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let copy = |tag: &[u8; 4]| -> Result<Vec<u8>, FontError> {
+            Ok(table_directory
+                .find_table_bytes(bytes, Tag(*tag))?
+                .ok_or(FontError::MissingTable)?
+                .to_vec())
+        };
+        let mut head = copy(b"head")?;
+        set_u16(&mut head, HEAD_INDEX_TO_LOC_FORMAT, 1);
+        let mut hhea = copy(b"hhea")?;
+        set_u16(
+            &mut hhea,
+            HHEA_NUMBER_OF_LONG_HORIZONTAL_METRICS,
+            glyph_count,
+        );
+        let mut maxp = copy(b"maxp")?;
+        set_u16(&mut maxp, MAXP_NUM_GLYPHS, glyph_count);
+
+        let mut tables = vec![
+            (Tag(*b"glyf"), glyf),
+            (Tag(*b"head"), head),
+            (Tag(*b"hhea"), hhea),
+            (Tag(*b"hmtx"), hmtx),
+            (Tag(*b"loca"), loca),
+            (Tag(*b"maxp"), maxp),
+        ];
+        for &tag in &HINTING_TABLES {
+            if let Some(table) = table_directory.find_table_bytes(bytes, tag)? {
+                tables.push((tag, table.to_vec()))
+            }
+        }
+        Ok(write_font_file(TRUETYPE_SIGNATURE, tables))
+    }
+
+    /// Return a font file with only this face:
+    /// the whole file, or a copy of this face’s tables for a face of a collection.
+    pub(crate) fn face_bytes(&self) -> Result<Cow<'_, [u8]>, FontError> {
+        let bytes = self.bytes();
+        let header = Position::<OffsetSubtable>::initial().cast::<CollectionHeader>();
+        if header.signature().read_from(bytes)? != COLLECTION_SIGNATURE {
+            return Ok(Cow::Borrowed(bytes))
+        }
+        let mut tables = Vec::with_capacity(self.table_directory.count() as usize);
+        for entry in self.table_directory {
+            tables.push((
+                entry.tag().read_from(bytes)?,
+                entry.table_bytes(bytes)?.to_vec(),
+            ))
+        }
+        let signature = match self.outline_format {
+            OutlineFormat::TrueType => TRUETYPE_SIGNATURE,
+            OutlineFormat::Cff => CFF_SIGNATURE,
+        };
+        Ok(Cow::Owned(write_font_file(signature, tables)))
+    }
+}
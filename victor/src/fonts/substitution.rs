@@ -0,0 +1,443 @@
+use crate::fonts::layout_common::*;
+use crate::fonts::parsing::{Position, Slice};
+use crate::fonts::tables::*;
+use crate::fonts::types::Tag;
+use crate::fonts::{FontError, GlyphId};
+
+/// The features and lookups of the `GSUB` table, empty if the font doesn’t have one
+#[derive(Default)]
+pub(in crate::fonts) struct Substitutions {
+    features: Vec<Feature>,
+    lookups: Vec<Lookup>,
+}
+
+const SINGLE: u16 = 1;
+const LIGATURE: u16 = 4;
+const CHAINED_CONTEXT: u16 = 6;
+const EXTENSION: u16 = 7;
+
+/// Chained context lookups can apply other lookups, including other chained context lookups.
+/// Limit how deep this goes in case a font contains a cycle.
+const MAX_NESTING_LEVEL: u8 = 8;
+
+impl Substitutions {
+    pub(in crate::fonts) fn parse(
+        bytes: &[u8],
+        table_directory: Slice<TableDirectoryEntry>,
+    ) -> Result<Self, FontError> {
+        let header = match table_directory.optional_table::<GlyphSubstitutionHeader>(bytes)? {
+            Some(header) => header,
+            None => return Ok(Substitutions::default()),
+        };
+        Ok(Substitutions {
+            features: parse_features(
+                bytes,
+                header.offset_bytes(header.feature_list_offset().read_from(bytes)?),
+            )?,
+            lookups: parse_lookups(
+                bytes,
+                header.offset_bytes(header.lookup_list_offset().read_from(bytes)?),
+                EXTENSION,
+            )?,
+        })
+    }
+
+    /// Apply the lookups of features that match `enabled` to the whole glyph sequence, in order.
+    ///
+    /// `clusters` has one entry per glyph. When several glyphs are replaced by one,
+    /// it keeps the entry of the first.
+    pub(in crate::fonts) fn apply<F>(
+        &self,
+        bytes: &[u8],
+        enabled: F,
+        glyphs: &mut Vec<GlyphId>,
+        clusters: &mut Vec<usize>,
+    ) -> Result<(), FontError>
+    where
+        F: Fn(Tag) -> bool,
+    {
+        if self.lookups.is_empty() {
+            return Ok(())
+        }
+        for lookup_index in lookup_indices(&self.features, enabled) {
+            let mut i = 0;
+            while i < glyphs.len() {
+                i = match self.apply_lookup_at(bytes, lookup_index, glyphs, clusters, i, 0)? {
+                    Some(next) => next,
+                    None => i + 1,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the first subtable of a lookup that matches at index `i` of the glyph sequence,
+    /// and return the index to continue from, or return `None` if no subtable matches.
+    ///
+    /// FIXME: support the multiple, alternate, context, and reverse chained context lookup types.
+    fn apply_lookup_at(
+        &self,
+        bytes: &[u8],
+        lookup_index: u16,
+        glyphs: &mut Vec<GlyphId>,
+        clusters: &mut Vec<usize>,
+        i: usize,
+        nesting_level: u8,
+    ) -> Result<Option<usize>, FontError> {
+        let lookup = match self.lookups.get(usize::from(lookup_index)) {
+            Some(lookup) => lookup,
+            None => return Ok(None),
+        };
+        for &subtable in &lookup.subtables {
+            let next = match lookup.lookup_type {
+                SINGLE => single(bytes, subtable, glyphs, i)?,
+                LIGATURE => ligature(bytes, subtable.cast(), glyphs, clusters, i)?,
+                CHAINED_CONTEXT if nesting_level < MAX_NESTING_LEVEL => {
+                    self.chained_context(bytes, subtable, glyphs, clusters, i, nesting_level)?
+                }
+                _ => None,
+            };
+            if next.is_some() {
+                return Ok(next)
+            }
+        }
+        Ok(None)
+    }
+
+    fn chained_context(
+        &self,
+        bytes: &[u8],
+        subtable: Position<u16>,
+        glyphs: &mut Vec<GlyphId>,
+        clusters: &mut Vec<usize>,
+        i: usize,
+        nesting_level: u8,
+    ) -> Result<Option<usize>, FontError> {
+        let first = glyphs[i];
+        match subtable.read_from(bytes)? {
+            // Rules with sequences of glyph IDs, in a rule set for each covered first glyph
+            1 => {
+                let header = subtable.cast::<ChainedContextFormat1>();
+                let coverage = header.offset_bytes(header.coverage_offset().read_from(bytes)?);
+                let rule_set_index = match coverage_index(bytes, coverage, first)? {
+                    Some(index) => index,
+                    None => return Ok(None),
+                };
+                let rule_set_offsets = Slice::new(
+                    header.followed_by::<u16>(),
+                    header.rule_set_count().read_from(bytes)?,
+                );
+                if rule_set_index >= rule_set_offsets.count() {
+                    return Ok(None)
+                }
+                let rule_set_offset = rule_set_offsets
+                    .get_unchecked(rule_set_index)
+                    .read_from(bytes)?;
+                let same_glyph = |id: u16, glyph: GlyphId| Ok(id == glyph.0);
+                for rule in rule_set(bytes, header.offset_bytes(rule_set_offset))? {
+                    let next = self.apply_chain_rule(
+                        bytes,
+                        &rule,
+                        (same_glyph, same_glyph, same_glyph),
+                        glyphs,
+                        clusters,
+                        i,
+                        nesting_level,
+                    )?;
+                    if next.is_some() {
+                        return Ok(next)
+                    }
+                }
+                Ok(None)
+            }
+            // Rules with sequences of classes, in a class set for each class of the first glyph
+            2 => {
+                let header = subtable.cast::<ChainedContextFormat2>();
+                let coverage = header.offset_bytes(header.coverage_offset().read_from(bytes)?);
+                if coverage_index(bytes, coverage, first)?.is_none() {
+                    return Ok(None)
+                }
+                let class_definition =
+                    |offset: u16| -> Position<u16> { header.offset_bytes(offset) };
+                let backtrack_classes =
+                    class_definition(header.backtrack_class_def_offset().read_from(bytes)?);
+                let input_classes =
+                    class_definition(header.input_class_def_offset().read_from(bytes)?);
+                let lookahead_classes =
+                    class_definition(header.lookahead_class_def_offset().read_from(bytes)?);
+                let class_set_offsets = Slice::new(
+                    header.followed_by::<u16>(),
+                    header.class_set_count().read_from(bytes)?,
+                );
+                let first_class = u32::from(class(bytes, input_classes, first)?);
+                if first_class >= class_set_offsets.count() {
+                    return Ok(None)
+                }
+                let class_set_offset = class_set_offsets
+                    .get_unchecked(first_class)
+                    .read_from(bytes)?;
+                if class_set_offset == 0 {
+                    return Ok(None)
+                }
+                let in_class = |class_definition: Position<u16>| {
+                    move |class_value: u16, glyph: GlyphId| {
+                        Ok(class(bytes, class_definition, glyph)? == class_value)
+                    }
+                };
+                let predicates = (
+                    in_class(backtrack_classes),
+                    in_class(input_classes),
+                    in_class(lookahead_classes),
+                );
+                for rule in rule_set(bytes, header.offset_bytes(class_set_offset))? {
+                    let next = self.apply_chain_rule(
+                        bytes,
+                        &rule,
+                        predicates,
+                        glyphs,
+                        clusters,
+                        i,
+                        nesting_level,
+                    )?;
+                    if next.is_some() {
+                        return Ok(next)
+                    }
+                }
+                Ok(None)
+            }
+            // A single rule with sequences of offsets to coverage tables
+            3 => {
+                let rule = chain_rule(bytes, subtable.followed_by(), true)?;
+                let covered = |offset: u16, glyph: GlyphId| {
+                    Ok(coverage_index(bytes, subtable.offset_bytes(offset), glyph)?.is_some())
+                };
+                self.apply_chain_rule(
+                    bytes,
+                    &rule,
+                    (covered, covered, covered),
+                    glyphs,
+                    clusters,
+                    i,
+                    nesting_level,
+                )
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// If the glyph sequence matches `rule` at index `i`, apply the rule’s lookups
+    /// and return the index after the input sequence.
+    ///
+    /// `predicates` test whether an item of the backtrack, input, and lookahead sequences
+    /// (respectively) of the rule matches a glyph.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_chain_rule<B, I, L>(
+        &self,
+        bytes: &[u8],
+        rule: &ChainRule,
+        predicates: (B, I, L),
+        glyphs: &mut Vec<GlyphId>,
+        clusters: &mut Vec<usize>,
+        i: usize,
+        nesting_level: u8,
+    ) -> Result<Option<usize>, FontError>
+    where
+        B: Fn(u16, GlyphId) -> Result<bool, FontError>,
+        I: Fn(u16, GlyphId) -> Result<bool, FontError>,
+        L: Fn(u16, GlyphId) -> Result<bool, FontError>,
+    {
+        let (backtrack, input, lookahead) = predicates;
+        // In formats 1 and 2, the first glyph of the input sequence is matched by a coverage table
+        // instead of being part of the rule.
+        let input_start = if rule.input_includes_first { i } else { i + 1 };
+        let mut end = input_start + rule.input.count() as usize;
+        let matches = match_sequence(bytes, rule.backtrack, glyphs[..i].iter().rev(), backtrack)?
+            && match_sequence(bytes, rule.input, glyphs[input_start..].iter(), input)?
+            && match_sequence(bytes, rule.lookahead, glyphs[end..].iter(), lookahead)?;
+        if !matches {
+            return Ok(None)
+        }
+        for record in rule.records {
+            let position = i + usize::from(record.sequence_index().read_from(bytes)?);
+            if position >= end {
+                continue
+            }
+            let length_before = glyphs.len();
+            self.apply_lookup_at(
+                bytes,
+                record.lookup_list_index().read_from(bytes)?,
+                glyphs,
+                clusters,
+                position,
+                nesting_level + 1,
+            )?;
+            // Ligatures make the input sequence shorter.
+            end = (end + glyphs.len()).saturating_sub(length_before)
+        }
+        Ok(Some(end.max(i + 1)))
+    }
+}
+
+fn single(
+    bytes: &[u8],
+    subtable: Position<u16>,
+    glyphs: &mut [GlyphId],
+    i: usize,
+) -> Result<Option<usize>, FontError> {
+    let glyph = &mut glyphs[i];
+    match subtable.read_from(bytes)? {
+        1 => {
+            let subtable = subtable.cast::<SingleSubstitutionFormat1>();
+            let coverage = subtable.offset_bytes(subtable.coverage_offset().read_from(bytes)?);
+            if coverage_index(bytes, coverage, *glyph)?.is_none() {
+                return Ok(None)
+            }
+            let delta = subtable.delta_glyph_id().read_from(bytes)?;
+            glyph.0 = glyph.0.wrapping_add(delta)
+        }
+        2 => {
+            let subtable = subtable.cast::<SingleSubstitutionFormat2>();
+            let coverage = subtable.offset_bytes(subtable.coverage_offset().read_from(bytes)?);
+            let substitutes = Slice::new(
+                subtable.followed_by::<u16>(),
+                subtable.glyph_count().read_from(bytes)?,
+            );
+            match coverage_index(bytes, coverage, *glyph)? {
+                Some(index) if index < substitutes.count() => {
+                    glyph.0 = substitutes.get_unchecked(index).read_from(bytes)?
+                }
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    }
+    Ok(Some(i + 1))
+}
+
+fn ligature(
+    bytes: &[u8],
+    subtable: Position<LigatureSubstitution>,
+    glyphs: &mut Vec<GlyphId>,
+    clusters: &mut Vec<usize>,
+    i: usize,
+) -> Result<Option<usize>, FontError> {
+    let coverage = subtable.offset_bytes(subtable.coverage_offset().read_from(bytes)?);
+    let ligature_set_index = match coverage_index(bytes, coverage, glyphs[i])? {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let ligature_set_offsets = Slice::new(
+        subtable.followed_by::<u16>(),
+        subtable.ligature_set_count().read_from(bytes)?,
+    );
+    if ligature_set_index >= ligature_set_offsets.count() {
+        return Ok(None)
+    }
+    let ligature_set: Position<u16> = subtable.offset_bytes(
+        ligature_set_offsets
+            .get_unchecked(ligature_set_index)
+            .read_from(bytes)?,
+    );
+    let ligature_offsets = Slice::new(
+        ligature_set.followed_by::<u16>(),
+        ligature_set.read_from(bytes)?,
+    );
+    // Ligatures are in order of preference, typically longest first.
+    for offset in ligature_offsets {
+        let ligature: Position<Ligature> = ligature_set.offset_bytes(offset.read_from(bytes)?);
+        // The count includes the first component, which is matched by the coverage table.
+        let component_count = ligature.component_count().read_from(bytes)?;
+        let components = Slice::new(
+            ligature.followed_by::<u16>(),
+            component_count.saturating_sub(1),
+        );
+        if !match_sequence(bytes, components, glyphs[i + 1..].iter(), |id, glyph| {
+            Ok(id == glyph.0)
+        })? {
+            continue
+        }
+        let end = i + 1 + components.count() as usize;
+        glyphs[i] = GlyphId(ligature.ligature_glyph().read_from(bytes)?);
+        glyphs.drain(i + 1..end);
+        clusters.drain(i + 1..end);
+        return Ok(Some(i + 1))
+    }
+    Ok(None)
+}
+
+/// Sequences of items to match before, at, and after the current glyph in a chained context
+/// subtable, and the lookups to apply if they all match.
+///
+/// Items are glyph IDs in format 1, classes in format 2, or offsets to coverage tables in format 3.
+struct ChainRule {
+    backtrack: Slice<u16>,
+    input: Slice<u16>,
+    input_includes_first: bool,
+    lookahead: Slice<u16>,
+    records: Slice<SequenceLookupRecord>,
+}
+
+/// The rules of formats 1 and 2
+fn rule_set(bytes: &[u8], rule_set: Position<u16>) -> Result<Vec<ChainRule>, FontError> {
+    let offsets = Slice::new(rule_set.followed_by::<u16>(), rule_set.read_from(bytes)?);
+    let mut rules = Vec::with_capacity(offsets.count() as usize);
+    for offset in offsets {
+        rules.push(chain_rule(
+            bytes,
+            rule_set.offset_bytes(offset.read_from(bytes)?),
+            false,
+        )?)
+    }
+    Ok(rules)
+}
+
+/// Each sequence is a 16-bit count followed by that many items.
+fn chain_rule(
+    bytes: &[u8],
+    rule: Position<u16>,
+    input_includes_first: bool,
+) -> Result<ChainRule, FontError> {
+    let sequence = |count: Position<u16>| -> Result<Slice<u16>, FontError> {
+        Ok(Slice::new(count.followed_by(), count.read_from(bytes)?))
+    };
+    let end = |sequence: Slice<u16>| sequence.get_unchecked(sequence.count());
+    let backtrack = sequence(rule)?;
+    let input_count = end(backtrack);
+    let mut input_length = input_count.read_from(bytes)?;
+    if !input_includes_first {
+        input_length = input_length.saturating_sub(1)
+    }
+    let input = Slice::new(input_count.followed_by(), input_length);
+    let lookahead = sequence(end(input))?;
+    let records_count = end(lookahead);
+    Ok(ChainRule {
+        backtrack,
+        input,
+        input_includes_first,
+        lookahead,
+        records: Slice::new(records_count.followed_by(), records_count.read_from(bytes)?),
+    })
+}
+
+/// Whether each item of `items` matches the corresponding glyph
+fn match_sequence<'a, G, F>(
+    bytes: &[u8],
+    items: Slice<u16>,
+    mut glyphs: G,
+    matches: F,
+) -> Result<bool, FontError>
+where
+    G: Iterator<Item = &'a GlyphId>,
+    F: Fn(u16, GlyphId) -> Result<bool, FontError>,
+{
+    for item in items {
+        let glyph = match glyphs.next() {
+            Some(&glyph) => glyph,
+            None => return Ok(false),
+        };
+        if !matches(item.read_from(bytes)?, glyph)? {
+            return Ok(false)
+        }
+    }
+    Ok(true)
+}
@@ -12,12 +12,67 @@ pub(in crate::fonts) struct OffsetSubtable {
     _range_shift: u16,
 }
 
+/// The header of a collection file, followed by the offset of each face’s `OffsetSubtable`
+#[derive(SfntTable)]
+pub(in crate::fonts) struct CollectionHeader {
+    signature: u32,
+    _major_version: u16,
+    _minor_version: u16,
+    font_count: u32,
+}
+
+/// The header of a WOFF file, followed by its table directory
+#[derive(SfntTable)]
+pub(in crate::fonts) struct WoffHeader {
+    _signature: u32,
+    flavor: u32,
+    _length: u32,
+    table_count: u16,
+    _reserved: u16,
+    _total_sfnt_size: u32,
+    _major_version: u16,
+    _minor_version: u16,
+    _metadata_offset: u32,
+    _metadata_length: u32,
+    _metadata_original_length: u32,
+    _private_data_offset: u32,
+    _private_data_length: u32,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct WoffTableDirectoryEntry {
+    tag: Tag,
+    table_offset: u32,
+    compressed_length: u32,
+    original_length: u32,
+    _original_checksum: u32,
+}
+
+/// The header of a WOFF2 file, followed by its variable-length table directory
+#[derive(SfntTable)]
+pub(in crate::fonts) struct Woff2Header {
+    _signature: u32,
+    flavor: u32,
+    _length: u32,
+    table_count: u16,
+    _reserved: u16,
+    _total_sfnt_size: u32,
+    total_compressed_size: u32,
+    _major_version: u16,
+    _minor_version: u16,
+    _metadata_offset: u32,
+    _metadata_length: u32,
+    _metadata_original_length: u32,
+    _private_data_offset: u32,
+    _private_data_length: u32,
+}
+
 #[derive(SfntTable)]
 pub(in crate::fonts) struct TableDirectoryEntry {
     tag: Tag,
     _checksum: u32,
     table_offset: u32,
-    _length: u32,
+    length: u32,
 }
 
 #[derive(SfntTable)]
@@ -41,7 +96,7 @@ pub(in crate::fonts) struct NamingTableHeader {
 pub(in crate::fonts) struct NameRecord {
     platform_id: u16,
     encoding_id: u16,
-    _language_id: u16,
+    language_id: u16,
     name_id: u16,
     length: u16,
     string_offset: u16,
@@ -103,10 +158,10 @@ pub(in crate::fonts) struct FontHeader {
     min_y: FWord,
     max_x: FWord,
     max_y: FWord,
-    _mac_style: u16,
+    mac_style: u16,
     _smallest_readable_size_in_px_per_em: u16,
     _font_direction_hint: i16,
-    _index_to_loc_format: i16,
+    index_to_loc_format: i16,
     _glyph_data_format: i16,
     __padding: u16,
 }
@@ -133,9 +188,414 @@ pub(in crate::fonts) struct HorizontalHeader {
     number_of_long_horizontal_metrics: u16,
 }
 
+#[derive(SfntTable)]
+#[tag = "vhea"]
+pub(in crate::fonts) struct VerticalHeader {
+    _version: FixedPoint,
+    _vert_typo_ascender: FWord,
+    _vert_typo_descender: FWord,
+    _vert_typo_line_gap: FWord,
+    _max_advance_height: UFWord,
+    _min_top_side_bearing: FWord,
+    _min_bottom_side_bearing: FWord,
+    _y_max_extent: FWord,
+    _caret_slope_rise: i16,
+    _caret_slope_run: i16,
+    _caret_offset: FWord,
+    __reserved_1: i16,
+    __reserved_2: i16,
+    __reserved_3: i16,
+    __reserved_4: i16,
+    _metric_data_format: i16,
+    number_of_long_vertical_metrics: u16,
+}
+
+#[derive(SfntTable)]
+#[tag = "OS/2"]
+pub(in crate::fonts) struct Os2 {
+    _version: u16,
+    _x_avg_char_width: i16,
+    weight_class: u16,
+    width_class: u16,
+    _type: u16,
+    _subscript_x_size: i16,
+    _subscript_y_size: i16,
+    _subscript_x_offset: i16,
+    _subscript_y_offset: i16,
+    _superscript_x_size: i16,
+    _superscript_y_size: i16,
+    _superscript_x_offset: i16,
+    _superscript_y_offset: i16,
+    _strikeout_size: i16,
+    _strikeout_position: i16,
+    _family_class: i16,
+    // 10 bytes of PANOSE classification
+    _panose_1: u16,
+    _panose_2: u16,
+    _panose_3: u16,
+    _panose_4: u16,
+    _panose_5: u16,
+    // `u32` fields that are not aligned to 4 bytes in this table
+    _unicode_range_1_high: u16,
+    _unicode_range_1_low: u16,
+    _unicode_range_2_high: u16,
+    _unicode_range_2_low: u16,
+    _unicode_range_3_high: u16,
+    _unicode_range_3_low: u16,
+    _unicode_range_4_high: u16,
+    _unicode_range_4_low: u16,
+    _vendor_id_high: u16,
+    _vendor_id_low: u16,
+    selection: u16,
+    // Depending of `version`, this table may have more fields that we don’t use.
+}
+
 #[derive(SfntTable)]
 #[tag = "hmtx"]
 pub(in crate::fonts) struct LongHorizontalMetricsRecord {
     advance_width: UFWord,
-    _left_side_bearing: i16,
+    left_side_bearing: i16,
+}
+
+/// Followed by the top side bearings of glyphs after the last long record,
+/// which have the same advance height as that record
+#[derive(SfntTable)]
+#[tag = "vmtx"]
+pub(in crate::fonts) struct LongVerticalMetricsRecord {
+    advance_height: UFWord,
+    top_side_bearing: FWord,
+}
+
+/// Followed by one `VerticalOriginRecord` per glyph that doesn’t use the default,
+/// sorted by glyph ID
+#[derive(SfntTable)]
+#[tag = "VORG"]
+pub(in crate::fonts) struct VerticalOriginHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    default_vertical_origin_y: FWord,
+    vertical_origin_y_metrics_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct VerticalOriginRecord {
+    glyph_index: u16,
+    vertical_origin_y: FWord,
+}
+
+/// With `index_to_loc_format` 0 in `head`, offsets in the `loca` table are divided by 2.
+#[derive(SfntTable)]
+#[tag = "loca"]
+pub(in crate::fonts) struct ShortGlyphOffset {
+    half_offset: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct LongGlyphOffset {
+    byte_offset: u32,
+}
+
+#[derive(SfntTable)]
+#[tag = "glyf"]
+pub(in crate::fonts) struct GlyphHeader {
+    /// Negative for composite glyphs
+    number_of_contours: i16,
+    min_x: FWord,
+    min_y: FWord,
+    max_x: FWord,
+    max_y: FWord,
+}
+
+/// Followed by arguments and a transform whose sizes depend on `flags`
+#[derive(SfntTable)]
+pub(in crate::fonts) struct CompositeGlyphComponent {
+    flags: u16,
+    glyph_index: u16,
+}
+
+#[derive(SfntTable)]
+#[tag = "kern"]
+pub(in crate::fonts) struct KerningHeader {
+    version: u16,
+    table_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct KerningSubtableHeader {
+    _version: u16,
+    length: u16,
+    coverage: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct KerningFormat0Header {
+    pair_count: u16,
+    _search_range: u16,
+    _entry_selector: u16,
+    _range_shift: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct KerningPair {
+    left: u16,
+    right: u16,
+    value: FWord,
+}
+
+#[derive(SfntTable)]
+#[tag = "GPOS"]
+pub(in crate::fonts) struct GlyphPositioningHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    _script_list_offset: u16,
+    feature_list_offset: u16,
+    lookup_list_offset: u16,
+}
+
+#[derive(SfntTable)]
+#[tag = "GSUB"]
+pub(in crate::fonts) struct GlyphSubstitutionHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    _script_list_offset: u16,
+    feature_list_offset: u16,
+    lookup_list_offset: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct FeatureRecord {
+    tag: Tag,
+    feature_offset: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct FeatureHeader {
+    _feature_params_offset: u16,
+    lookup_index_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct LookupHeader {
+    lookup_type: u16,
+    _lookup_flag: u16,
+    subtable_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ExtensionSubtable {
+    _format: u16,
+    extension_lookup_type: u16,
+    extension_offset: u32,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct PairPositioningFormat1 {
+    _format: u16,
+    coverage_offset: u16,
+    value_format_1: u16,
+    value_format_2: u16,
+    pair_set_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct PairPositioningFormat2 {
+    _format: u16,
+    coverage_offset: u16,
+    value_format_1: u16,
+    value_format_2: u16,
+    class_def_1_offset: u16,
+    class_def_2_offset: u16,
+    class_1_count: u16,
+    class_2_count: u16,
+}
+
+/// Coverage format 1 is followed by an array of glyph IDs, format 2 by an array of ranges.
+#[derive(SfntTable)]
+pub(in crate::fonts) struct CoverageHeader {
+    format: u16,
+    count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ClassDefinitionFormat1Header {
+    _format: u16,
+    start_glyph_id: u16,
+    glyph_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ClassDefinitionFormat2Header {
+    _format: u16,
+    range_count: u16,
+}
+
+/// In a coverage table `value` is the coverage index of `start_glyph_id`,
+/// in a class definition table it is the class of every glyph in the range.
+#[derive(SfntTable)]
+pub(in crate::fonts) struct RangeRecord {
+    start_glyph_id: u16,
+    end_glyph_id: u16,
+    value: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct SingleSubstitutionFormat1 {
+    _format: u16,
+    coverage_offset: u16,
+    // Really i16, but only used modulo 2^16 with u16::wrapping_add
+    delta_glyph_id: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct SingleSubstitutionFormat2 {
+    _format: u16,
+    coverage_offset: u16,
+    glyph_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct LigatureSubstitution {
+    _format: u16,
+    coverage_offset: u16,
+    ligature_set_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct Ligature {
+    ligature_glyph: u16,
+    component_count: u16,
+}
+
+/// Format 1 is followed by offsets to rule sets, format 2 by offsets to class sets.
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ChainedContextFormat1 {
+    _format: u16,
+    coverage_offset: u16,
+    rule_set_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ChainedContextFormat2 {
+    _format: u16,
+    coverage_offset: u16,
+    backtrack_class_def_offset: u16,
+    input_class_def_offset: u16,
+    lookahead_class_def_offset: u16,
+    class_set_count: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct SequenceLookupRecord {
+    sequence_index: u16,
+    lookup_list_index: u16,
+}
+
+#[derive(SfntTable)]
+#[tag = "fvar"]
+pub(in crate::fonts) struct FontVariationsHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    axes_array_offset: u16,
+    _reserved: u16,
+    axis_count: u16,
+    axis_size: u16,
+    _instance_count: u16,
+    _instance_size: u16,
+}
+
+#[derive(SfntTable)]
+pub(in crate::fonts) struct VariationAxisRecord {
+    tag: Tag,
+    min_value: FixedPoint,
+    default_value: FixedPoint,
+    max_value: FixedPoint,
+    _flags: u16,
+    _axis_name_id: u16,
+}
+
+/// Followed by a segment map for each axis:
+/// a `u16` count followed by that many `AxisValueMap` records
+#[derive(SfntTable)]
+#[tag = "avar"]
+pub(in crate::fonts) struct AxisVariationsHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    _reserved: u16,
+    axis_count: u16,
+}
+
+/// Normalized coordinates, as 2.14 fixed-point numbers
+#[derive(SfntTable)]
+pub(in crate::fonts) struct AxisValueMap {
+    from_coordinate: i16,
+    to_coordinate: i16,
+}
+
+#[derive(SfntTable)]
+#[tag = "HVAR"]
+pub(in crate::fonts) struct HorizontalMetricsVariationsHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    item_variation_store_offset: u32,
+    advance_width_mapping_offset: u32,
+    _left_side_bearing_mapping_offset: u32,
+    _right_side_bearing_mapping_offset: u32,
+}
+
+/// Followed by the `u32` offset of each item variation data subtable
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ItemVariationStoreHeader {
+    _format: u16,
+    // A `u32` field that is not aligned to 4 bytes in this table
+    region_list_offset_high: u16,
+    region_list_offset_low: u16,
+    item_variation_data_count: u16,
+}
+
+/// Followed by `region_count` regions of `axis_count` records each
+#[derive(SfntTable)]
+pub(in crate::fonts) struct VariationRegionListHeader {
+    axis_count: u16,
+    region_count: u16,
+}
+
+/// Normalized coordinates, as 2.14 fixed-point numbers
+#[derive(SfntTable)]
+pub(in crate::fonts) struct RegionAxisCoordinates {
+    start_coordinate: i16,
+    peak_coordinate: i16,
+    end_coordinate: i16,
+}
+
+/// Followed by `region_index_count` region indices, then `item_count` rows of deltas
+#[derive(SfntTable)]
+pub(in crate::fonts) struct ItemVariationDataHeader {
+    item_count: u16,
+    word_delta_count: u16,
+    region_index_count: u16,
+}
+
+/// Followed by `glyph_count + 1` offsets to glyph variation data,
+/// each `u16` divided by 2 or `u32` depending on `flags`
+#[derive(SfntTable)]
+#[tag = "gvar"]
+pub(in crate::fonts) struct GlyphVariationsHeader {
+    _major_version: u16,
+    _minor_version: u16,
+    axis_count: u16,
+    _shared_tuple_count: u16,
+    shared_tuples_offset: u32,
+    glyph_count: u16,
+    flags: u16,
+    glyph_variation_data_array_offset: u32,
+}
+
+/// The header of the `CFF ` table, followed by its Name INDEX
+#[derive(SfntTable)]
+#[tag = "CFF "]
+pub(in crate::fonts) struct CffHeader {
+    _major_version: u8,
+    _minor_version: u8,
+    header_size: u8,
+    _offset_size: u8,
 }
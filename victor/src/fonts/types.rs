@@ -8,7 +8,7 @@ pub(in crate::fonts) type FWord = euclid::Length<i16, FontDesignUnit>;
 pub(in crate::fonts) type UFWord = euclid::Length<u16, FontDesignUnit>;
 
 /// 32-bit signed fixed-point number (16.16)
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, ReadFromBytes)]
 pub(in crate::fonts) struct FixedPoint(pub u32);
 
 /// Instant in time as seconds since 1904-01-01 midnight UTC
@@ -18,6 +18,22 @@ pub(in crate::fonts) struct LongDateTime(pub i64);
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ReadFromBytes)]
 pub(in crate::fonts) struct Tag(pub [u8; 4]);
 
+impl FixedPoint {
+    pub(in crate::fonts) fn to_f32(self) -> f32 {
+        self.0 as i32 as f32 / 65536.
+    }
+}
+
+/// Convert a 16-bit signed fixed-point number with 14 fractional bits (2.14),
+/// such as normalized axis coordinates in variable fonts or scales of composite glyphs.
+pub(in crate::fonts) fn f2dot14_to_f32(value: i16) -> f32 {
+    f32::from(value) / 16384.
+}
+
+pub(in crate::fonts) fn f32_to_f2dot14(value: f32) -> i16 {
+    (value * 16384.).round().max(-32768.).min(32767.) as i16
+}
+
 // ~~~~ Trait impls ~~~~
 
 impl fmt::Debug for Tag {
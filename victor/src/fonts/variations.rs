@@ -0,0 +1,738 @@
+//! Variable fonts: design axes from the `fvar` table, and instances at given axis values.
+//!
+//! Axis values are normalized and mapped by `avar`,
+//! then select deltas for advance widths in `HVAR` and for glyph outlines in `gvar`.
+//!
+//! <https://docs.microsoft.com/en-us/typography/opentype/spec/otvaroverview>
+
+use crate::fonts::glyf::{Glyph, GlyphPoint, ARGS_ARE_XY_VALUES};
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::types::f2dot14_to_f32;
+use crate::fonts::{Font, FontError, GlyphId, OutlineFormat};
+use std::sync::Arc;
+
+/// A design axis of a variable font, such as weight or width
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VariationAxis {
+    /// Such as `*b"wght"` or `*b"wdth"`
+    pub tag: [u8; 4],
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+#[derive(Default)]
+pub(in crate::fonts) struct Variations {
+    axes: Vec<VariationAxis>,
+
+    /// The value of each axis in this instance, in user space
+    coordinates: Vec<f32>,
+
+    /// Each coordinate normalized to the range from -1 to 1 where 0 is the default,
+    /// then mapped by `avar`. Empty for the default instance.
+    normalized: Vec<f32>,
+
+    horizontal_metrics: Option<Position<HorizontalMetricsVariationsHeader>>,
+    glyphs: Option<Position<GlyphVariationsHeader>>,
+}
+
+/// After the points of a glyph outline, the variations of its metrics
+/// are expressed as those of four “phantom” points:
+/// the origin, the advance width, and two for vertical metrics.
+pub(in crate::fonts) const PHANTOM_POINT_COUNT: usize = 4;
+
+// Flags in `gvar`
+const LONG_OFFSETS: u16 = 0x0001;
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const TUPLE_COUNT_MASK: u16 = 0x0FFF;
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+const TUPLE_INDEX_MASK: u16 = 0x0FFF;
+const POINTS_ARE_WORDS: u8 = 0x80;
+const POINT_RUN_COUNT_MASK: u8 = 0x7F;
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+const DELTA_RUN_COUNT_MASK: u8 = 0x3F;
+
+// Flags in item variation data and delta-set index maps
+const LONG_WORDS: u16 = 0x8000;
+const WORD_DELTA_COUNT_MASK: u16 = 0x7FFF;
+const INNER_INDEX_BIT_COUNT_MASK: u8 = 0x0F;
+const MAP_ENTRY_SIZE_MASK: u8 = 0x30;
+
+impl Variations {
+    pub(in crate::fonts) fn parse(
+        bytes: &[u8],
+        table_directory: Slice<TableDirectoryEntry>,
+    ) -> Result<Self, FontError> {
+        let mut axes = Vec::new();
+        if let Some(fvar) = table_directory.optional_table::<FontVariationsHeader>(bytes)? {
+            let first_axis = fvar
+                .offset_bytes::<VariationAxisRecord, _>(fvar.axes_array_offset().read_from(bytes)?);
+            let axis_size = fvar.axis_size().read_from(bytes)?;
+            for index in 0..fvar.axis_count().read_from(bytes)? {
+                let axis = first_axis.offset_bytes::<VariationAxisRecord, _>(
+                    u32::from(index) * u32::from(axis_size),
+                );
+                axes.push(VariationAxis {
+                    tag: axis.tag().read_from(bytes)?.0,
+                    min: axis.min_value().read_from(bytes)?.to_f32(),
+                    default: axis.default_value().read_from(bytes)?.to_f32(),
+                    max: axis.max_value().read_from(bytes)?.to_f32(),
+                })
+            }
+        }
+        Ok(Variations {
+            coordinates: axes.iter().map(|axis| axis.default).collect(),
+            axes,
+            normalized: Vec::new(),
+            horizontal_metrics: table_directory.optional_table(bytes)?,
+            glyphs: table_directory.optional_table(bytes)?,
+        })
+    }
+
+    /// Set the value of each axis in user space, which must be within the axis’s range.
+    fn set_coordinates(
+        &mut self,
+        bytes: &[u8],
+        table_directory: Slice<TableDirectoryEntry>,
+        coordinates: Vec<f32>,
+    ) -> Result<(), FontError> {
+        let mut normalized: Vec<f32> = self
+            .axes
+            .iter()
+            .zip(&coordinates)
+            .map(|(axis, &value)| axis.normalize(value))
+            .collect();
+        if let Some(avar) = table_directory.optional_table::<AxisVariationsHeader>(bytes)? {
+            // A segment map for each axis, of variable size
+            let mut segment_map = avar.followed_by::<u16>();
+            let axis_count = avar.axis_count().read_from(bytes)?;
+            for coordinate in normalized.iter_mut().take(usize::from(axis_count)) {
+                let map_count = segment_map.read_from(bytes)?;
+                let maps = Slice::new(segment_map.followed_by::<AxisValueMap>(), map_count);
+                *coordinate = map_segments(bytes, maps, *coordinate)?;
+                segment_map = maps.get_unchecked(u32::from(map_count)).cast();
+            }
+        }
+        // Deltas are selected by coordinates with the precision of 2.14 fixed-point numbers.
+        for coordinate in &mut normalized {
+            *coordinate = (*coordinate * 16384.).round() / 16384.
+        }
+        self.normalized = if normalized.iter().all(|&coordinate| coordinate == 0.) {
+            Vec::new()
+        } else {
+            normalized
+        };
+        self.coordinates = coordinates;
+        Ok(())
+    }
+}
+
+impl VariationAxis {
+    /// Map a value in user space to -1 for `min`, 0 for `default`, and 1 for `max`.
+    fn normalize(&self, value: f32) -> f32 {
+        let value = value.max(self.min).min(self.max);
+        if value < self.default {
+            (value - self.default) / (self.default - self.min)
+        } else if value > self.default {
+            (value - self.default) / (self.max - self.default)
+        } else {
+            0.
+        }
+    }
+}
+
+impl Font {
+    /// The design axes of this variable font, empty for other fonts
+    pub fn variation_axes(&self) -> &[VariationAxis] {
+        &self.variations.axes
+    }
+
+    /// The value of each of `variation_axes` in this instance
+    pub fn variation_coordinates(&self) -> &[f32] {
+        &self.variations.coordinates
+    }
+
+    /// Return the instance of this variable font with the given axis values in user space,
+    /// such as `(*b"wght", 700.)`.
+    ///
+    /// Values are clamped to the range of their axis,
+    /// axes that are not in `settings` have their default value,
+    /// and tags that are not axes of this font are ignored.
+    /// When a tag is repeated, the last value is used.
+    ///
+    /// Instances are cached, so that the same values return the same `Arc`.
+    /// For fonts without variation axes, this returns `self`.
+    /// Fonts with CFF outlines return `FontError::UnsupportedCffVariations`
+    /// for values other than the defaults.
+    pub fn instance(self: &Arc<Self>, settings: &[([u8; 4], f32)]) -> Result<Arc<Self>, FontError> {
+        let axes = &self.variations.axes;
+        let mut coordinates: Vec<f32> = axes.iter().map(|axis| axis.default).collect();
+        for &(tag, value) in settings {
+            for (axis, coordinate) in axes.iter().zip(&mut coordinates) {
+                if axis.tag == tag && !value.is_nan() {
+                    *coordinate = value.max(axis.min).min(axis.max)
+                }
+            }
+        }
+        if coordinates == self.variations.coordinates {
+            return Ok(self.clone())
+        }
+        // PDF embedding applies variations to `glyf` outlines when subsetting
+        if self.outline_format == OutlineFormat::Cff {
+            return Err(FontError::UnsupportedCffVariations)
+        }
+        let mut instances = self.instances.lock();
+        if let Some(instance) = instances
+            .iter()
+            .find(|instance| instance.variations.coordinates == coordinates)
+        {
+            return Ok(instance.clone())
+        }
+        let mut instance = Self::parse_without_cow_bytes_field(self.bytes(), self.face_index)?;
+        instance
+            .variations
+            .set_coordinates(self.bytes(), self.table_directory, coordinates)?;
+        instance.bytes = self.bytes.clone();
+        let instance = Arc::new(instance);
+        instances.push(instance.clone());
+        Ok(instance)
+    }
+
+    /// Whether this is an instance of a variable font other than the default
+    pub(crate) fn is_variation_instance(&self) -> bool {
+        !self.variations.normalized.is_empty()
+    }
+
+    /// The difference between the advance width of a glyph in this instance
+    /// and in the default instance, in font design units
+    pub(in crate::fonts) fn advance_width_delta(&self, glyph: GlyphId) -> Result<f32, FontError> {
+        let normalized = &self.variations.normalized;
+        if normalized.is_empty() {
+            return Ok(0.)
+        }
+        let bytes = self.bytes();
+        if let Some(hvar) = self.variations.horizontal_metrics {
+            let mapping_offset = hvar.advance_width_mapping_offset().read_from(bytes)?;
+            let index = if mapping_offset == 0 {
+                Some((0, u32::from(glyph.0)))
+            } else {
+                delta_set_index(bytes, hvar.offset_bytes(mapping_offset), glyph)?
+            };
+            let store = hvar.offset_bytes(hvar.item_variation_store_offset().read_from(bytes)?);
+            return match index {
+                Some((outer, inner)) => item_delta(bytes, store, outer, inner, normalized),
+                None => Ok(0.),
+            }
+        }
+        if self.variations.glyphs.is_some() {
+            // Without `HVAR`, advance widths vary with the phantom points of glyph outlines.
+            let point_count = match self.glyph(glyph)? {
+                Glyph::Empty => 0,
+                Glyph::Simple(simple) => simple.points.len(),
+                Glyph::Composite(composite) => composite.components.len(),
+            };
+            let deltas = self.glyph_deltas(glyph, &[], &[], point_count + PHANTOM_POINT_COUNT)?;
+            return Ok(deltas[point_count + 1].0 - deltas[point_count].0)
+        }
+        Ok(0.)
+    }
+
+    /// Append the data of a glyph to a `glyf` table, with the deltas of this instance
+    /// applied to its points, and return the `min_x` of its bounding box.
+    /// Return `None` for glyphs without an outline.
+    pub(in crate::fonts) fn write_glyph_instance(
+        &self,
+        glyph: GlyphId,
+        glyf: &mut Vec<u8>,
+    ) -> Result<Option<i16>, FontError> {
+        let bytes = self.bytes();
+        match self.glyph(glyph)? {
+            Glyph::Empty => Ok(None),
+            Glyph::Simple(mut simple) => {
+                let deltas = self.glyph_deltas(
+                    glyph,
+                    &simple.points,
+                    &simple.end_points,
+                    simple.points.len() + PHANTOM_POINT_COUNT,
+                )?;
+                for (point, &(dx, dy)) in simple.points.iter_mut().zip(&deltas) {
+                    point.x += dx.round() as i32;
+                    point.y += dy.round() as i32;
+                }
+                let [min_x, _, _, _] = simple.write(bytes, glyf)?;
+                Ok(Some(min_x))
+            }
+            Glyph::Composite(mut composite) => {
+                // The points of a composite glyph are the offsets of its components.
+                let deltas = self.glyph_deltas(
+                    glyph,
+                    &[],
+                    &[],
+                    composite.components.len() + PHANTOM_POINT_COUNT,
+                )?;
+                for (component, &(dx, dy)) in composite.components.iter_mut().zip(&deltas) {
+                    if component.flags & ARGS_ARE_XY_VALUES != 0 {
+                        component.arguments.0 += dx.round() as i32;
+                        component.arguments.1 += dy.round() as i32;
+                    }
+                }
+                // FIXME: compute the bounding box of the varied components
+                composite.write(bytes, glyf)?;
+                Ok(Some(composite.bounding_box[0]))
+            }
+        }
+    }
+
+    /// The offset from `gvar` of each point of a glyph in this instance.
+    ///
+    /// `point_count` includes the phantom points after those of the outline.
+    /// `points` and `end_points` are the outline’s points and the ends of its contours,
+    /// to infer deltas for points that a variation does not list.
+    /// They are empty for composite glyphs, whose unlisted points do not move.
+    pub(in crate::fonts) fn glyph_deltas(
+        &self,
+        GlyphId(id): GlyphId,
+        points: &[GlyphPoint],
+        end_points: &[u16],
+        point_count: usize,
+    ) -> Result<Vec<(f32, f32)>, FontError> {
+        let mut deltas = vec![(0., 0.); point_count];
+        let normalized = &self.variations.normalized;
+        let gvar = match self.variations.glyphs {
+            Some(gvar) if !normalized.is_empty() => gvar,
+            _ => return Ok(deltas),
+        };
+        let bytes = self.bytes();
+        let axis_count = gvar.axis_count().read_from(bytes)?;
+        if id >= gvar.glyph_count().read_from(bytes)? || usize::from(axis_count) != normalized.len()
+        {
+            return Ok(deltas)
+        }
+        let offsets = gvar.followed_by::<u16>();
+        let (start, end) = if gvar.flags().read_from(bytes)? & LONG_OFFSETS != 0 {
+            let offsets = offsets.cast::<u32>();
+            (
+                offsets.offset(id).read_from(bytes)?,
+                offsets.offset(id + 1).read_from(bytes)?,
+            )
+        } else {
+            (
+                u32::from(offsets.offset(id).read_from(bytes)?) * 2,
+                u32::from(offsets.offset(id + 1).read_from(bytes)?) * 2,
+            )
+        };
+        if end <= start {
+            // This glyph does not vary
+            return Ok(deltas)
+        }
+        let data = gvar.offset_bytes::<u16, _>(
+            gvar.glyph_variation_data_array_offset().read_from(bytes)? + start,
+        );
+        let tuple_count = data.read_from(bytes)?;
+        let serialized_offset = u32::from(data.offset(1_u32).read_from(bytes)?);
+        let serialized_length = (end - start)
+            .checked_sub(serialized_offset)
+            .ok_or(FontError::OffsetPlusLengthBeyondEof)?;
+        let mut serialized = Reader(
+            Slice::<u8>::new(data.offset_bytes(serialized_offset), serialized_length)
+                .read_from(bytes)?,
+        );
+        let shared_point_numbers = if tuple_count & SHARED_POINT_NUMBERS != 0 {
+            serialized.point_numbers()?
+        } else {
+            None
+        };
+        let shared_tuples =
+            gvar.offset_bytes::<i16, _>(gvar.shared_tuples_offset().read_from(bytes)?);
+
+        // Each tuple variation header is followed by the next, with a variable size
+        let mut header = data.offset(2_u32);
+        for _ in 0..tuple_count & TUPLE_COUNT_MASK {
+            let data_size = header.read_from(bytes)?;
+            let tuple_index = header.offset(1_u32).read_from(bytes)?;
+            header = header.offset(2_u32);
+            let peak = if tuple_index & EMBEDDED_PEAK_TUPLE != 0 {
+                let peak = header.cast::<i16>();
+                header = header.offset(axis_count);
+                peak
+            } else {
+                shared_tuples
+                    .offset(u32::from(tuple_index & TUPLE_INDEX_MASK) * u32::from(axis_count))
+            };
+            let intermediate = if tuple_index & INTERMEDIATE_REGION != 0 {
+                let start = header.cast::<i16>();
+                header = header.offset(u32::from(axis_count) * 2);
+                Some((start, start.offset(axis_count)))
+            } else {
+                None
+            };
+            let mut tuple_data = Reader(serialized.take(usize::from(data_size))?);
+
+            let mut scalar = 1.;
+            for (index, &coordinate) in normalized.iter().enumerate() {
+                let index = index as u32;
+                let peak = f2dot14_to_f32(peak.offset(index).read_from(bytes)?);
+                let (start, end) = match intermediate {
+                    Some((start, end)) => (
+                        f2dot14_to_f32(start.offset(index).read_from(bytes)?),
+                        f2dot14_to_f32(end.offset(index).read_from(bytes)?),
+                    ),
+                    None => (peak.min(0.), peak.max(0.)),
+                };
+                scalar *= axis_scalar(coordinate, start, peak, end)
+            }
+            if scalar == 0. {
+                continue
+            }
+
+            let private_point_numbers;
+            let point_numbers = if tuple_index & PRIVATE_POINT_NUMBERS != 0 {
+                private_point_numbers = tuple_data.point_numbers()?;
+                &private_point_numbers
+            } else {
+                &shared_point_numbers
+            };
+            let count = point_numbers
+                .as_ref()
+                .map_or(point_count, |numbers| numbers.len());
+            let x_deltas = tuple_data.deltas(count)?;
+            let y_deltas = tuple_data.deltas(count)?;
+            let mut tuple_deltas = vec![None; point_count];
+            match point_numbers {
+                Some(numbers) => {
+                    for (index, &number) in numbers.iter().enumerate() {
+                        if let Some(delta) = tuple_deltas.get_mut(usize::from(number)) {
+                            *delta = Some((f32::from(x_deltas[index]), f32::from(y_deltas[index])))
+                        }
+                    }
+                    interpolate_untouched(points, end_points, &mut tuple_deltas)
+                }
+                None => {
+                    for (delta, (&x, &y)) in
+                        tuple_deltas.iter_mut().zip(x_deltas.iter().zip(&y_deltas))
+                    {
+                        *delta = Some((f32::from(x), f32::from(y)))
+                    }
+                }
+            }
+            for (delta, tuple_delta) in deltas.iter_mut().zip(tuple_deltas) {
+                if let Some((x, y)) = tuple_delta {
+                    delta.0 += x * scalar;
+                    delta.1 += y * scalar;
+                }
+            }
+        }
+        Ok(deltas)
+    }
+}
+
+/// Map a normalized coordinate with the piecewise linear function of an `avar` segment map
+fn map_segments(
+    bytes: &[u8],
+    maps: Slice<AxisValueMap>,
+    coordinate: f32,
+) -> Result<f32, FontError> {
+    let mut previous: Option<(f32, f32)> = None;
+    for map in maps {
+        let from = f2dot14_to_f32(map.from_coordinate().read_from(bytes)?);
+        let to = f2dot14_to_f32(map.to_coordinate().read_from(bytes)?);
+        if coordinate <= from {
+            return Ok(match previous {
+                Some((previous_from, previous_to)) if from != previous_from => {
+                    previous_to
+                        + (coordinate - previous_from) * (to - previous_to) / (from - previous_from)
+                }
+                _ => coordinate - from + to,
+            })
+        }
+        previous = Some((from, to))
+    }
+    Ok(match previous {
+        Some((from, to)) => coordinate - from + to,
+        None => coordinate,
+    })
+}
+
+/// How much a region of the design space that peaks at `peak` applies at `coordinate`,
+/// on one axis. Regions that are not valid apply fully.
+fn axis_scalar(coordinate: f32, start: f32, peak: f32, end: f32) -> f32 {
+    if peak == 0. || coordinate == peak || start > peak || peak > end || (start < 0. && end > 0.) {
+        1.
+    } else if coordinate <= start || coordinate >= end {
+        0.
+    } else if coordinate < peak {
+        (coordinate - start) / (peak - start)
+    } else {
+        (end - coordinate) / (end - peak)
+    }
+}
+
+/// Infer deltas for the points of each contour that a variation does not list,
+/// by interpolating between the nearest listed points before and after them on the contour.
+fn interpolate_untouched(
+    points: &[GlyphPoint],
+    end_points: &[u16],
+    deltas: &mut [Option<(f32, f32)>],
+) {
+    fn interpolate(
+        coordinate: i32,
+        coordinate_1: i32,
+        coordinate_2: i32,
+        delta_1: f32,
+        delta_2: f32,
+    ) -> f32 {
+        let (coordinate, coordinate_1, coordinate_2) =
+            (coordinate as f32, coordinate_1 as f32, coordinate_2 as f32);
+        if coordinate_1 == coordinate_2 {
+            return if delta_1 == delta_2 { delta_1 } else { 0. }
+        }
+        let ((c1, d1), (c2, d2)) = if coordinate_1 < coordinate_2 {
+            ((coordinate_1, delta_1), (coordinate_2, delta_2))
+        } else {
+            ((coordinate_2, delta_2), (coordinate_1, delta_1))
+        };
+        if coordinate <= c1 {
+            d1
+        } else if coordinate >= c2 {
+            d2
+        } else {
+            d1 + (coordinate - c1) * (d2 - d1) / (c2 - c1)
+        }
+    }
+
+    let mut start = 0;
+    for &end in end_points {
+        let end = usize::from(end);
+        if end < start || end >= points.len() || end >= deltas.len() {
+            break
+        }
+        let touched: Vec<usize> = (start..=end).filter(|&i| deltas[i].is_some()).collect();
+        for (index, &first) in touched.iter().enumerate() {
+            let next = touched[(index + 1) % touched.len()];
+            let (first_delta, next_delta) = match (deltas[first], deltas[next]) {
+                (Some(first_delta), Some(next_delta)) => (first_delta, next_delta),
+                _ => continue,
+            };
+            // The untouched points from `first` to `next`, wrapping around the contour
+            let mut i = first;
+            loop {
+                i = if i == end { start } else { i + 1 };
+                if i == next {
+                    break
+                }
+                deltas[i] = Some((
+                    interpolate(
+                        points[i].x,
+                        points[first].x,
+                        points[next].x,
+                        first_delta.0,
+                        next_delta.0,
+                    ),
+                    interpolate(
+                        points[i].y,
+                        points[first].y,
+                        points[next].y,
+                        first_delta.1,
+                        next_delta.1,
+                    ),
+                ))
+            }
+        }
+        start = end + 1
+    }
+}
+
+/// The `(outer, inner)` index of the deltas for a glyph in a delta-set index map,
+/// or `None` if the map is empty
+fn delta_set_index(
+    bytes: &[u8],
+    map: Position<u8>,
+    GlyphId(id): GlyphId,
+) -> Result<Option<(u32, u32)>, FontError> {
+    let format = map.read_from(bytes)?;
+    let entry_format = map.offset(1_u32).read_from(bytes)?;
+    let (map_count, entries) = if format == 0 {
+        let count = map.offset(2_u32).cast::<u16>();
+        (
+            u32::from(count.read_from(bytes)?),
+            count.followed_by::<u8>(),
+        )
+    } else {
+        let count = map.offset(2_u32).cast::<u32>();
+        (count.read_from(bytes)?, count.followed_by::<u8>())
+    };
+    if map_count == 0 {
+        return Ok(None)
+    }
+    // Glyphs after the last entry use it.
+    let index = u32::from(id).min(map_count - 1);
+    let entry_size = u32::from((entry_format & MAP_ENTRY_SIZE_MASK) >> 4) + 1;
+    let inner_bit_count = u32::from(entry_format & INNER_INDEX_BIT_COUNT_MASK) + 1;
+    let mut entry = 0_u32;
+    for byte in Slice::new(entries.offset(index * entry_size), entry_size) {
+        entry = (entry << 8) | u32::from(byte.read_from(bytes)?)
+    }
+    Ok(Some((
+        entry >> inner_bit_count,
+        entry & ((1 << inner_bit_count) - 1),
+    )))
+}
+
+/// The delta of an item in an item variation store, in this instance
+fn item_delta(
+    bytes: &[u8],
+    store: Position<ItemVariationStoreHeader>,
+    outer: u32,
+    inner: u32,
+    normalized: &[f32],
+) -> Result<f32, FontError> {
+    if outer >= u32::from(store.item_variation_data_count().read_from(bytes)?) {
+        return Ok(0.)
+    }
+    let region_list_offset = u32::from(store.region_list_offset_high().read_from(bytes)?) << 16
+        | u32::from(store.region_list_offset_low().read_from(bytes)?);
+    let region_list = store.offset_bytes::<VariationRegionListHeader, _>(region_list_offset);
+    let region_axis_count = u32::from(region_list.axis_count().read_from(bytes)?);
+    let region_count = region_list.region_count().read_from(bytes)?;
+    let regions = region_list.followed_by::<RegionAxisCoordinates>();
+
+    let data_offset = store.followed_by::<u32>().offset(outer).read_from(bytes)?;
+    let data = store.offset_bytes::<ItemVariationDataHeader, _>(data_offset);
+    if inner >= u32::from(data.item_count().read_from(bytes)?) {
+        return Ok(0.)
+    }
+    let word_delta_count = data.word_delta_count().read_from(bytes)?;
+    let (word_size, short_size) = if word_delta_count & LONG_WORDS != 0 {
+        (4, 2)
+    } else {
+        (2, 1)
+    };
+    let word_count = u32::from(word_delta_count & WORD_DELTA_COUNT_MASK);
+    let region_indices = Slice::new(
+        data.followed_by::<u16>(),
+        data.region_index_count().read_from(bytes)?,
+    );
+    let row_length =
+        word_count * word_size + (region_indices.count().saturating_sub(word_count)) * short_size;
+    let row = region_indices
+        .get_unchecked(region_indices.count())
+        .cast::<u8>()
+        .offset(inner * row_length);
+
+    let mut sum = 0.;
+    for (index, region_index) in region_indices.into_iter().enumerate() {
+        let index = index as u32;
+        let delta = if index < word_count {
+            let delta = row.offset(index * word_size);
+            if word_size == 4 {
+                delta.cast::<i32>().read_from(bytes)? as f32
+            } else {
+                f32::from(delta.cast::<i16>().read_from(bytes)?)
+            }
+        } else {
+            let delta = row.offset(word_count * word_size + (index - word_count) * short_size);
+            if short_size == 2 {
+                f32::from(delta.cast::<i16>().read_from(bytes)?)
+            } else {
+                f32::from(delta.cast::<i8>().read_from(bytes)?)
+            }
+        };
+        let region_index = region_index.read_from(bytes)?;
+        if delta == 0. || region_index >= region_count {
+            continue
+        }
+        let region = regions.offset(u32::from(region_index) * region_axis_count);
+        let mut scalar = 1.;
+        for (axis, &coordinate) in normalized
+            .iter()
+            .enumerate()
+            .take(region_axis_count as usize)
+        {
+            let coordinates = region.offset(axis as u32);
+            scalar *= axis_scalar(
+                coordinate,
+                f2dot14_to_f32(coordinates.start_coordinate().read_from(bytes)?),
+                f2dot14_to_f32(coordinates.peak_coordinate().read_from(bytes)?),
+                f2dot14_to_f32(coordinates.end_coordinate().read_from(bytes)?),
+            )
+        }
+        sum += delta * scalar
+    }
+    Ok(sum)
+}
+
+/// Sequential reading of the packed point numbers and deltas in `gvar`
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], FontError> {
+        if length > self.0.len() {
+            return Err(FontError::OffsetPlusLengthBeyondEof)
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, FontError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, FontError> {
+        let bytes = self.take(2)?;
+        Ok(i16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Packed point numbers, or `None` for all points of the glyph
+    fn point_numbers(&mut self) -> Result<Option<Vec<u16>>, FontError> {
+        let first = self.u8()?;
+        if first == 0 {
+            return Ok(None)
+        }
+        let count = if first & POINTS_ARE_WORDS != 0 {
+            usize::from(first & POINT_RUN_COUNT_MASK) << 8 | usize::from(self.u8()?)
+        } else {
+            usize::from(first)
+        };
+        // Each number is the difference from the previous one.
+        let mut numbers = Vec::with_capacity(count);
+        let mut number = 0_u16;
+        while numbers.len() < count {
+            let control = self.u8()?;
+            for _ in 0..=(control & POINT_RUN_COUNT_MASK) {
+                let difference = if control & POINTS_ARE_WORDS != 0 {
+                    self.i16()? as u16
+                } else {
+                    u16::from(self.u8()?)
+                };
+                number = number.wrapping_add(difference);
+                numbers.push(number)
+            }
+        }
+        numbers.truncate(count);
+        Ok(Some(numbers))
+    }
+
+    /// `count` packed deltas
+    fn deltas(&mut self, count: usize) -> Result<Vec<i16>, FontError> {
+        let mut deltas = Vec::with_capacity(count);
+        while deltas.len() < count {
+            let control = self.u8()?;
+            for _ in 0..=(control & DELTA_RUN_COUNT_MASK) {
+                deltas.push(if control & DELTAS_ARE_ZERO != 0 {
+                    0
+                } else if control & DELTAS_ARE_WORDS != 0 {
+                    self.i16()?
+                } else {
+                    i16::from(self.u8()? as i8)
+                })
+            }
+        }
+        deltas.truncate(count);
+        Ok(deltas)
+    }
+}
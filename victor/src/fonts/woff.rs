@@ -0,0 +1,43 @@
+//! [WOFF](https://www.w3.org/TR/WOFF/) decoding: an sfnt font file with tables compressed by zlib
+
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::writing::write_font_file;
+use crate::fonts::FontError;
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+/// Decode a WOFF file into the TrueType or OpenType font file that it contains.
+pub fn decode_woff(bytes: &[u8]) -> Result<Vec<u8>, FontError> {
+    let file_start = Position::<OffsetSubtable>::initial();
+    let header = file_start.cast::<WoffHeader>();
+    let table_directory = Slice::new(
+        header.followed_by::<WoffTableDirectoryEntry>(),
+        header.table_count().read_from(bytes)?,
+    );
+    let mut tables = Vec::with_capacity(table_directory.count() as usize);
+    for entry in table_directory {
+        let data = Slice::<u8>::new(
+            file_start.offset_bytes(entry.table_offset().read_from(bytes)?),
+            entry.compressed_length().read_from(bytes)?,
+        )
+        .read_from(bytes)?;
+        let original_length = entry.original_length().read_from(bytes)?;
+        // Tables that compression would not make smaller are stored uncompressed.
+        let table = if data.len() < original_length as usize {
+            let mut table = Vec::new();
+            ZlibDecoder::new(data)
+                .take(u64::from(original_length))
+                .read_to_end(&mut table)
+                .map_err(|_| FontError::InvalidWoff)?;
+            table
+        } else {
+            data.to_vec()
+        };
+        if table.len() != original_length as usize {
+            Err(FontError::InvalidWoff)?
+        }
+        tables.push((entry.tag().read_from(bytes)?, table))
+    }
+    Ok(write_font_file(header.flavor().read_from(bytes)?, tables))
+}
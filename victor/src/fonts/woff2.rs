@@ -0,0 +1,594 @@
+//! [WOFF2](https://www.w3.org/TR/WOFF2/) decoding: an sfnt font file compressed with Brotli,
+//! where the `glyf` and `loca` tables (and optionally `hmtx`) are stored
+//! in a transformed format that compresses better.
+
+use crate::fonts::parsing::*;
+use crate::fonts::tables::*;
+use crate::fonts::types::Tag;
+use crate::fonts::writing::{set_u16, write_font_file};
+use crate::fonts::{FontError, COLLECTION_SIGNATURE};
+use std::io::Read;
+use std::mem;
+
+/// Tags of the table directory, by their index in the `flags` byte of an entry.
+/// Index 63 means that an arbitrary tag follows.
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+const ARBITRARY_TAG: u8 = 63;
+
+const GLYF: Tag = Tag(*b"glyf");
+const HEAD: Tag = Tag(*b"head");
+const HHEA: Tag = Tag(*b"hhea");
+const HMTX: Tag = Tag(*b"hmtx");
+const LOCA: Tag = Tag(*b"loca");
+
+// Byte offsets of fields that are read or modified in some tables
+const HEAD_INDEX_TO_LOC_FORMAT: usize = 50;
+const HHEA_NUMBER_OF_LONG_HORIZONTAL_METRICS: usize = 34;
+
+// Flags of simple glyph points
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// Flags of composite glyph components
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+// Flags of the transformed `glyf` and `hmtx` tables
+const HAS_OVERLAP_SIMPLE_BITMAP: u16 = 0x0001;
+const NO_PROPORTIONAL_LEFT_SIDE_BEARINGS: u8 = 0x01;
+const NO_MONOSPACED_LEFT_SIDE_BEARINGS: u8 = 0x02;
+
+struct DirectoryEntry {
+    tag: Tag,
+    original_length: u32,
+    transformed: bool,
+
+    /// The range of this table in the decompressed data
+    start: usize,
+    end: usize,
+}
+
+/// Decode a WOFF2 file into the TrueType or OpenType font file that it contains.
+pub fn decode_woff2(bytes: &[u8]) -> Result<Vec<u8>, FontError> {
+    let header = Position::<OffsetSubtable>::initial().cast::<Woff2Header>();
+    let flavor = header.flavor().read_from(bytes)?;
+    if flavor == COLLECTION_SIGNATURE {
+        // FIXME: support WOFF2 collections, whose faces can share tables
+        Err(FontError::UnsupportedFormat)?
+    }
+    let mut reader = Reader(
+        bytes
+            .get(mem::size_of::<Woff2Header>()..)
+            .ok_or(FontError::OffsetBeyondEof)?,
+    );
+    let table_count = header.table_count().read_from(bytes)?;
+    let mut directory = Vec::with_capacity(usize::from(table_count));
+    let mut decompressed_length = 0_usize;
+    for _ in 0..table_count {
+        let flags = reader.u8()?;
+        let tag = match flags & 0x3F {
+            ARBITRARY_TAG => {
+                let tag = reader.take(4)?;
+                Tag([tag[0], tag[1], tag[2], tag[3]])
+            }
+            index => Tag(*KNOWN_TAGS[usize::from(index)]),
+        };
+        let transform_version = flags >> 6;
+        let original_length = reader.uint_base128()?;
+        // Version 0 is the `glyf` and `loca` transform, but the null transform of other tables
+        let transformed = if tag == GLYF || tag == LOCA {
+            transform_version == 0
+        } else {
+            transform_version != 0
+        };
+        let length = if transformed {
+            reader.uint_base128()?
+        } else {
+            original_length
+        };
+        let start = decompressed_length;
+        decompressed_length = decompressed_length
+            .checked_add(length as usize)
+            .ok_or(FontError::InvalidWoff)?;
+        directory.push(DirectoryEntry {
+            tag,
+            original_length,
+            transformed,
+            start,
+            end: decompressed_length,
+        })
+    }
+
+    let compressed = reader.take(header.total_compressed_size().read_from(bytes)? as usize)?;
+    let mut data = Vec::new();
+    brotli_decompressor::Decompressor::new(compressed, 4096)
+        .take(decompressed_length as u64)
+        .read_to_end(&mut data)
+        .map_err(|_| FontError::InvalidWoff)?;
+    if data.len() != decompressed_length {
+        Err(FontError::InvalidWoff)?
+    }
+
+    let find = |tag: Tag| directory.iter().find(|entry| entry.tag == tag);
+    let table_data = |tag: Tag| find(tag).map(|entry| &data[entry.start..entry.end]);
+    let mut reconstructed_glyf = None;
+    if let Some(glyf) = find(GLYF) {
+        let loca = find(LOCA).ok_or(FontError::MissingTable)?;
+        if glyf.transformed != loca.transformed {
+            Err(FontError::InvalidWoff)?
+        }
+        if glyf.transformed {
+            let glyf = reconstruct_glyf(&data[glyf.start..glyf.end])?;
+            if glyf.loca.len() != loca.original_length as usize {
+                Err(FontError::InvalidWoff)?
+            }
+            reconstructed_glyf = Some(glyf)
+        }
+    }
+
+    let mut tables = Vec::with_capacity(directory.len());
+    for entry in &directory {
+        let table = match (entry.tag, &reconstructed_glyf) {
+            (GLYF, Some(glyf)) => glyf.glyf.clone(),
+            (LOCA, Some(glyf)) => glyf.loca.clone(),
+            (HEAD, Some(glyf)) => {
+                let mut head = data[entry.start..entry.end].to_vec();
+                if head.len() < HEAD_INDEX_TO_LOC_FORMAT + 2 {
+                    Err(FontError::InvalidWoff)?
+                }
+                set_u16(&mut head, HEAD_INDEX_TO_LOC_FORMAT, glyf.index_format);
+                head
+            }
+            (HMTX, _) if entry.transformed => {
+                // The left side bearings that are omitted are the `xMin` of each glyph.
+                let glyf = reconstructed_glyf.as_ref().ok_or(FontError::InvalidWoff)?;
+                let hhea = table_data(HHEA).ok_or(FontError::MissingTable)?;
+                let long_metrics_count = Reader(
+                    hhea.get(HHEA_NUMBER_OF_LONG_HORIZONTAL_METRICS..)
+                        .ok_or(FontError::InvalidWoff)?,
+                )
+                .u16()?;
+                reconstruct_hmtx(
+                    &data[entry.start..entry.end],
+                    long_metrics_count,
+                    &glyf.x_mins,
+                )?
+            }
+            _ if entry.transformed => Err(FontError::InvalidWoff)?,
+            _ => data[entry.start..entry.end].to_vec(),
+        };
+        // The padding of reconstructed glyphs can differ from that of the original `glyf`.
+        if entry.tag != GLYF && table.len() != entry.original_length as usize {
+            Err(FontError::InvalidWoff)?
+        }
+        tables.push((entry.tag, table))
+    }
+    Ok(write_font_file(flavor, tables))
+}
+
+struct ReconstructedGlyf {
+    glyf: Vec<u8>,
+    loca: Vec<u8>,
+
+    /// `indexToLocFormat` in the `head` table: 0 for short `loca` offsets, 1 for long
+    index_format: u16,
+
+    /// The `xMin` of each glyph’s bounding box, or zero for empty glyphs
+    x_mins: Vec<i16>,
+}
+
+/// Decode the transformed `glyf` table into `glyf` and `loca` tables.
+fn reconstruct_glyf(transformed: &[u8]) -> Result<ReconstructedGlyf, FontError> {
+    let mut header = Reader(transformed);
+    let _reserved = header.u16()?;
+    let option_flags = header.u16()?;
+    let glyph_count = header.u16()?;
+    let index_format = header.u16()?;
+    let mut stream_lengths = [0; 7];
+    for length in &mut stream_lengths {
+        *length = header.u32()? as usize
+    }
+    // The streams follow each other, after the header
+    let mut rest = header;
+    let mut contour_count_stream = Reader(rest.take(stream_lengths[0])?);
+    let mut point_count_stream = Reader(rest.take(stream_lengths[1])?);
+    let mut flag_stream = Reader(rest.take(stream_lengths[2])?);
+    let mut glyph_stream = Reader(rest.take(stream_lengths[3])?);
+    let mut composite_stream = Reader(rest.take(stream_lengths[4])?);
+    let mut bbox_stream = Reader(rest.take(stream_lengths[5])?);
+    let mut instruction_stream = Reader(rest.take(stream_lengths[6])?);
+
+    let bitmap_length = (usize::from(glyph_count) + 31) / 32 * 4;
+    let bbox_bitmap = bbox_stream.take(bitmap_length)?;
+    let overlap_bitmap = if option_flags & HAS_OVERLAP_SIMPLE_BITMAP != 0 {
+        Some(rest.take((usize::from(glyph_count) + 7) / 8)?)
+    } else {
+        None
+    };
+    let bit =
+        |bitmap: &[u8], index: u16| bitmap[usize::from(index >> 3)] & (0x80 >> (index & 7)) != 0;
+
+    // Glyph data is 2-byte aligned with short offsets in `loca`, and 4-byte aligned with long
+    let alignment = match index_format {
+        0 => 2,
+        1 => 4,
+        _ => Err(FontError::InvalidWoff)?,
+    };
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((usize::from(glyph_count) + 1) * alignment);
+    let mut x_mins = Vec::with_capacity(usize::from(glyph_count));
+    let push_offset = |loca: &mut Vec<u8>, offset: usize| -> Result<(), FontError> {
+        if index_format == 0 {
+            if offset / 2 > usize::from(u16::max_value()) {
+                Err(FontError::InvalidWoff)?
+            }
+            loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes())
+        } else {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes())
+        }
+        Ok(())
+    };
+    for id in 0..glyph_count {
+        push_offset(&mut loca, glyf.len())?;
+        let has_bbox = bit(bbox_bitmap, id);
+        let contour_count = contour_count_stream.i16()?;
+        let bbox = if has_bbox {
+            Some([
+                bbox_stream.i16()?,
+                bbox_stream.i16()?,
+                bbox_stream.i16()?,
+                bbox_stream.i16()?,
+            ])
+        } else {
+            None
+        };
+        match contour_count {
+            0 => {
+                // An empty glyph, for example for a space
+                if has_bbox {
+                    Err(FontError::InvalidWoff)?
+                }
+                x_mins.push(0);
+                continue
+            }
+            -1 => {
+                // A composite glyph, which must have an explicit bounding box
+                let bbox = bbox.ok_or(FontError::InvalidWoff)?;
+                let components_start = composite_stream.0;
+                let mut have_instructions = false;
+                loop {
+                    let flags = composite_stream.u16()?;
+                    let _glyph_index = composite_stream.u16()?;
+                    let arguments_size = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                        4
+                    } else {
+                        2
+                    };
+                    let transform_size = if flags & WE_HAVE_A_SCALE != 0 {
+                        2
+                    } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                        4
+                    } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                        8
+                    } else {
+                        0
+                    };
+                    composite_stream.take(arguments_size + transform_size)?;
+                    have_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+                    if flags & MORE_COMPONENTS == 0 {
+                        break
+                    }
+                }
+                let components_length = components_start.len() - composite_stream.0.len();
+                push_i16s(&mut glyf, &[-1, bbox[0], bbox[1], bbox[2], bbox[3]]);
+                glyf.extend_from_slice(&components_start[..components_length]);
+                if have_instructions {
+                    let instructions_length = glyph_stream.u255_16()?;
+                    glyf.extend_from_slice(&instructions_length.to_be_bytes());
+                    glyf.extend_from_slice(
+                        instruction_stream.take(usize::from(instructions_length))?,
+                    );
+                }
+                x_mins.push(bbox[0])
+            }
+            _ if contour_count > 0 => {
+                let mut end_points = Vec::with_capacity(contour_count as usize);
+                let mut point_count = 0_u16;
+                for _ in 0..contour_count {
+                    point_count = point_count
+                        .checked_add(point_count_stream.u255_16()?)
+                        .ok_or(FontError::InvalidWoff)?;
+                    end_points.push(point_count.wrapping_sub(1))
+                }
+                let mut flags = Vec::with_capacity(usize::from(point_count));
+                let mut x_coordinates = Vec::new();
+                let mut y_coordinates = Vec::new();
+                let (mut x, mut y) = (0_i32, 0_i32);
+                let (mut x_min, mut y_min, mut x_max, mut y_max) = (0, 0, 0, 0);
+                for index in 0..point_count {
+                    let (on_curve, dx, dy) = decode_triplet(&mut flag_stream, &mut glyph_stream)?;
+                    x = x.checked_add(dx).ok_or(FontError::InvalidWoff)?;
+                    y = y.checked_add(dy).ok_or(FontError::InvalidWoff)?;
+                    if index == 0 {
+                        x_min = x;
+                        x_max = x;
+                        y_min = y;
+                        y_max = y;
+                    } else {
+                        x_min = x_min.min(x);
+                        x_max = x_max.max(x);
+                        y_min = y_min.min(y);
+                        y_max = y_max.max(y);
+                    }
+                    let mut flag = if on_curve { ON_CURVE_POINT } else { 0 };
+                    if index == 0 && overlap_bitmap.map_or(false, |bitmap| bit(bitmap, id)) {
+                        flag |= OVERLAP_SIMPLE
+                    }
+                    flag |= encode_coordinate(
+                        dx,
+                        &mut x_coordinates,
+                        X_SHORT_VECTOR,
+                        X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+                    )?;
+                    flag |= encode_coordinate(
+                        dy,
+                        &mut y_coordinates,
+                        Y_SHORT_VECTOR,
+                        Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+                    )?;
+                    flags.push(flag)
+                }
+                let instructions_length = glyph_stream.u255_16()?;
+                let instructions = instruction_stream.take(usize::from(instructions_length))?;
+                let bbox = match bbox {
+                    Some(bbox) => bbox,
+                    None => {
+                        let fits = |value: i32| value as i16 as i32 == value;
+                        if !(fits(x_min) && fits(y_min) && fits(x_max) && fits(y_max)) {
+                            Err(FontError::InvalidWoff)?
+                        }
+                        [x_min as i16, y_min as i16, x_max as i16, y_max as i16]
+                    }
+                };
+
+                push_i16s(
+                    &mut glyf,
+                    &[contour_count, bbox[0], bbox[1], bbox[2], bbox[3]],
+                );
+                for end_point in end_points {
+                    glyf.extend_from_slice(&end_point.to_be_bytes())
+                }
+                glyf.extend_from_slice(&instructions_length.to_be_bytes());
+                glyf.extend_from_slice(instructions);
+                let mut flags = flags.iter().peekable();
+                while let Some(&flag) = flags.next() {
+                    let mut repeat = 0_u8;
+                    while repeat < u8::max_value() && flags.peek() == Some(&&flag) {
+                        flags.next();
+                        repeat += 1
+                    }
+                    if repeat > 0 {
+                        glyf.extend_from_slice(&[flag | REPEAT_FLAG, repeat])
+                    } else {
+                        glyf.push(flag)
+                    }
+                }
+                glyf.extend_from_slice(&x_coordinates);
+                glyf.extend_from_slice(&y_coordinates);
+                x_mins.push(bbox[0])
+            }
+            _ => Err(FontError::InvalidWoff)?,
+        }
+        while glyf.len() % alignment != 0 {
+            glyf.push(0)
+        }
+    }
+    push_offset(&mut loca, glyf.len())?;
+    Ok(ReconstructedGlyf {
+        glyf,
+        loca,
+        index_format,
+        x_mins,
+    })
+}
+
+/// Decode the transformed `hmtx` table, where some left side bearings may be omitted.
+fn reconstruct_hmtx(
+    transformed: &[u8],
+    long_metrics_count: u16,
+    x_mins: &[i16],
+) -> Result<Vec<u8>, FontError> {
+    let mut reader = Reader(transformed);
+    let flags = reader.u8()?;
+    let long_metrics_count = usize::from(long_metrics_count);
+    if long_metrics_count == 0 || long_metrics_count > x_mins.len() {
+        Err(FontError::InvalidWoff)?
+    }
+    let mut advance_widths = Vec::with_capacity(long_metrics_count);
+    for _ in 0..long_metrics_count {
+        advance_widths.push(reader.u16()?)
+    }
+    let mut left_side_bearing = |index: usize, omitted: bool| -> Result<i16, FontError> {
+        if omitted {
+            Ok(x_mins[index])
+        } else {
+            reader.i16()
+        }
+    };
+    let mut hmtx =
+        Vec::with_capacity(long_metrics_count * 4 + (x_mins.len() - long_metrics_count) * 2);
+    for (index, advance_width) in advance_widths.into_iter().enumerate() {
+        let omitted = flags & NO_PROPORTIONAL_LEFT_SIDE_BEARINGS != 0;
+        hmtx.extend_from_slice(&advance_width.to_be_bytes());
+        hmtx.extend_from_slice(&left_side_bearing(index, omitted)?.to_be_bytes())
+    }
+    for index in long_metrics_count..x_mins.len() {
+        let omitted = flags & NO_MONOSPACED_LEFT_SIDE_BEARINGS != 0;
+        hmtx.extend_from_slice(&left_side_bearing(index, omitted)?.to_be_bytes())
+    }
+    Ok(hmtx)
+}
+
+/// Read the flag and coordinates of a point of a simple glyph,
+/// and return whether it is on the curve and its offset from the previous point.
+fn decode_triplet(
+    flag_stream: &mut Reader,
+    glyph_stream: &mut Reader,
+) -> Result<(bool, i32, i32), FontError> {
+    fn with_sign(flag: u8, value: i32) -> i32 {
+        if flag & 1 != 0 {
+            value
+        } else {
+            -value
+        }
+    }
+    let flag = flag_stream.u8()?;
+    let on_curve = flag & 0x80 == 0;
+    let flag = flag & 0x7F;
+    let data_length = match flag {
+        0..=83 => 1,
+        84..=119 => 2,
+        120..=123 => 3,
+        _ => 4,
+    };
+    let bytes = glyph_stream.take(data_length)?;
+    let data = |index: usize| i32::from(bytes[index]);
+    let flag_value = i32::from(flag);
+    let (dx, dy) = match flag {
+        0..=9 => (0, with_sign(flag, ((flag_value & 14) << 7) + data(0))),
+        10..=19 => (
+            with_sign(flag, (((flag_value - 10) & 14) << 7) + data(0)),
+            0,
+        ),
+        20..=83 => {
+            let b0 = flag_value - 20;
+            (
+                with_sign(flag, 1 + (b0 & 0x30) + (data(0) >> 4)),
+                with_sign(flag >> 1, 1 + ((b0 & 0x0C) << 2) + (data(0) & 0x0F)),
+            )
+        }
+        84..=119 => {
+            let b0 = flag_value - 84;
+            (
+                with_sign(flag, 1 + ((b0 / 12) << 8) + data(0)),
+                with_sign(flag >> 1, 1 + (((b0 % 12) >> 2) << 8) + data(1)),
+            )
+        }
+        120..=123 => (
+            with_sign(flag, (data(0) << 4) + (data(1) >> 4)),
+            with_sign(flag >> 1, ((data(1) & 0x0F) << 8) + data(2)),
+        ),
+        _ => (
+            with_sign(flag, (data(0) << 8) + data(1)),
+            with_sign(flag >> 1, (data(2) << 8) + data(3)),
+        ),
+    };
+    Ok((on_curve, dx, dy))
+}
+
+/// Append a coordinate offset of a simple glyph point in the shortest encoding,
+/// and return the corresponding bits of the point’s flags.
+fn encode_coordinate(
+    delta: i32,
+    coordinates: &mut Vec<u8>,
+    short_vector: u8,
+    is_same_or_positive: u8,
+) -> Result<u8, FontError> {
+    Ok(if delta == 0 {
+        is_same_or_positive
+    } else if delta.abs() < 256 {
+        coordinates.push(delta.abs() as u8);
+        short_vector | if delta > 0 { is_same_or_positive } else { 0 }
+    } else if delta as i16 as i32 == delta {
+        coordinates.extend_from_slice(&(delta as i16).to_be_bytes());
+        0
+    } else {
+        Err(FontError::InvalidWoff)?
+    })
+}
+
+fn push_i16s(bytes: &mut Vec<u8>, values: &[i16]) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_be_bytes())
+    }
+}
+
+/// Sequential reading of the variable-length data in WOFF2 files
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], FontError> {
+        if length > self.0.len() {
+            Err(FontError::InvalidWoff)?
+        }
+        let (taken, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, FontError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FontError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, FontError> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, FontError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// A `UIntBase128`: up to five bytes of seven bits each, most significant first
+    fn uint_base128(&mut self) -> Result<u32, FontError> {
+        let mut value = 0_u32;
+        for index in 0..5 {
+            let byte = self.u8()?;
+            // No leading zeros, and no overflow
+            if (index == 0 && byte == 0x80) || value & 0xFE00_0000 != 0 {
+                Err(FontError::InvalidWoff)?
+            }
+            value = (value << 7) | u32::from(byte & 0x7F);
+            if byte & 0x80 == 0 {
+                return Ok(value)
+            }
+        }
+        Err(FontError::InvalidWoff)?
+    }
+
+    /// A `255UInt16`: one byte for small values, with codes for larger ones
+    fn u255_16(&mut self) -> Result<u16, FontError> {
+        const ONE_MORE_BYTE_CODE_2: u8 = 254;
+        const WORD_CODE: u8 = 253;
+        const ONE_MORE_BYTE_CODE_1: u8 = 255;
+        const LOWEST_U_CODE: u16 = 253;
+        Ok(match self.u8()? {
+            WORD_CODE => self.u16()?,
+            ONE_MORE_BYTE_CODE_1 => u16::from(self.u8()?) + LOWEST_U_CODE,
+            ONE_MORE_BYTE_CODE_2 => u16::from(self.u8()?) + LOWEST_U_CODE * 2,
+            code => u16::from(code),
+        })
+    }
+}
@@ -0,0 +1,73 @@
+//! Serializing font files, the reverse of `parsing`
+
+use crate::fonts::types::Tag;
+
+const HEAD_CHECKSUM_ADJUSTMENT: usize = 8;
+
+/// Serialize tables into a font file, with checksums.
+pub(in crate::fonts) fn write_font_file(
+    signature: u32,
+    mut tables: Vec<(Tag, Vec<u8>)>,
+) -> Vec<u8> {
+    // The table directory is sorted by tag, for binary search
+    tables.sort_by_key(|&(tag, _)| tag);
+    let table_count = tables.len() as u16;
+    let entry_selector = 15 - table_count.leading_zeros() as u16;
+    let search_range: u16 = (1 << entry_selector) * 16;
+    let mut file = Vec::new();
+    file.extend_from_slice(&signature.to_be_bytes());
+    file.extend_from_slice(&table_count.to_be_bytes());
+    file.extend_from_slice(&search_range.to_be_bytes());
+    file.extend_from_slice(&entry_selector.to_be_bytes());
+    file.extend_from_slice(&(table_count * 16 - search_range).to_be_bytes());
+
+    let mut table_offset = file.len() + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, table) in &mut tables {
+        if tag.0 == *b"head" && table.len() >= HEAD_CHECKSUM_ADJUSTMENT + 4 {
+            // Checksums are computed with a zero adjustment.
+            set_u32(table, HEAD_CHECKSUM_ADJUSTMENT, 0);
+            head_offset = Some(table_offset)
+        }
+        file.extend_from_slice(&tag.0);
+        file.extend_from_slice(&checksum(table).to_be_bytes());
+        file.extend_from_slice(&(table_offset as u32).to_be_bytes());
+        file.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        table_offset += padded_length(table)
+    }
+    for (_, table) in &tables {
+        file.extend_from_slice(table);
+        file.resize(file.len() + padded_length(table) - table.len(), 0)
+    }
+    if let Some(head_offset) = head_offset {
+        let adjustment = 0x_B1B0_AFBA_u32.wrapping_sub(checksum(&file));
+        set_u32(
+            &mut file,
+            head_offset + HEAD_CHECKSUM_ADJUSTMENT,
+            adjustment,
+        )
+    }
+    file
+}
+
+/// Tables start at 4-byte boundaries
+fn padded_length(table: &[u8]) -> usize {
+    (table.len() + 3) / 4 * 4
+}
+
+/// The sum of big-endian `u32` words, with zero padding at the end
+fn checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+pub(in crate::fonts) fn set_u16(table: &mut [u8], offset: usize, value: u16) {
+    table[offset..offset + 2].copy_from_slice(&value.to_be_bytes())
+}
+
+pub(in crate::fonts) fn set_u32(table: &mut [u8], offset: usize, value: u32) {
+    table[offset..offset + 4].copy_from_slice(&value.to_be_bytes())
+}
@@ -4,21 +4,21 @@ use crate::style::values::*;
 use crate::style::*;
 
 impl dom::Document {
-    pub fn render(&self) {
-        let _ = self.box_tree();
-    }
-
-    fn box_tree(&self) -> BoxTreeRoot {
-        let mut builder = StyleSetBuilder::new();
-        self.parse_stylesheets(&mut builder);
-        let author_styles = builder.finish();
+    pub(super) fn box_tree(
+        &self,
+        author_styles: &StyleSet,
+        viewport_size: Size<CssPx>,
+    ) -> BoxTreeRoot {
+        let root_element = self.root_element();
+        let mut device = Device::new(viewport_size);
+        let root_element_style = cascade(author_styles, self, root_element, None, &device);
+        device.root_font_size = root_element_style.font.font_size;
         let context = Context {
             document: self,
-            author_styles: &author_styles,
+            author_styles,
+            device: &device,
         };
 
-        let root_element = self.root_element();
-        let root_element_style = cascade(&author_styles, self, root_element, None);
         // If any, anonymous blocks wrapping inlines at the root level get initial styles,
         // they don’t have a parent element to inherit from.
         let initial_values = ComputedValues::initial();
@@ -32,6 +32,7 @@ impl dom::Document {
 struct Context<'a> {
     document: &'a dom::Document,
     author_styles: &'a StyleSet,
+    device: &'a Device,
 }
 
 struct Builder<Extra> {
@@ -64,6 +65,7 @@ impl<Extra: Default + PushBlock> Builder<Extra> {
                             context.document,
                             child,
                             Some(&self.style),
+                            context.device,
                         );
                         self.push_element(context, child, style)
                     }
@@ -0,0 +1,299 @@
+//! Block-level layout in normal flow
+//!
+//! <https://drafts.csswg.org/css2/visuren.html#normal-flow>
+
+use super::fragments::{BoxFragment, Fragment};
+use super::inline::layout_inline_formatting_context;
+use super::*;
+use crate::fonts::FontError;
+use crate::primitives::{CssPx, Length, Point, Rect, SideOffsets, Size};
+use crate::style::values::border::BorderStyle;
+use crate::style::values::length::LengthPercentageOrAuto;
+use crate::style::values::BoxSizing;
+
+pub(super) struct ContainingBlock {
+    pub(super) width: Length<CssPx>,
+
+    /// `None` if it depends on the contents, which makes percentage heights behave as `auto`
+    pub(super) height: Option<Length<CssPx>>,
+}
+
+impl BlockFormattingContext {
+    pub(super) fn layout(
+        &self,
+        context: &LayoutContext,
+        containing_block: &ContainingBlock,
+    ) -> Result<(Vec<Fragment>, Length<CssPx>), FontError> {
+        // Anonymous blocks and inline content at the root get initial styles,
+        // like in box generation.
+        let style = ComputedValues::initial();
+        self.0.layout(context, containing_block, &style)
+    }
+}
+
+impl BlockContainer {
+    /// Return the fragments of this container’s children,
+    /// and the height of its content box.
+    pub(super) fn layout(
+        &self,
+        context: &LayoutContext,
+        containing_block: &ContainingBlock,
+        style: &Rc<ComputedValues>,
+    ) -> Result<(Vec<Fragment>, Length<CssPx>), FontError> {
+        match self {
+            BlockContainer::BlockLevels(children) => {
+                layout_block_level_children(context, containing_block, children)
+            }
+            BlockContainer::InlineFormattingContext(children) => {
+                layout_inline_formatting_context(context, containing_block, style, children)
+            }
+        }
+    }
+}
+
+fn layout_block_level_children(
+    context: &LayoutContext,
+    containing_block: &ContainingBlock,
+    children: &[BlockLevel],
+) -> Result<(Vec<Fragment>, Length<CssPx>), FontError> {
+    let mut fragments = Vec::with_capacity(children.len());
+    let mut y = Length::new(0.);
+    let mut pending_margin = CollapsedMargin::zero();
+    for child in children {
+        match child {
+            BlockLevel::SameFormattingContextBlock { style, contents } => {
+                // Percentages in vertical margins and padding are also relative to the width.
+                let cb_width = containing_block.width;
+                let mut margin = style.margin(cb_width);
+                let padding = style.padding(cb_width);
+                let border = style.border_width();
+                let pb = padding + border;
+                let (width, margin_left, margin_right) =
+                    used_width_and_margins(style, containing_block, pb.horizontal_typed());
+                margin.left = margin_left.get();
+                margin.right = margin_right.get();
+                let sizes = VerticalSizes::new(style, containing_block, pb.vertical_typed());
+                let specified_height = sizes.specified();
+                let inner_containing_block = ContainingBlock {
+                    width,
+                    height: specified_height,
+                };
+                let (children, content_height) =
+                    contents.layout(context, &inner_containing_block, style)?;
+                let height = specified_height.unwrap_or_else(|| sizes.clamp(content_height));
+
+                // Vertical margins of adjacent siblings collapse.
+                // FIXME: collapse through empty boxes, and between parent and children.
+                pending_margin.adjoin(CollapsedMargin::new(margin.top_typed()));
+                y += pending_margin.solve();
+                pending_margin = CollapsedMargin::new(margin.bottom_typed());
+
+                fragments.push(Fragment::Box(BoxFragment {
+                    style: style.clone(),
+                    children,
+                    content_rect: Rect::new(
+                        Point::from_lengths(
+                            margin.left_typed() + pb.left_typed(),
+                            y + pb.top_typed(),
+                        ),
+                        Size::from_lengths(width, height),
+                    ),
+                    padding,
+                    border,
+                    margin,
+                }));
+                y += pb.top_typed() + height + pb.bottom_typed();
+            }
+        }
+    }
+    y += pending_margin.solve();
+    Ok((fragments, y))
+}
+
+/// Return the used `width`, `margin-left`, and `margin-right` of a block-level box in normal flow.
+///
+/// `pb` is the sum of horizontal padding and borders.
+///
+/// <https://drafts.csswg.org/css2/visudet.html#blockwidth>
+/// <https://drafts.csswg.org/css2/visudet.html#min-max-widths>
+fn used_width_and_margins(
+    style: &ComputedValues,
+    containing_block: &ContainingBlock,
+    pb: Length<CssPx>,
+) -> (Length<CssPx>, Length<CssPx>, Length<CssPx>) {
+    let cb_width = containing_block.width;
+    let basis = Some(cb_width);
+    let sizing = BoxSizingOffset::new(style, pb);
+    let zero = Length::new(0.);
+    let width = sizing.content_size(style.size.width.resolve(basis));
+    // For block-level boxes, `auto` is zero: the used width is never negative.
+    let min_width = sizing
+        .content_size(style.size.min_width.resolve(basis))
+        .unwrap_or(zero);
+    let max_width = sizing.content_size(style.size.max_width.resolve(basis));
+    // `None` for `auto`
+    let margin_left = style.margin.margin_left.resolve(basis);
+    let margin_right = style.margin.margin_right.resolve(basis);
+
+    let solve = |width: Option<Length<CssPx>>| match width {
+        None => {
+            let margin_left = margin_left.unwrap_or(zero);
+            let margin_right = margin_right.unwrap_or(zero);
+            let width = cb_width - pb - margin_left - margin_right;
+            (width, margin_left, margin_right)
+        }
+        Some(width) => {
+            let available = cb_width - pb - width;
+            // `auto` margins are zero if the box is wider than its containing block.
+            let fixed_margins = margin_left.unwrap_or(zero) + margin_right.unwrap_or(zero);
+            let (margin_left, margin_right) = if available < fixed_margins {
+                (margin_left.or(Some(zero)), margin_right.or(Some(zero)))
+            } else {
+                (margin_left, margin_right)
+            };
+            match (margin_left, margin_right) {
+                (None, None) => (width, available / 2., available / 2.),
+                (None, Some(margin_right)) => (width, available - margin_right, margin_right),
+                // If over-constrained, `margin-right` is ignored.
+                // FIXME: ignore `margin-left` instead in right-to-left containing blocks.
+                (Some(margin_left), _) => (width, margin_left, available - margin_left),
+            }
+        }
+    };
+    let mut used = solve(width);
+    if let Some(max_width) = max_width {
+        if used.0 > max_width {
+            used = solve(Some(max_width))
+        }
+    }
+    if used.0 < min_width {
+        used = solve(Some(min_width))
+    }
+    used
+}
+
+/// `height`, `min-height`, and `max-height` of a block-level box, for the content box
+///
+/// <https://drafts.csswg.org/css2/visudet.html#normal-block>
+/// <https://drafts.csswg.org/css2/visudet.html#min-max-heights>
+struct VerticalSizes {
+    height: Option<Length<CssPx>>,
+    min_height: Option<Length<CssPx>>,
+    max_height: Option<Length<CssPx>>,
+}
+
+impl VerticalSizes {
+    /// `pb` is the sum of vertical padding and borders.
+    fn new(style: &ComputedValues, containing_block: &ContainingBlock, pb: Length<CssPx>) -> Self {
+        let basis = containing_block.height;
+        let sizing = BoxSizingOffset::new(style, pb);
+        VerticalSizes {
+            height: sizing.content_size(style.size.height.resolve(basis)),
+            min_height: sizing.content_size(style.size.min_height.resolve(basis)),
+            max_height: sizing.content_size(style.size.max_height.resolve(basis)),
+        }
+    }
+
+    /// The used height if it doesn’t depend on the contents
+    fn specified(&self) -> Option<Length<CssPx>> {
+        self.height.map(|height| self.clamp(height))
+    }
+
+    fn clamp(&self, mut height: Length<CssPx>) -> Length<CssPx> {
+        if let Some(max_height) = self.max_height {
+            height = Length::new(height.get().min(max_height.get()))
+        }
+        if let Some(min_height) = self.min_height {
+            height = Length::new(height.get().max(min_height.get()))
+        }
+        height
+    }
+}
+
+/// How much to subtract from sizing properties to get the size of the content box
+struct BoxSizingOffset(Length<CssPx>);
+
+impl BoxSizingOffset {
+    fn new(style: &ComputedValues, pb: Length<CssPx>) -> Self {
+        BoxSizingOffset(match style.size.box_sizing {
+            BoxSizing::ContentBox => Length::new(0.),
+            BoxSizing::BorderBox => pb,
+        })
+    }
+
+    fn content_size(&self, size: Option<Length<CssPx>>) -> Option<Length<CssPx>> {
+        size.map(|size| Length::new((size - self.0).get().max(0.)))
+    }
+}
+
+/// <https://drafts.csswg.org/css2/box.html#collapsing-margins>
+struct CollapsedMargin {
+    max_positive: Length<CssPx>,
+    min_negative: Length<CssPx>,
+}
+
+impl CollapsedMargin {
+    fn zero() -> Self {
+        Self::new(Length::new(0.))
+    }
+
+    fn new(margin: Length<CssPx>) -> Self {
+        CollapsedMargin {
+            max_positive: Length::new(margin.0.max(0.)),
+            min_negative: Length::new(margin.0.min(0.)),
+        }
+    }
+
+    fn adjoin(&mut self, other: Self) {
+        self.max_positive = Length::new(self.max_positive.0.max(other.max_positive.0));
+        self.min_negative = Length::new(self.min_negative.0.min(other.min_negative.0));
+    }
+
+    fn solve(&self) -> Length<CssPx> {
+        self.max_positive + self.min_negative
+    }
+}
+
+impl ComputedValues {
+    /// `basis` is the width of the containing block, which percentages are relative to.
+    ///
+    /// `auto` is treated as zero.
+    pub(super) fn margin(&self, basis: Length<CssPx>) -> SideOffsets<CssPx> {
+        let resolve =
+            |value: &LengthPercentageOrAuto| value.resolve(Some(basis)).unwrap_or(Length::new(0.));
+        SideOffsets::from_lengths(
+            resolve(&self.margin.margin_top),
+            resolve(&self.margin.margin_right),
+            resolve(&self.margin.margin_bottom),
+            resolve(&self.margin.margin_left),
+        )
+    }
+
+    /// `basis` is the width of the containing block, which percentages are relative to.
+    pub(super) fn padding(&self, basis: Length<CssPx>) -> SideOffsets<CssPx> {
+        SideOffsets::from_lengths(
+            self.padding.padding_top.resolve(basis),
+            self.padding.padding_right.resolve(basis),
+            self.padding.padding_bottom.resolve(basis),
+            self.padding.padding_left.resolve(basis),
+        )
+    }
+
+    /// Zero on sides where `border-style` is `none` or `hidden`
+    pub(super) fn border_width(&self) -> SideOffsets<CssPx> {
+        let b = &self.border;
+        let width = |width: Length<CssPx>, style: BorderStyle| {
+            if style.is_none_or_hidden() {
+                Length::new(0.)
+            } else {
+                width
+            }
+        };
+        SideOffsets::from_lengths(
+            width(b.border_top_width, b.border_top_style),
+            width(b.border_right_width, b.border_right_style),
+            width(b.border_bottom_width, b.border_bottom_style),
+            width(b.border_left_width, b.border_left_style),
+        )
+    }
+}
@@ -0,0 +1,207 @@
+//! Selecting a font face for the computed `font-*` properties of an element,
+//! and the font features to shape its text with
+//!
+//! <https://drafts.csswg.org/css-fonts/#font-matching-algorithm>
+
+use super::LayoutContext;
+use crate::fonts::{Font, FontFace, FontFeatures};
+use crate::style::values::font::{FamilyName, FontStyle, GenericFamily};
+use crate::style::ComputedValues;
+use std::sync::Arc;
+
+/// The last family tried from `font-family`, if it is in the collection
+const DEFAULT_FAMILY: &str = "Bitstream Vera Sans";
+
+/// FIXME: map generic families to different fonts once more than one family is available.
+fn generic_family_name(generic: GenericFamily) -> &'static str {
+    match generic {
+        GenericFamily::Serif
+        | GenericFamily::SansSerif
+        | GenericFamily::Cursive
+        | GenericFamily::Fantasy
+        | GenericFamily::Monospace
+        | GenericFamily::SystemUi => DEFAULT_FAMILY,
+    }
+}
+
+impl LayoutContext<'_> {
+    /// Return the font face that best matches `font-family`, `font-stretch`,
+    /// `font-style`, and `font-weight`.
+    pub(super) fn font(&self, style: &ComputedValues) -> Arc<Font> {
+        self.fallback_fonts(style).swap_remove(0)
+    }
+
+    /// Return the best matching face of each family in `font-family` that is available,
+    /// then of the default family.
+    ///
+    /// If none of these are available, return a face of the first family in the collection.
+    /// The result is never empty.
+    pub(super) fn fallback_fonts(&self, style: &ComputedValues) -> Vec<Arc<Font>> {
+        let mut fonts = Vec::new();
+        let families = style.font.font_family.0.iter().map(|name| match name {
+            FamilyName::Named(name) => &**name,
+            FamilyName::Generic(generic) => generic_family_name(*generic),
+        });
+        for family in families.chain(Some(DEFAULT_FAMILY)) {
+            if let Some(font) = self.match_family(family, style) {
+                if !fonts.iter().any(|f| Arc::ptr_eq(f, &font)) {
+                    fonts.push(font)
+                }
+            }
+        }
+        if fonts.is_empty() {
+            let family = &self.fonts.faces()[0].family_name;
+            fonts.extend(self.match_family(family, style))
+        }
+        fonts
+    }
+
+    /// Return the best matching face of every family in the collection
+    /// that is not already in `fonts`, in the order families were added.
+    ///
+    /// This is the last resort of font fallback, for characters that no font in
+    /// `fallback_fonts` supports.
+    pub(super) fn system_fallback_fonts(
+        &self,
+        style: &ComputedValues,
+        fonts: &[Arc<Font>],
+    ) -> Vec<Arc<Font>> {
+        let mut families: Vec<&str> = Vec::new();
+        for face in self.fonts.faces() {
+            let family = &*face.family_name;
+            if !families.iter().any(|f| f.eq_ignore_ascii_case(family)) {
+                families.push(family)
+            }
+        }
+        families
+            .into_iter()
+            .filter_map(|family| self.match_family(family, style))
+            .filter(|font| !fonts.iter().any(|f| Arc::ptr_eq(f, font)))
+            .collect()
+    }
+
+    /// Narrow down faces of one family by `font-stretch`, then `font-style`, then `font-weight`.
+    ///
+    /// For a variable font, this is the instance for these properties
+    /// and `font-variation-settings`.
+    /// A face with a `wdth` or `wght` axis matches any width or weight in the axis’s range.
+    fn match_family(&self, family: &str, style: &ComputedValues) -> Option<Arc<Font>> {
+        let mut candidates: Vec<&FontFace> = self.fonts.family(family).iter().collect();
+        let stretch = (style.font.font_stretch.0).0;
+        narrow(&mut candidates, |face| {
+            // `wdth` axis values are percentages.
+            let available = match axis_range(&face.font, *b"wdth") {
+                Some((min, max)) => (stretch * 100.).max(min).min(max) / 100.,
+                None => face.stretch,
+            };
+            stretch_rank(stretch, available)
+        });
+        let style_rank = |face: &FontFace| match (style.font.font_style, face.is_italic) {
+            (FontStyle::Normal, false) | (FontStyle::Italic, true) | (FontStyle::Oblique, true) => {
+                0
+            }
+            _ => 1,
+        };
+        narrow(&mut candidates, style_rank);
+        let weight = style.font.font_weight;
+        narrow(&mut candidates, |face| {
+            let available = match axis_range(&face.font, *b"wght") {
+                Some((min, max)) => f32::from(weight).max(min).min(max).round() as u16,
+                None => face.weight,
+            };
+            weight_rank(weight, available)
+        });
+        let font = &candidates.first()?.font;
+        let mut settings = vec![(*b"wght", f32::from(weight)), (*b"wdth", stretch * 100.)];
+        settings.extend(style.font.font_variation_settings.0.iter().cloned());
+        Some(font.instance(&settings).unwrap_or_else(|_| font.clone()))
+    }
+}
+
+/// The features enabled by default, then `font-variant-ligatures`, then `font-feature-settings`
+///
+/// <https://drafts.csswg.org/css-fonts/#feature-precedence>
+pub(super) fn font_features(style: &ComputedValues) -> FontFeatures {
+    let mut features = FontFeatures::default();
+    let ligatures = style.font.font_variant_ligatures;
+    let kinds: [(Option<bool>, &[[u8; 4]]); 4] = [
+        (ligatures.common, &[*b"liga", *b"clig"]),
+        (ligatures.discretionary, &[*b"dlig"]),
+        (ligatures.historical, &[*b"hlig"]),
+        (ligatures.contextual, &[*b"calt"]),
+    ];
+    for &(enabled, tags) in &kinds {
+        if let Some(enabled) = enabled {
+            for &tag in tags {
+                features.set(tag, enabled as u32)
+            }
+        }
+    }
+    for &(tag, value) in style.font.font_feature_settings.0.iter() {
+        features.set(tag, value)
+    }
+    features
+}
+
+/// The minimum and maximum values of a variation axis of this font, if it has that axis
+fn axis_range(font: &Font, tag: [u8; 4]) -> Option<(f32, f32)> {
+    font.variation_axes()
+        .iter()
+        .find(|axis| axis.tag == tag)
+        .map(|axis| (axis.min, axis.max))
+}
+
+/// Keep only the candidates with the lowest rank.
+fn narrow<R: PartialOrd>(candidates: &mut Vec<&FontFace>, rank: impl Fn(&FontFace) -> R) {
+    let best = candidates
+        .iter()
+        .map(|face| rank(face))
+        .fold(None, |best: Option<R>, rank| match best {
+            Some(best) if best <= rank => Some(best),
+            _ => Some(rank),
+        });
+    if let Some(best) = best {
+        candidates.retain(|face| rank(face) == best)
+    }
+}
+
+/// Narrower widths are preferred for condensed values, and wider widths for expanded ones.
+fn stretch_rank(desired: f32, available: f32) -> (u8, f32) {
+    let is_preferred_side = if desired <= 1. {
+        available <= desired
+    } else {
+        available >= desired
+    };
+    let rank = if is_preferred_side { 0 } else { 1 };
+    (rank, (available - desired).abs())
+}
+
+fn weight_rank(desired: u16, available: u16) -> (u8, u16) {
+    let lighter = available < desired;
+    let distance = if lighter {
+        desired - available
+    } else {
+        available - desired
+    };
+    let rank = if (400..=500).contains(&desired) {
+        // Heavier weights up to 500, then lighter weights, then heavier than 500
+        if !lighter && available <= 500 {
+            0
+        } else if lighter {
+            1
+        } else {
+            2
+        }
+    } else if desired < 400 {
+        if lighter || distance == 0 {
+            0
+        } else {
+            1
+        }
+    } else if !lighter {
+        0
+    } else {
+        1
+    };
+    (rank, distance)
+}
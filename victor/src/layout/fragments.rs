@@ -0,0 +1,107 @@
+use crate::primitives::{CssPx, Length, Point, Rect, SideOffsets, Size, Vector};
+use crate::style::ComputedValues;
+use crate::text::ShapedSegment;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub(crate) enum Fragment {
+    Box(BoxFragment),
+    Anonymous(AnonymousFragment),
+    Text(TextFragment),
+}
+
+#[derive(Clone)]
+pub(crate) struct BoxFragment {
+    pub(crate) style: Rc<ComputedValues>,
+    pub(crate) children: Vec<Fragment>,
+
+    /// From the containing block’s content box origin
+    pub(crate) content_rect: Rect<CssPx>,
+    pub(crate) padding: SideOffsets<CssPx>,
+    pub(crate) border: SideOffsets<CssPx>,
+    pub(crate) margin: SideOffsets<CssPx>,
+}
+
+/// Can contain child fragments with relative coordinates, but does not contribute to painting itself.
+///
+/// Line boxes are anonymous fragments.
+#[derive(Clone)]
+pub(crate) struct AnonymousFragment {
+    pub(crate) rect: Rect<CssPx>,
+    pub(crate) children: Vec<Fragment>,
+}
+
+#[derive(Clone)]
+pub(crate) struct TextFragment {
+    pub(crate) parent_style: Rc<ComputedValues>,
+    pub(crate) content_rect: Rect<CssPx>,
+    pub(crate) text: ShapedSegment,
+}
+
+impl BoxFragment {
+    pub(crate) fn padding_rect(&self) -> Rect<CssPx> {
+        self.content_rect.outer_rect(self.padding)
+    }
+
+    pub(crate) fn border_rect(&self) -> Rect<CssPx> {
+        self.padding_rect().outer_rect(self.border)
+    }
+
+    pub(crate) fn margin_rect(&self) -> Rect<CssPx> {
+        self.border_rect().outer_rect(self.margin)
+    }
+}
+
+impl Fragment {
+    pub(crate) fn translate(&mut self, offset: Vector<CssPx>) {
+        let rect = match self {
+            Fragment::Box(fragment) => &mut fragment.content_rect,
+            Fragment::Anonymous(fragment) => &mut fragment.rect,
+            Fragment::Text(fragment) => &mut fragment.content_rect,
+        };
+        rect.origin += offset
+    }
+
+    /// Move a fragment laid out in logical coordinates, as if in horizontal writing mode,
+    /// to its physical position in `vertical-rl` writing mode:
+    /// the inline direction is down and the block direction is from right to left.
+    ///
+    /// `block_size` is the logical height of the containing block.
+    pub(crate) fn rotate_to_vertical_rl(&mut self, block_size: Length<CssPx>) {
+        match self {
+            Fragment::Box(fragment) => {
+                let inner_block_size = fragment.content_rect.size.height_typed();
+                for child in &mut fragment.children {
+                    child.rotate_to_vertical_rl(inner_block_size)
+                }
+                fragment.content_rect = rotate_rect(fragment.content_rect, block_size);
+                fragment.padding = rotate_sides(fragment.padding);
+                fragment.border = rotate_sides(fragment.border);
+                fragment.margin = rotate_sides(fragment.margin);
+            }
+            Fragment::Anonymous(fragment) => {
+                let inner_block_size = fragment.rect.size.height_typed();
+                for child in &mut fragment.children {
+                    child.rotate_to_vertical_rl(inner_block_size)
+                }
+                fragment.rect = rotate_rect(fragment.rect, block_size)
+            }
+            Fragment::Text(fragment) => {
+                fragment.content_rect = rotate_rect(fragment.content_rect, block_size)
+            }
+        }
+    }
+}
+
+fn rotate_rect(rect: Rect<CssPx>, block_size: Length<CssPx>) -> Rect<CssPx> {
+    Rect::new(
+        Point::from_lengths(block_size - rect.max_y_typed(), rect.min_x_typed()),
+        Size::from_lengths(rect.size.height_typed(), rect.size.width_typed()),
+    )
+}
+
+/// Logical sides are block-start, inline-end, block-end, and inline-start,
+/// which are right, bottom, left, and top in `vertical-rl`.
+fn rotate_sides(sides: SideOffsets<CssPx>) -> SideOffsets<CssPx> {
+    SideOffsets::new(sides.left, sides.top, sides.right, sides.bottom)
+}
@@ -0,0 +1,725 @@
+//! Inline formatting context layout: breaking inline-level content into line boxes
+//!
+//! <https://drafts.csswg.org/css2/visuren.html#inline-formatting>
+
+use super::flow::ContainingBlock;
+use super::font_matching::font_features;
+use super::fragments::{AnonymousFragment, BoxFragment, Fragment, TextFragment};
+use super::*;
+use crate::fonts::{Em, FontError};
+use crate::primitives::{CssPx, Length, Point, Rect, SideOffsets, Size, Vector};
+use crate::style::values::{self, UnicodeBidi, WritingMode};
+use crate::text::bidi::{level_runs, visual_order, BidiInfo, Level};
+use crate::text::{Direction, ShapedSegment};
+use std::ops::Range;
+use std::sync::Arc;
+use xi_unicode::LineBreakIterator;
+
+/// Return the line box fragments, and the total height of the lines.
+///
+/// In `vertical-rl` writing mode, lines are columns from right to left
+/// and the height is that of the longest one.
+pub(super) fn layout_inline_formatting_context(
+    context: &LayoutContext,
+    containing_block: &ContainingBlock,
+    style: &Rc<ComputedValues>,
+    children: &[InlineLevel],
+) -> Result<(Vec<Fragment>, Length<CssPx>), FontError> {
+    // Break opportunities can be between two inline boxes (`foo <b>bar</b>`)
+    // and not within a single text node (`foo<b>bar</b>`),
+    // so look for them in the text of the entire formatting context at once.
+    let mut text = String::new();
+    let mut items = Vec::new();
+    // On a block container, `bidi-override` applies to its entire inline content
+    // and other values of `unicode-bidi` have no effect, except `plaintext`:
+    // <https://drafts.csswg.org/css-writing-modes/#unicode-bidi>
+    let rtl = style.writing_modes.direction == values::Direction::Rtl;
+    if matches!(
+        style.text.unicode_bidi,
+        UnicodeBidi::BidiOverride | UnicodeBidi::IsolateOverride
+    ) {
+        text.push(if rtl { RLO } else { LRO })
+    }
+    flatten(children, style, &mut text, &mut items);
+    let mut break_opportunities = LineBreakIterator::new(&text)
+        .map(|(position, _is_hard_break)| position)
+        .peekable();
+
+    // The paragraph level is found from the text itself for `unicode-bidi: plaintext`
+    let paragraph_level = match style.text.unicode_bidi {
+        UnicodeBidi::Plaintext => None,
+        _ if rtl => Some(Level::rtl()),
+        _ => Some(Level::ltr()),
+    };
+    let bidi = BidiInfo::new(&text, paragraph_level);
+    let paragraph_level = bidi
+        .paragraphs
+        .first()
+        .map_or(Level::ltr(), |paragraph| paragraph.level);
+
+    // Vertical lines are laid out as if horizontal, then rotated.
+    // Without a definite height, the available space in the inline direction
+    // is the height of the initial containing block:
+    // <https://drafts.csswg.org/css-writing-modes/#orthogonal-auto>
+    let vertical = style.writing_modes.writing_mode == WritingMode::VerticalRl;
+    let logical_containing_block = ContainingBlock {
+        width: containing_block
+            .height
+            .unwrap_or(context.viewport.height_typed()),
+        height: Some(containing_block.width),
+    };
+    let mut lines = LinesState::new(
+        context,
+        if vertical {
+            &logical_containing_block
+        } else {
+            containing_block
+        },
+        style,
+        bidi.levels,
+        paragraph_level,
+    );
+    // Content between two consecutive break opportunities, which must not be split across lines.
+    let mut chunk = Vec::new();
+    for item in items {
+        match item {
+            Item::StartInline {
+                style,
+                first_fragment,
+                bidi_controls_start,
+            } => chunk.push(ChunkItem::StartInline {
+                style,
+                first_fragment,
+                level: bidi_controls_start.map(|position| lines.levels[position]),
+            }),
+            Item::EndInline {
+                style,
+                last_fragment,
+            } => chunk.push(ChunkItem::EndInline {
+                style,
+                last_fragment,
+            }),
+            Item::Text {
+                parent_style,
+                range,
+            } => {
+                let mut start = range.start;
+                while start < range.end {
+                    while break_opportunities.peek().map_or(false, |&b| b <= start) {
+                        break_opportunities.next();
+                    }
+                    let end = match break_opportunities.peek() {
+                        Some(&b) if b <= range.end => b,
+                        _ => range.end,
+                    };
+                    lines.shape(parent_style, &text, start..end, &mut chunk)?;
+                    if break_opportunities.peek() == Some(&end) {
+                        lines.place_chunk(&mut chunk);
+                    }
+                    start = end
+                }
+            }
+        }
+    }
+    lines.place_chunk(&mut chunk);
+    lines.finish_line();
+    if vertical {
+        // FIXME: columns that don’t fit in the width of the containing block
+        // overflow on the left instead of continuing below.
+        let longest_line = lines.longest_line;
+        let mut line_boxes = lines.line_boxes;
+        for line in &mut line_boxes {
+            if let Fragment::Anonymous(line) = line {
+                line.rect.size.width = longest_line.get()
+            }
+            line.rotate_to_vertical_rl(containing_block.width)
+        }
+        return Ok((line_boxes, longest_line))
+    }
+    Ok((lines.line_boxes, lines.next_line_y))
+}
+
+enum Item<'box_tree> {
+    StartInline {
+        style: &'box_tree Rc<ComputedValues>,
+        first_fragment: bool,
+        /// Where the bidi control characters inserted for this inline box, if any,
+        /// start in the text of the entire inline formatting context
+        bidi_controls_start: Option<usize>,
+    },
+    EndInline {
+        style: &'box_tree Rc<ComputedValues>,
+        last_fragment: bool,
+    },
+    Text {
+        parent_style: &'box_tree Rc<ComputedValues>,
+        /// In the text of the entire inline formatting context, after white space collapsing
+        range: Range<usize>,
+    },
+}
+
+fn flatten<'box_tree>(
+    children: &'box_tree [InlineLevel],
+    parent_style: &'box_tree Rc<ComputedValues>,
+    text: &mut String,
+    items: &mut Vec<Item<'box_tree>>,
+) {
+    for child in children {
+        match child {
+            InlineLevel::Text(contents) => {
+                let start = text.len();
+                collapse_white_space(contents, text);
+                if text.len() > start {
+                    items.push(Item::Text {
+                        parent_style,
+                        range: start..text.len(),
+                    })
+                }
+            }
+            InlineLevel::Inline {
+                style,
+                first_fragment,
+                last_fragment,
+                children,
+            } => {
+                let (open, close) = bidi_control_characters(style);
+                items.push(Item::StartInline {
+                    style,
+                    first_fragment: *first_fragment,
+                    bidi_controls_start: if open.is_empty() {
+                        None
+                    } else {
+                        Some(text.len())
+                    },
+                });
+                text.extend(open);
+                flatten(children, style, text, items);
+                text.extend(close);
+                items.push(Item::EndInline {
+                    style,
+                    last_fragment: *last_fragment,
+                })
+            }
+        }
+    }
+}
+
+const LRE: char = '\u{202A}';
+const RLE: char = '\u{202B}';
+const PDF: char = '\u{202C}';
+const LRO: char = '\u{202D}';
+const RLO: char = '\u{202E}';
+const LRI: char = '\u{2066}';
+const RLI: char = '\u{2067}';
+const FSI: char = '\u{2068}';
+const PDI: char = '\u{2069}';
+
+/// The bidi control characters to insert before and after the content of an inline box
+/// for its `unicode-bidi` and `direction` properties
+///
+/// <https://drafts.csswg.org/css-writing-modes/#bidi-control-codes-injection-table>
+fn bidi_control_characters(style: &ComputedValues) -> (&'static [char], &'static [char]) {
+    let rtl = style.writing_modes.direction == values::Direction::Rtl;
+    match style.text.unicode_bidi {
+        UnicodeBidi::Normal => (&[], &[]),
+        UnicodeBidi::Embed if rtl => (&[RLE], &[PDF]),
+        UnicodeBidi::Embed => (&[LRE], &[PDF]),
+        UnicodeBidi::Isolate if rtl => (&[RLI], &[PDI]),
+        UnicodeBidi::Isolate => (&[LRI], &[PDI]),
+        UnicodeBidi::BidiOverride if rtl => (&[RLO], &[PDF]),
+        UnicodeBidi::BidiOverride => (&[LRO], &[PDF]),
+        UnicodeBidi::IsolateOverride if rtl => (&[RLI, RLO], &[PDF, PDI]),
+        UnicodeBidi::IsolateOverride => (&[LRI, LRO], &[PDF, PDI]),
+        UnicodeBidi::Plaintext => (&[FSI], &[PDI]),
+    }
+}
+
+fn is_bidi_control_character(c: char) -> bool {
+    matches!(c, LRE | RLE | PDF | LRO | RLO | LRI | RLI | FSI | PDI)
+}
+
+/// <https://drafts.csswg.org/css-text/#white-space-phase-1> for `white-space: normal`
+fn collapse_white_space(input: &str, output: &mut String) {
+    for c in input.chars() {
+        if matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C') {
+            // Collapse consecutive white space (even across inline boxes
+            // and the bidi control characters inserted for them),
+            // and remove it at the start of the formatting context.
+            let before = output.trim_end_matches(is_bidi_control_character);
+            if !(before.is_empty() || before.ends_with(' ')) {
+                output.push(' ')
+            }
+        } else {
+            output.push(c)
+        }
+    }
+}
+
+enum ChunkItem<'box_tree> {
+    StartInline {
+        style: &'box_tree Rc<ComputedValues>,
+        first_fragment: bool,
+        /// The embedding level of the bidi control characters inserted for this inline box
+        level: Option<Level>,
+    },
+    EndInline {
+        style: &'box_tree Rc<ComputedValues>,
+        last_fragment: bool,
+    },
+    Text {
+        parent_style: &'box_tree Rc<ComputedValues>,
+        segment: ShapedSegment,
+        width: Length<CssPx>,
+        /// Spaces at the end of a line “hang”: they don’t count when checking if content fits.
+        trailing_spaces_width: Length<CssPx>,
+        level: Level,
+    },
+}
+
+impl<'box_tree> ChunkItem<'box_tree> {
+    fn inline_size(&self, containing_block: &ContainingBlock) -> Length<CssPx> {
+        match self {
+            ChunkItem::StartInline {
+                style,
+                first_fragment: true,
+                ..
+            } => edges(style, containing_block).left_typed(),
+            ChunkItem::EndInline {
+                style,
+                last_fragment: true,
+            } => edges(style, containing_block).right_typed(),
+            ChunkItem::StartInline { .. } | ChunkItem::EndInline { .. } => Length::new(0.),
+            ChunkItem::Text { width, .. } => *width,
+        }
+    }
+}
+
+struct LinesState<'box_tree, 'a> {
+    context: &'a LayoutContext<'a>,
+
+    /// In logical coordinates: its width is in the inline direction
+    containing_block: &'a ContainingBlock,
+    writing_mode: WritingMode,
+    line_boxes: Vec<Fragment>,
+    next_line_y: Length<CssPx>,
+
+    /// The width of the longest line box content so far
+    longest_line: Length<CssPx>,
+
+    /// The embedding level of each byte of the text of the inline formatting context
+    levels: Vec<Level>,
+    paragraph_level: Level,
+
+    /// Children of the current line box that are not in an inline box,
+    /// in logical order with their embedding level
+    line_children: Vec<(Level, Fragment)>,
+
+    /// Inline boxes that contain the current position, outermost first
+    inline_boxes: Vec<PartialInlineBox<'box_tree>>,
+
+    /// From the start of the current line box
+    x: Length<CssPx>,
+    line_has_text: bool,
+
+    /// The strut: metrics of the block container’s own font
+    /// <https://drafts.csswg.org/css2/visudet.html#strut>
+    strut_ascent: Length<CssPx>,
+    strut_descent: Length<CssPx>,
+
+    /// Above and below the baseline of the current line box
+    max_ascent: Length<CssPx>,
+    max_descent: Length<CssPx>,
+}
+
+/// The part of an inline box within one line box
+struct PartialInlineBox<'box_tree> {
+    style: &'box_tree Rc<ComputedValues>,
+    has_start_edge: bool,
+    content_start_x: Length<CssPx>,
+    level: Option<Level>,
+    children: Vec<(Level, Fragment)>,
+}
+
+impl<'box_tree, 'a> LinesState<'box_tree, 'a> {
+    fn new(
+        context: &'a LayoutContext<'a>,
+        containing_block: &'a ContainingBlock,
+        style: &ComputedValues,
+        levels: Vec<Level>,
+        paragraph_level: Level,
+    ) -> Self {
+        let (strut_ascent, strut_descent) = context.font_metrics(style);
+        LinesState {
+            context,
+            containing_block,
+            writing_mode: style.writing_modes.writing_mode,
+            levels,
+            paragraph_level,
+            line_boxes: Vec::new(),
+            next_line_y: Length::new(0.),
+            longest_line: Length::new(0.),
+            line_children: Vec::new(),
+            inline_boxes: Vec::new(),
+            x: Length::new(0.),
+            line_has_text: false,
+            strut_ascent,
+            strut_descent,
+            max_ascent: strut_ascent,
+            max_descent: strut_descent,
+        }
+    }
+
+    /// Shape the `range` of `text` into one chunk item per font and embedding level,
+    /// with font fallback.
+    fn shape(
+        &self,
+        parent_style: &'box_tree Rc<ComputedValues>,
+        text: &str,
+        range: Range<usize>,
+        chunk: &mut Vec<ChunkItem<'box_tree>>,
+    ) -> Result<(), FontError> {
+        let full_text = text;
+        let text = &full_text[range.clone()];
+        let mut fonts = self.context.fallback_fonts(parent_style);
+        let mut all_supported = true;
+        for ch in text.chars() {
+            let mut supported = false;
+            for font in &fonts {
+                if font.has_glyph(ch)? {
+                    supported = true;
+                    break
+                }
+            }
+            all_supported &= supported
+        }
+        if !all_supported {
+            let more = self.context.system_fallback_fonts(parent_style, &fonts);
+            fonts.extend(more)
+        }
+        let features = font_features(parent_style);
+        let mut segments = Vec::new();
+        for (run, level) in level_runs(&self.levels, range) {
+            let direction = match self.writing_mode {
+                WritingMode::HorizontalTb => Direction::of_level(level),
+                // FIXME: right-to-left runs in vertical lines
+                WritingMode::VerticalRl => Direction::TopToBottom,
+            };
+            let run_segments = self.context.shaper.shape_with_fallback(
+                &full_text[run],
+                &fonts,
+                &features,
+                direction,
+            )?;
+            segments.extend(run_segments.into_iter().map(|segment| (segment, level)))
+        }
+
+        let px_per_em = parent_style.font.font_size / Length::<Em>::new(1.);
+        let trailing_spaces = text.len() - text.trim_end_matches(' ').len();
+        let last = segments.len().saturating_sub(1);
+        for (i, (segment, level)) in segments.into_iter().enumerate() {
+            // Spaces all use the same font, so trailing spaces are in the last segment.
+            let trailing_spaces_width = if i == last {
+                let font = &segment.font;
+                let space = font.glyph_id(' ')?;
+                let advance = match self.writing_mode {
+                    WritingMode::HorizontalTb => font.glyph_width(space)?,
+                    WritingMode::VerticalRl => font.glyph_vertical_advance(space)?,
+                };
+                advance * px_per_em * trailing_spaces as f32
+            } else {
+                Length::new(0.)
+            };
+            chunk.push(ChunkItem::Text {
+                parent_style,
+                width: segment.advance_width * px_per_em,
+                trailing_spaces_width,
+                segment,
+                level,
+            })
+        }
+        Ok(())
+    }
+
+    fn place_chunk(&mut self, chunk: &mut Vec<ChunkItem<'box_tree>>) {
+        let width = chunk.iter().fold(Length::new(0.), |width, item| {
+            width + item.inline_size(self.containing_block)
+        });
+        let trailing_spaces_width = chunk
+            .iter()
+            .rev()
+            .find_map(|item| match item {
+                ChunkItem::Text {
+                    trailing_spaces_width,
+                    ..
+                } => Some(*trailing_spaces_width),
+                _ => None,
+            })
+            .unwrap_or(Length::new(0.));
+        if self.line_has_text
+            && self.x + width - trailing_spaces_width > self.containing_block.width
+        {
+            self.finish_line()
+        }
+        for item in chunk.drain(..) {
+            match item {
+                ChunkItem::StartInline {
+                    style,
+                    first_fragment,
+                    level,
+                } => {
+                    if first_fragment {
+                        self.x += edges(style, self.containing_block).left_typed()
+                    }
+                    self.inline_boxes.push(PartialInlineBox {
+                        style,
+                        has_start_edge: first_fragment,
+                        content_start_x: self.x,
+                        level,
+                        children: Vec::new(),
+                    })
+                }
+                ChunkItem::EndInline { last_fragment, .. } => {
+                    let partial = self.inline_boxes.pop().unwrap();
+                    self.close_inline_box(partial, last_fragment)
+                }
+                ChunkItem::Text {
+                    parent_style,
+                    segment,
+                    width,
+                    level,
+                    ..
+                } => {
+                    let (ascent, descent) = self.context.font_metrics(parent_style);
+                    self.max_ascent = max(self.max_ascent, ascent);
+                    self.max_descent = max(self.max_descent, descent);
+                    let x = self.x;
+                    self.x += width;
+                    self.line_has_text = true;
+                    let siblings = self.current_children();
+                    // Merge with the previous text fragment if it has the same parent element,
+                    // font, and embedding level, even if it comes from another text node.
+                    if let Some((previous_level, Fragment::Text(previous))) = siblings.last_mut() {
+                        if Rc::ptr_eq(&previous.parent_style, parent_style)
+                            && Arc::ptr_eq(&previous.text.font, &segment.font)
+                            && *previous_level == level
+                        {
+                            previous.content_rect.size.width += width.get();
+                            previous.text.append(segment);
+                            continue
+                        }
+                    }
+                    siblings.push((
+                        level,
+                        Fragment::Text(TextFragment {
+                            parent_style: parent_style.clone(),
+                            content_rect: Rect::new(
+                                Point::from_lengths(x, -ascent),
+                                Size::from_lengths(width, ascent + descent),
+                            ),
+                            text: segment,
+                        }),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn current_children(&mut self) -> &mut Vec<(Level, Fragment)> {
+        match self.inline_boxes.last_mut() {
+            Some(partial) => &mut partial.children,
+            None => &mut self.line_children,
+        }
+    }
+
+    /// Positions of fragments in a line box are relative to its baseline until the line is done.
+    ///
+    /// Padding and borders of inline boxes in the block direction are painted,
+    /// but don’t affect the height of line boxes.
+    fn close_inline_box(&mut self, partial: PartialInlineBox<'box_tree>, has_end_edge: bool) {
+        let PartialInlineBox {
+            style,
+            has_start_edge,
+            content_start_x,
+            level,
+            children,
+        } = partial;
+        // Among its siblings, an inline box is reordered at the lowest level of its content
+        // or of its own bidi control characters.
+        let level = children
+            .iter()
+            .map(|&(level, _)| level)
+            .chain(level)
+            .min()
+            .unwrap_or(self.paragraph_level);
+        let mut children = reorder(children, content_start_x);
+        let cb_width = self.containing_block.width;
+        let mut margin = logical_sides(style, style.margin(cb_width));
+        let mut padding = logical_sides(style, style.padding(cb_width));
+        let mut border = logical_sides(style, style.border_width());
+        // Margins in the block direction don’t apply to inline boxes.
+        margin.top = 0.;
+        margin.bottom = 0.;
+        if !has_start_edge {
+            margin.left = 0.;
+            padding.left = 0.;
+            border.left = 0.;
+        }
+        if !has_end_edge {
+            margin.right = 0.;
+            padding.right = 0.;
+            border.right = 0.;
+        }
+        let (ascent, descent) = self.context.font_metrics(style);
+        self.max_ascent = max(self.max_ascent, ascent);
+        self.max_descent = max(self.max_descent, descent);
+        let content_rect = Rect::new(
+            Point::from_lengths(content_start_x, -ascent),
+            Size::from_lengths(self.x - content_start_x, ascent + descent),
+        );
+        for child in &mut children {
+            child.translate(-content_rect.origin.to_vector())
+        }
+        self.x += padding.right_typed() + border.right_typed() + margin.right_typed();
+        self.current_children().push((
+            level,
+            Fragment::Box(BoxFragment {
+                style: style.clone(),
+                children,
+                content_rect,
+                padding,
+                border,
+                margin,
+            }),
+        ))
+    }
+
+    fn finish_line(&mut self) {
+        // Inline boxes that are still open continue on the next line,
+        // but without their start edge.
+        let open_inline_boxes: Vec<_> = self
+            .inline_boxes
+            .iter()
+            .map(|b| (b.style, b.level))
+            .collect();
+        while let Some(partial) = self.inline_boxes.pop() {
+            self.close_inline_box(partial, false)
+        }
+
+        // Lines without text (for example if they only contain collapsed white space)
+        // are not rendered at all.
+        if self.line_has_text {
+            let baseline = self.max_ascent;
+            let children = std::mem::replace(&mut self.line_children, Vec::new());
+            let mut children = reorder(children, Length::new(0.));
+            // Right-to-left paragraphs are aligned to the right
+            let free_space = self.containing_block.width - self.x;
+            let start = if self.paragraph_level.is_rtl() && free_space > Length::new(0.) {
+                free_space
+            } else {
+                Length::new(0.)
+            };
+            for child in &mut children {
+                child.translate(Vector::from_lengths(start, baseline))
+            }
+            let height = self.max_ascent + self.max_descent;
+            self.longest_line = max(self.longest_line, self.x);
+            self.line_boxes.push(Fragment::Anonymous(AnonymousFragment {
+                rect: Rect::new(
+                    Point::from_lengths(Length::new(0.), self.next_line_y),
+                    Size::from_lengths(self.containing_block.width, height),
+                ),
+                children,
+            }));
+            self.next_line_y += height;
+        }
+
+        self.line_children.clear();
+        self.x = Length::new(0.);
+        self.line_has_text = false;
+        self.max_ascent = self.strut_ascent;
+        self.max_descent = self.strut_descent;
+        for (style, level) in open_inline_boxes {
+            self.inline_boxes.push(PartialInlineBox {
+                style,
+                has_start_edge: false,
+                content_start_x: self.x,
+                level,
+                children: Vec::new(),
+            })
+        }
+    }
+}
+
+/// Take fragments in logical order, laid out next to each other from `start_x`,
+/// and move them to their visual position based on their embedding levels.
+fn reorder(children: Vec<(Level, Fragment)>, start_x: Length<CssPx>) -> Vec<Fragment> {
+    let levels: Vec<Level> = children.iter().map(|&(level, _)| level).collect();
+    let mut children: Vec<_> = children
+        .into_iter()
+        .map(|(_, fragment)| Some(fragment))
+        .collect();
+    if !levels.iter().any(|level| level.is_rtl()) {
+        // Without right-to-left content, visual order is the same as logical order.
+        return children.into_iter().map(Option::unwrap).collect()
+    }
+    let mut x = start_x;
+    visual_order(&levels)
+        .into_iter()
+        .map(|index| {
+            let mut fragment = children[index].take().unwrap();
+            let rect = match &fragment {
+                Fragment::Box(fragment) => fragment.margin_rect(),
+                Fragment::Anonymous(fragment) => fragment.rect,
+                Fragment::Text(fragment) => fragment.content_rect,
+            };
+            fragment.translate(Vector::from_lengths(
+                x - rect.min_x_typed(),
+                Length::new(0.),
+            ));
+            x += rect.size.width_typed();
+            fragment
+        })
+        .collect()
+}
+
+/// The sum of margins, borders, and padding on each side of an inline box, in logical order
+fn edges(style: &ComputedValues, containing_block: &ContainingBlock) -> SideOffsets<CssPx> {
+    let cb_width = containing_block.width;
+    logical_sides(
+        style,
+        style.margin(cb_width) + style.border_width() + style.padding(cb_width),
+    )
+}
+
+/// Map physical sides to block-start, inline-end, block-end, and inline-start,
+/// in the writing mode of `style`
+fn logical_sides(style: &ComputedValues, sides: SideOffsets<CssPx>) -> SideOffsets<CssPx> {
+    match style.writing_modes.writing_mode {
+        WritingMode::HorizontalTb => sides,
+        WritingMode::VerticalRl => {
+            SideOffsets::new(sides.right, sides.bottom, sides.left, sides.top)
+        }
+    }
+}
+
+impl LayoutContext<'_> {
+    /// Distances above and below the baseline,
+    /// or on each side of the central baseline in vertical writing mode
+    fn font_metrics(&self, style: &ComputedValues) -> (Length<CssPx>, Length<CssPx>) {
+        if style.writing_modes.writing_mode == WritingMode::VerticalRl {
+            let half = style.font.font_size / 2.;
+            return (half, half)
+        }
+        let font = self.font(style);
+        let px_per_em = style.font.font_size / Length::<Em>::new(1.);
+        (font.ascender() * px_per_em, -font.descender() * px_per_em)
+    }
+}
+
+fn max(a: Length<CssPx>, b: Length<CssPx>) -> Length<CssPx> {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
@@ -0,0 +1,264 @@
+//! Page-margin boxes, for running headers and footers
+//!
+//! <https://drafts.csswg.org/css-page-3/#margin-boxes>
+
+use super::flow::ContainingBlock;
+use super::fragments::{BoxFragment, Fragment};
+use super::{BlockContainer, InlineLevel, LayoutContext};
+use crate::fonts::FontError;
+use crate::primitives::{CssPx, Length, Point, Rect, SideOffsets, Size, Vector};
+use crate::style::values::content::{Content, ContentItem};
+use crate::style::{ComputedValues, PageMarginBox, PageStyles};
+
+/// Values of the counters available in margin boxes
+pub(super) struct PageCounters {
+    /// The current page number, starting at 1
+    pub(super) page: usize,
+
+    /// The total number of pages
+    pub(super) pages: usize,
+}
+
+impl PageCounters {
+    fn get(&self, name: &str) -> usize {
+        match name {
+            "page" => self.page,
+            "pages" => self.pages,
+            // Counters that were never instantiated are zero.
+            _ => 0,
+        }
+    }
+}
+
+/// Lay out the margin boxes of one page.
+///
+/// This needs to happen after paginating the document’s content,
+/// since `counter(pages)` depends on the total number of pages.
+///
+/// Fragments are positioned relative to the top-left corner of the page box.
+pub(super) fn layout_margin_boxes(
+    context: &LayoutContext,
+    page_styles: &PageStyles,
+    page_margin: &SideOffsets<CssPx>,
+    counters: &PageCounters,
+) -> Result<Vec<Fragment>, FontError> {
+    let mut generated = Vec::new();
+    for (margin_box, style) in &page_styles.margin_boxes {
+        // `normal` computes to `none` on margin boxes, which are then not generated.
+        let text: String = match &style.content.content {
+            Content::Normal | Content::None => continue,
+            Content::Items(items) => items
+                .iter()
+                .map(|item| match item {
+                    ContentItem::String(string) => string.clone(),
+                    ContentItem::Counter(name) => counters.get(name).to_string(),
+                })
+                .collect(),
+        };
+        let contents =
+            BlockContainer::InlineFormattingContext(vec![InlineLevel::Text(text.into())]);
+        generated.push((*margin_box, style, contents))
+    }
+
+    // FIXME: percentages should be relative to the box’s own containing block,
+    // which depends on its position.
+    let available_width = page_styles.size.width_typed() - page_margin.horizontal_typed();
+    let edges = |style: &ComputedValues| {
+        style.margin(available_width) + style.border_width() + style.padding(available_width)
+    };
+
+    // Max-content widths of boxes at the start, center, and end of the top and bottom sides,
+    // including horizontal margins, borders, and padding
+    let mut top = [None; 3];
+    let mut bottom = [None; 3];
+    for (margin_box, style, contents) in &generated {
+        if let Some((is_top, index)) = margin_box.horizontal_position() {
+            let max_content = ContainingBlock {
+                width: Length::new(std::f32::INFINITY),
+                height: None,
+            };
+            let (lines, _) = contents.layout(context, &max_content, style)?;
+            let width = lines.iter().map(line_content_width).fold(0., f32::max);
+            let side = if is_top { &mut top } else { &mut bottom };
+            side[index] = Some(Length::new(width) + edges(style).horizontal_typed())
+        }
+    }
+    let top_widths = resolve_widths(available_width, top);
+    let bottom_widths = resolve_widths(available_width, bottom);
+
+    let mut fragments = Vec::new();
+    for (margin_box, style, contents) in generated {
+        let side_widths = match margin_box.horizontal_position() {
+            Some((false, _)) => &bottom_widths,
+            _ => &top_widths,
+        };
+        let margin = style.margin(available_width);
+        let padding = style.padding(available_width);
+        let border = style.border_width();
+        let rect = margin_box
+            .rect(page_styles.size, page_margin, side_widths)
+            .inner_rect(margin + border + padding);
+        let containing_block = ContainingBlock {
+            width: rect.size.width_typed(),
+            height: Some(rect.size.height_typed()),
+        };
+        let (mut lines, height) = contents.layout(context, &containing_block, style)?;
+
+        // FIXME: use the `text-align` and `vertical-align` properties when they are supported.
+        let (horizontal, vertical) = margin_box.alignment();
+        let dy = (rect.size.height_typed() - height) * vertical;
+        for line in &mut lines {
+            let width = Length::new(line_content_width(line));
+            let dx = (rect.size.width_typed() - width) * horizontal;
+            line.translate(Vector::from_lengths(dx, dy))
+        }
+        fragments.push(Fragment::Box(BoxFragment {
+            style: style.clone(),
+            children: lines,
+            content_rect: rect,
+            padding,
+            border,
+            margin,
+        }))
+    }
+    Ok(fragments)
+}
+
+/// Distribute the width between page margins to the boxes
+/// at the start, center, and end of the top or bottom side of the page,
+/// in proportion to their max-content widths.
+/// `None` is for boxes that are not generated.
+///
+/// The center box stays centered.
+///
+/// <https://drafts.csswg.org/css-page-3/#margin-width>
+fn resolve_widths(available: Length<CssPx>, max_content: [Option<Length<CssPx>>; 3]) -> [f32; 3] {
+    let available = available.0;
+    let [start, center, end] = max_content;
+    let width = |length: Option<Length<CssPx>>| length.map_or(0., |length| length.0);
+    if center.is_some() {
+        let sides = width(start).max(width(end));
+        let total = width(center) + 2. * sides;
+        let center = if total > 0. {
+            available * width(center) / total
+        } else {
+            available / 3.
+        };
+        let side = (available - center) / 2.;
+        [side, center, side]
+    } else {
+        let total = width(start) + width(end);
+        let start = if total > 0. {
+            available * width(start) / total
+        } else {
+            available / 2.
+        };
+        [start, 0., available - start]
+    }
+}
+
+impl PageMarginBox {
+    /// For boxes on the top or bottom side of the page (excluding corners):
+    /// whether this is the top side, and the position (start, center, or end) on that side.
+    fn horizontal_position(self) -> Option<(bool, usize)> {
+        use self::PageMarginBox::*;
+
+        match self {
+            TopLeft => Some((true, 0)),
+            TopCenter => Some((true, 1)),
+            TopRight => Some((true, 2)),
+            BottomLeft => Some((false, 0)),
+            BottomCenter => Some((false, 1)),
+            BottomRight => Some((false, 2)),
+            _ => None,
+        }
+    }
+
+    /// `side_widths` are the widths of boxes at the start, center, and end of
+    /// this box’s side of the page, if it is the top or bottom side.
+    ///
+    /// <https://drafts.csswg.org/css-page-3/#margin-dimension>
+    ///
+    /// FIXME: size boxes on the left and right sides based on their contents.
+    /// For now, they get equal heights.
+    fn rect(
+        self,
+        page_size: Size<CssPx>,
+        page_margin: &SideOffsets<CssPx>,
+        side_widths: &[f32; 3],
+    ) -> Rect<CssPx> {
+        use self::PageMarginBox::*;
+
+        let page_width = page_size.width_typed();
+        let page_height = page_size.height_typed();
+        let left = page_margin.left_typed();
+        let right = page_margin.right_typed();
+        let top = page_margin.top_typed();
+        let bottom = page_margin.bottom_typed();
+        let zero = Length::new(0.);
+        let third_height = (page_height - top - bottom) / 3.;
+        let vertical = |index: f32| top + third_height * index;
+        let [start_width, center_width, end_width] = *side_widths;
+        let start_width = Length::new(start_width);
+        let center_width = Length::new(center_width);
+        let end_width = Length::new(end_width);
+        let start_x = left;
+        let center_x = left + start_width;
+        let end_x = page_width - right - end_width;
+
+        let (x, y, width, height) = match self {
+            TopLeftCorner => (zero, zero, left, top),
+            TopLeft => (start_x, zero, start_width, top),
+            TopCenter => (center_x, zero, center_width, top),
+            TopRight => (end_x, zero, end_width, top),
+            TopRightCorner => (page_width - right, zero, right, top),
+            RightTop => (page_width - right, vertical(0.), right, third_height),
+            RightMiddle => (page_width - right, vertical(1.), right, third_height),
+            RightBottom => (page_width - right, vertical(2.), right, third_height),
+            BottomRightCorner => (page_width - right, page_height - bottom, right, bottom),
+            BottomRight => (end_x, page_height - bottom, end_width, bottom),
+            BottomCenter => (center_x, page_height - bottom, center_width, bottom),
+            BottomLeft => (start_x, page_height - bottom, start_width, bottom),
+            BottomLeftCorner => (zero, page_height - bottom, left, bottom),
+            LeftBottom => (zero, vertical(2.), left, third_height),
+            LeftMiddle => (zero, vertical(1.), left, third_height),
+            LeftTop => (zero, vertical(0.), left, third_height),
+        };
+        Rect::new(Point::from_lengths(x, y), Size::from_lengths(width, height))
+    }
+
+    /// Horizontal and vertical alignment of contents:
+    /// 0 for start, 0.5 for center, 1 for end.
+    ///
+    /// <https://drafts.csswg.org/css-page-3/#margin-text-alignment>
+    fn alignment(self) -> (f32, f32) {
+        use self::PageMarginBox::*;
+
+        match self {
+            TopLeftCorner | BottomLeftCorner => (1., 0.5),
+            TopRightCorner | BottomRightCorner => (0., 0.5),
+            TopLeft | BottomLeft => (0., 0.5),
+            TopCenter | BottomCenter => (0.5, 0.5),
+            TopRight | BottomRight => (1., 0.5),
+            LeftTop | RightTop => (0.5, 0.),
+            LeftMiddle | RightMiddle => (0.5, 0.5),
+            LeftBottom | RightBottom => (0.5, 1.),
+        }
+    }
+}
+
+/// The width taken by the contents of a line box
+fn line_content_width(line: &Fragment) -> f32 {
+    let mut width = 0_f32;
+    if let Fragment::Anonymous(line) = line {
+        for child in &line.children {
+            let right = match child {
+                Fragment::Box(fragment) => fragment.content_rect.max_x() + fragment.margin.right,
+                Fragment::Anonymous(fragment) => fragment.rect.max_x(),
+                Fragment::Text(fragment) => fragment.content_rect.max_x(),
+            };
+            width = width.max(right)
+        }
+    }
+    width
+}
@@ -1,8 +1,178 @@
-use crate::style::ComputedValues;
+use self::flow::ContainingBlock;
+use self::fragments::Fragment;
+use self::margin_boxes::{layout_margin_boxes, PageCounters};
+use crate::dom;
+use crate::fonts::{FontCollection, FontError};
+use crate::pdf;
+use crate::primitives::{CssPx, Length, Point, Rect, SideOffsets, Size, Vector};
+use crate::style::values::length::LengthPercentageOrAuto;
+use crate::style::{cascade, cascade_page, ComputedValues, Device, StyleSet, StyleSetBuilder};
+use crate::text::{OpenTypeShaper, Shaper};
+use crate::text_plain::css_units::{Mm, Px};
 use html5ever::tendril::StrTendril;
 use std::rc::Rc;
 
 mod box_generation;
+mod flow;
+mod font_matching;
+mod fragments;
+mod inline;
+mod margin_boxes;
+mod pagination;
+mod painting;
+
+/// Defaults for the size and margins of pages,
+/// used when the document’s stylesheets don’t specify them with `@page` rules.
+pub struct PageSettings {
+    pub page_size: Size<Mm>,
+    pub page_margin: Length<Mm>,
+}
+
+impl dom::Document {
+    /// Lay out this document, break it into pages, and paint them into a new PDF document.
+    ///
+    /// Text is rendered with the regular, bold, oblique, and bold oblique faces
+    /// of the Bitstream Vera Sans font, selected based on style.
+    pub fn render_to_pdf(&self, page_settings: &PageSettings) -> Result<pdf::Document, FontError> {
+        self.render_to_pdf_with_fonts(page_settings, &FontCollection::bitstream_vera_sans()?)
+    }
+
+    /// Like `render_to_pdf`, but text is rendered with fonts selected from `fonts`
+    /// based on `font-family` and other font properties.
+    ///
+    /// If `fonts` is empty, Bitstream Vera Sans is used instead.
+    /// Fonts from `@font-face` rules are added to it.
+    ///
+    /// Text is shaped with `OpenTypeShaper`.
+    pub fn render_to_pdf_with_fonts(
+        &self,
+        page_settings: &PageSettings,
+        fonts: &FontCollection,
+    ) -> Result<pdf::Document, FontError> {
+        self.render_to_pdf_with_shaper(page_settings, fonts, &OpenTypeShaper)
+    }
+
+    /// Like `render_to_pdf_with_fonts`, with the given implementation of text shaping.
+    pub fn render_to_pdf_with_shaper(
+        &self,
+        page_settings: &PageSettings,
+        fonts: &FontCollection,
+        shaper: &dyn Shaper,
+    ) -> Result<pdf::Document, FontError> {
+        let author_styles = self.author_styles();
+        let default_page_size = page_settings.page_size * Px::per_mm();
+        let mut device = Device::new(default_page_size);
+        let root_element_style = cascade(&author_styles, self, self.root_element(), None, &device);
+        device.root_font_size = root_element_style.font.font_size;
+        let page_styles = cascade_page(
+            &author_styles,
+            &device,
+            default_page_size,
+            SideOffsets::from_length_all_same(page_settings.page_margin * Px::per_mm()),
+        );
+        let page_width = page_styles.size.width_typed();
+        let page_height = page_styles.size.height_typed();
+        // FIXME: resolve `auto` margins of the page box
+        let page_margin = page_styles.page_context.margin(page_width);
+        // Unlike for other boxes, percentages in vertical page margins are relative to the height.
+        let vertical = |value: &LengthPercentageOrAuto| {
+            value.resolve(Some(page_height)).unwrap_or(Length::new(0.))
+        };
+        let page_margin = SideOffsets::from_lengths(
+            vertical(&page_styles.page_context.margin.margin_top),
+            page_margin.right_typed(),
+            vertical(&page_styles.page_context.margin.margin_bottom),
+            page_margin.left_typed(),
+        );
+        let page_area = Rect::new(Point::origin(), page_styles.size).inner_rect(page_margin);
+        let mut fonts = if fonts.is_empty() {
+            FontCollection::bitstream_vera_sans()?
+        } else {
+            fonts.clone()
+        };
+        for rule in author_styles.font_face_rules() {
+            // FIXME: error reporting for fonts that fail to load
+            if let Some(face) = rule.load(self.base_path(), &fonts) {
+                fonts.add_face(face)
+            }
+        }
+        let fragments = self.layout(&author_styles, page_area.size, fonts.clone(), shaper)?;
+        let pages = pagination::paginate(&fragments, page_area.size.height_typed());
+
+        // Second pass, now that the number of pages is known
+        let context = LayoutContext {
+            fonts,
+            shaper,
+            viewport: page_area.size,
+        };
+        let mut pdf_doc = pdf::Document::new();
+        for (index, page_fragments) in pages.iter().enumerate() {
+            let counters = PageCounters {
+                page: index + 1,
+                pages: pages.len(),
+            };
+            let margin_boxes =
+                layout_margin_boxes(&context, &page_styles, &page_margin, &counters)?;
+            let mut page = pdf_doc.add_page(page_styles.size);
+            for fragment in &margin_boxes {
+                fragment.paint_onto(&mut page, Vector::zero())?
+            }
+            for fragment in page_fragments {
+                fragment.paint_onto(&mut page, page_area.origin.to_vector())?
+            }
+        }
+        Ok(pdf_doc)
+    }
+
+    /// Generate the box tree of this document for an A4 viewport, then discard it.
+    #[deprecated(note = "use `render_to_pdf`, which lays out the document and returns its pages")]
+    pub fn render(&self) {
+        let viewport = Size::new(210., 297.) * Px::per_mm();
+        let _ = self.box_tree(&self.author_styles(), viewport);
+    }
+
+    fn author_styles(&self) -> StyleSet {
+        let mut builder = StyleSetBuilder::new();
+        self.parse_stylesheets(&mut builder);
+        builder.finish()
+    }
+
+    /// Return the fragments of the root element’s box, and of any anonymous box at the root level.
+    ///
+    /// Their positions are relative to the top-left corner of the initial containing block,
+    /// which has the dimensions of `viewport`.
+    pub(crate) fn layout(
+        &self,
+        author_styles: &StyleSet,
+        viewport: Size<CssPx>,
+        fonts: FontCollection,
+        shaper: &dyn Shaper,
+    ) -> Result<Vec<Fragment>, FontError> {
+        let box_tree = self.box_tree(author_styles, viewport);
+        let context = LayoutContext {
+            fonts,
+            shaper,
+            viewport,
+        };
+        let initial_containing_block = ContainingBlock {
+            width: viewport.width_typed(),
+            height: Some(viewport.height_typed()),
+        };
+        let (fragments, _) = box_tree.layout(&context, &initial_containing_block)?;
+        Ok(fragments)
+    }
+}
+
+struct LayoutContext<'a> {
+    /// Available font faces, which text selects from based on its style.
+    /// Never empty.
+    fonts: FontCollection,
+
+    shaper: &'a dyn Shaper,
+
+    /// The size of the initial containing block
+    viewport: Size<CssPx>,
+}
 
 type BoxTreeRoot = BlockFormattingContext;
 
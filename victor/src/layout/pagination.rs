@@ -0,0 +1,272 @@
+//! Breaking block-level content across pages
+//!
+//! <https://drafts.csswg.org/css-break/>
+
+use super::fragments::{BoxFragment, Fragment};
+use crate::primitives::{CssPx, Length, Point, Rect, Size, Vector};
+use crate::style::values::{BreakBetween, BreakWithin};
+
+/// Split fragments laid out in a page area of infinite height into pages of `page_height`.
+///
+/// Each page’s fragments are positioned relative to the top of its page area.
+///
+/// Line boxes are never split. Block boxes are split between their children,
+/// and their margins, borders, and padding are truncated at page breaks.
+pub(super) fn paginate(fragments: &[Fragment], page_height: Length<CssPx>) -> Vec<Vec<Fragment>> {
+    let mut pages = Vec::new();
+    let mut page_start = Length::new(0.);
+    loop {
+        let page = PageRange {
+            start: page_start,
+            end: page_start + page_height,
+        };
+        let mut has_content = false;
+        let (mut page_fragments, next_page_start) =
+            paginate_siblings(fragments, &page, &mut has_content);
+        let offset = Vector::from_lengths(Length::new(0.), -page_start);
+        for fragment in &mut page_fragments {
+            fragment.translate(offset)
+        }
+        pages.push(page_fragments);
+        match next_page_start {
+            // Always make progress
+            Some(next) if next > page_start => page_start = next,
+            Some(_) => page_start = page.end,
+            None => return pages,
+        }
+    }
+}
+
+/// In the coordinate system of the fragments being paginated
+struct PageRange {
+    start: Length<CssPx>,
+    end: Length<CssPx>,
+}
+
+/// Return the parts of `fragments` that go on the given page,
+/// and where the next page starts if some of them don’t fit.
+///
+/// `has_content` is whether anything was already placed on this page,
+/// before these fragments in tree order.
+fn paginate_siblings(
+    fragments: &[Fragment],
+    page: &PageRange,
+    has_content: &mut bool,
+) -> (Vec<Fragment>, Option<Length<CssPx>>) {
+    let mut on_this_page = Vec::new();
+    // Index in `fragments` of the last item of `on_this_page`,
+    // if that fragment starts on this page and was not split.
+    let mut previous_unsplit = None;
+    for (i, fragment) in fragments.iter().enumerate() {
+        let (top, bottom) = fragment.block_extent();
+        let is_box = match fragment {
+            Fragment::Box(_) => true,
+            Fragment::Anonymous(_) | Fragment::Text(_) => false,
+        };
+        if top < page.start && (bottom <= page.start || !is_box) {
+            // Already on previous pages
+            continue
+        }
+
+        let previous = if i > 0 { Some(&fragments[i - 1]) } else { None };
+        if let Some(previous) = previous {
+            if *has_content
+                && top >= page.start
+                && (any_break_after(previous, BreakBetween::is_forced)
+                    || any_break_before(fragment, BreakBetween::is_forced))
+            {
+                // Margins after a forced break are preserved.
+                return (on_this_page, Some(top - fragment.margin_top()))
+            }
+        }
+
+        let placed = match fragment {
+            _ if top >= page.end => false,
+            Fragment::Box(box_fragment) => {
+                let avoid_break_inside = match box_fragment.style.fragmentation.break_inside {
+                    BreakWithin::Auto => false,
+                    BreakWithin::Avoid | BreakWithin::AvoidPage => true,
+                };
+                if *has_content
+                    && avoid_break_inside
+                    && bottom > page.end
+                    && bottom - top <= page.end - page.start
+                {
+                    false
+                } else {
+                    let had_content = *has_content;
+                    let (part, next_page_start) = box_fragment.paginate(page, has_content);
+                    if had_content
+                        && top >= page.start
+                        && part.children.is_empty()
+                        && next_page_start.is_some()
+                    {
+                        // Nothing inside this box fits on this page, move all of it to the next.
+                        false
+                    } else {
+                        on_this_page.push(Fragment::Box(part));
+                        *has_content = true;
+                        if next_page_start.is_some() {
+                            return (on_this_page, next_page_start)
+                        }
+                        previous_unsplit = if top >= page.start && bottom <= page.end {
+                            Some(i)
+                        } else {
+                            None
+                        };
+                        true
+                    }
+                }
+            }
+            // Monolithic: placed in full, overflowing if it doesn’t fit in an empty page.
+            Fragment::Anonymous(_) | Fragment::Text(_) => {
+                if bottom <= page.end || !*has_content {
+                    on_this_page.push(fragment.clone());
+                    *has_content = true;
+                    previous_unsplit = Some(i);
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if placed {
+            continue
+        }
+
+        // Unforced break before this fragment
+        if let Some(previous) = previous {
+            let avoid = any_break_after(previous, BreakBetween::is_avoid)
+                || any_break_before(fragment, BreakBetween::is_avoid);
+            let (previous_top, _) = previous.block_extent();
+            if avoid
+                && previous_unsplit == Some(i - 1)
+                && on_this_page.len() > 1
+                && previous_top > page.start
+            {
+                on_this_page.pop();
+                return (on_this_page, Some(previous_top))
+            }
+        }
+        return (on_this_page, Some(top))
+    }
+    (on_this_page, None)
+}
+
+impl BoxFragment {
+    /// Return the part of this box that goes on the given page,
+    /// and where the next page starts if the rest of the box doesn’t fit.
+    fn paginate(
+        &self,
+        page: &PageRange,
+        has_content: &mut bool,
+    ) -> (BoxFragment, Option<Length<CssPx>>) {
+        let content_top = self.content_rect.min_y_typed();
+        let content_bottom = self.content_rect.max_y_typed();
+        let border_top = self.border_rect().min_y_typed();
+        let inner_page = PageRange {
+            start: page.start - content_top,
+            end: page.end - content_top,
+        };
+        let (mut children, inner_break) =
+            paginate_siblings(&self.children, &inner_page, has_content);
+        let next_page_start = inner_break.map(|position| position + content_top);
+
+        // FIXME: with explicit heights, content could continue past the end
+        // of the page without any child fragment being split.
+        let top = max(content_top, page.start);
+        let bottom = max(
+            top,
+            min(content_bottom, next_page_start.unwrap_or(page.end)),
+        );
+        let offset = Vector::from_lengths(Length::new(0.), content_top - top);
+        for child in &mut children {
+            child.translate(offset)
+        }
+        let mut padding = self.padding;
+        let mut border = self.border;
+        let mut margin = self.margin;
+        // FIXME: support `box-decoration-break: clone`
+        if border_top < page.start {
+            padding.top = 0.;
+            border.top = 0.;
+            margin.top = 0.
+        }
+        if content_bottom > bottom {
+            padding.bottom = 0.;
+            border.bottom = 0.;
+            margin.bottom = 0.
+        }
+        let part = BoxFragment {
+            style: self.style.clone(),
+            children,
+            content_rect: Rect::new(
+                Point::from_lengths(self.content_rect.min_x_typed(), top),
+                Size::from_lengths(self.content_rect.size.width_typed(), bottom - top),
+            ),
+            padding,
+            border,
+            margin,
+        };
+        (part, next_page_start)
+    }
+}
+
+impl Fragment {
+    /// The top and bottom edges of the border box or line box
+    fn block_extent(&self) -> (Length<CssPx>, Length<CssPx>) {
+        let rect = match self {
+            Fragment::Box(fragment) => fragment.border_rect(),
+            Fragment::Anonymous(fragment) => fragment.rect,
+            Fragment::Text(fragment) => fragment.content_rect,
+        };
+        (rect.min_y_typed(), rect.max_y_typed())
+    }
+
+    fn margin_top(&self) -> Length<CssPx> {
+        match self {
+            Fragment::Box(fragment) => fragment.margin.top_typed(),
+            Fragment::Anonymous(_) | Fragment::Text(_) => Length::new(0.),
+        }
+    }
+}
+
+/// Whether `predicate` is true for the `break-before` value of this fragment,
+/// or of one propagated from its first child.
+///
+/// <https://drafts.csswg.org/css-break/#break-propagation>
+fn any_break_before(fragment: &Fragment, predicate: fn(BreakBetween) -> bool) -> bool {
+    match fragment {
+        Fragment::Box(fragment) => {
+            predicate(fragment.style.fragmentation.break_before)
+                || fragment
+                    .children
+                    .first()
+                    .map_or(false, |child| any_break_before(child, predicate))
+        }
+        Fragment::Anonymous(_) | Fragment::Text(_) => false,
+    }
+}
+
+/// Whether `predicate` is true for the `break-after` value of this fragment,
+/// or of one propagated from its last child.
+fn any_break_after(fragment: &Fragment, predicate: fn(BreakBetween) -> bool) -> bool {
+    match fragment {
+        Fragment::Box(fragment) => {
+            predicate(fragment.style.fragmentation.break_after)
+                || fragment
+                    .children
+                    .last()
+                    .map_or(false, |child| any_break_after(child, predicate))
+        }
+        Fragment::Anonymous(_) | Fragment::Text(_) => false,
+    }
+}
+
+fn min(a: Length<CssPx>, b: Length<CssPx>) -> Length<CssPx> {
+    Length::new(a.0.min(b.0))
+}
+
+fn max(a: Length<CssPx>, b: Length<CssPx>) -> Length<CssPx> {
+    Length::new(a.0.max(b.0))
+}
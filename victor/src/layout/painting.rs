@@ -0,0 +1,159 @@
+use super::fragments::{BoxFragment, Fragment, TextFragment};
+use crate::fonts::{Em, FontError};
+use crate::pdf::Page;
+use crate::primitives::{rect, CssPx, Length, Rect, TextRun, Vector};
+use crate::style::values::border::BorderStyle;
+use crate::text::Direction;
+
+impl Fragment {
+    /// `containing_block_origin` is in page coordinates.
+    pub(super) fn paint_onto(
+        &self,
+        page: &mut Page,
+        containing_block_origin: Vector<CssPx>,
+    ) -> Result<(), FontError> {
+        match self {
+            Fragment::Box(fragment) => fragment.paint_onto(page, containing_block_origin),
+            Fragment::Anonymous(fragment) => {
+                let origin = containing_block_origin + fragment.rect.origin.to_vector();
+                for child in &fragment.children {
+                    child.paint_onto(page, origin)?
+                }
+                Ok(())
+            }
+            Fragment::Text(fragment) => fragment.paint_onto(page, containing_block_origin),
+        }
+    }
+}
+
+impl BoxFragment {
+    fn paint_onto(
+        &self,
+        page: &mut Page,
+        containing_block_origin: Vector<CssPx>,
+    ) -> Result<(), FontError> {
+        // FIXME: propagate the root element’s background to the canvas
+        // https://drafts.csswg.org/css-backgrounds/#root-background
+        let border_rect = self.border_rect().translate(&containing_block_origin);
+        let current_color = self.style.color.color;
+        let background_color = self
+            .style
+            .background
+            .background_color
+            .resolve(current_color);
+        if background_color.3 > 0. {
+            page.set_color(&background_color)
+                .paint_rectangle(&border_rect);
+        }
+        self.paint_borders(page, &border_rect);
+        let content_origin = containing_block_origin + self.content_rect.origin.to_vector();
+        for child in &self.children {
+            child.paint_onto(page, content_origin)?
+        }
+        Ok(())
+    }
+}
+
+impl BoxFragment {
+    /// Each side is painted as filled rectangles.
+    /// Where two sides meet, the corner is part of the top or bottom side.
+    ///
+    /// FIXME: paint `dotted`, `dashed`, `groove`, `ridge`, `inset`, and `outset` borders
+    /// with their respective patterns and shading. For now they are painted as `solid`.
+    fn paint_borders(&self, page: &mut Page, border_rect: &Rect<CssPx>) {
+        let b = &self.style.border;
+        let current_color = self.style.color.color;
+        let width = self.border;
+        let outer = border_rect;
+        let inner_top = outer.min_y() + width.top;
+        let inner_bottom = outer.max_y() - width.bottom;
+        let sides = [
+            (
+                b.border_top_style,
+                b.border_top_color,
+                rect(outer.min_x(), outer.min_y(), outer.size.width, width.top),
+                false,
+            ),
+            (
+                b.border_right_style,
+                b.border_right_color,
+                rect(
+                    outer.max_x() - width.right,
+                    inner_top,
+                    width.right,
+                    inner_bottom - inner_top,
+                ),
+                true,
+            ),
+            (
+                b.border_bottom_style,
+                b.border_bottom_color,
+                rect(outer.min_x(), inner_bottom, outer.size.width, width.bottom),
+                false,
+            ),
+            (
+                b.border_left_style,
+                b.border_left_color,
+                rect(
+                    outer.min_x(),
+                    inner_top,
+                    width.left,
+                    inner_bottom - inner_top,
+                ),
+                true,
+            ),
+        ];
+        for &(style, color, side, is_vertical) in &sides {
+            let color = color.resolve(current_color);
+            if style.is_none_or_hidden() || side.size.is_empty_or_negative() || color.3 <= 0. {
+                continue
+            }
+            page.set_color(&color);
+            if style == BorderStyle::Double {
+                // Two lines separated by a gap, each one third of the border width
+                let (first, second) = if is_vertical {
+                    let third = side.size.width / 3.;
+                    (
+                        rect(side.min_x(), side.min_y(), third, side.size.height),
+                        rect(side.max_x() - third, side.min_y(), third, side.size.height),
+                    )
+                } else {
+                    let third = side.size.height / 3.;
+                    (
+                        rect(side.min_x(), side.min_y(), side.size.width, third),
+                        rect(side.min_x(), side.max_y() - third, side.size.width, third),
+                    )
+                };
+                page.paint_rectangle(&first).paint_rectangle(&second);
+            } else {
+                page.paint_rectangle(&side);
+            }
+        }
+    }
+}
+
+impl TextFragment {
+    fn paint_onto(
+        &self,
+        page: &mut Page,
+        containing_block_origin: Vector<CssPx>,
+    ) -> Result<(), FontError> {
+        let font_size = self.parent_style.font.font_size;
+        let top_left = self.content_rect.origin + containing_block_origin;
+        let origin = if self.text.direction == Direction::TopToBottom {
+            // Vertical text is centered on its line
+            let half_width = self.content_rect.size.width_typed() / 2.;
+            top_left + Vector::from_lengths(half_width, Length::new(0.))
+        } else {
+            let ascent = self.text.font.ascender() * (font_size / Length::<Em>::new(1.));
+            top_left + Vector::from_lengths(Length::new(0.), ascent)
+        };
+        page.set_color(&self.parent_style.color.color);
+        page.show_text(&TextRun {
+            segment: self.text.clone(),
+            font_size,
+            origin,
+        })?;
+        Ok(())
+    }
+}
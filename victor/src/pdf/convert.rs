@@ -1,9 +1,12 @@
-use crate::fonts::{Font, FontError, GlyphId};
+use crate::fonts::{Em, Font, FontError, GlyphId, OutlineFormat};
 use crate::pdf::object::{Dictionary, Object};
-use crate::pdf::syntax::{BasicObjects, PdfFile, PAGE_TREE_ID};
+use crate::pdf::syntax::{BasicObjects, IndirectObjectId, PdfFile, PAGE_TREE_ID};
 use crate::primitives::*;
+use crate::text::Direction;
+use std::borrow::Cow;
+use std::collections::btree_map;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash;
 use std::io::{self, Write};
 use std::ops::Deref;
@@ -19,9 +22,12 @@ pub(crate) struct InProgressDoc {
     pdf: PdfFile,
     page_ids: Vec<Object<'static>>,
     extended_graphics_states: Vec<(Vec<u8>, Object<'static>)>,
-    font_resources: Vec<(Vec<u8>, Object<'static>)>,
     alpha_states: HashMap<u16, String>,
-    fonts: HashMap<ByAddress<Arc<Font>>, String>,
+
+    /// Indices in `embedded_fonts`, by font and whether it is used for vertical text.
+    /// A font used in both writing modes is embedded twice, with different encodings.
+    fonts: HashMap<(ByAddress<Arc<Font>>, bool), usize>,
+    embedded_fonts: Vec<EmbeddedFont>,
 }
 
 impl InProgressDoc {
@@ -30,31 +36,54 @@ impl InProgressDoc {
             pdf: PdfFile::new(),
             page_ids: Vec::new(),
             extended_graphics_states: Vec::new(),
-            font_resources: Vec::new(),
             alpha_states: HashMap::new(),
             fonts: HashMap::new(),
+            embedded_fonts: Vec::new(),
         }
     }
 
     pub(crate) fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut font_objects = self.pdf.continuation();
+        let font_resources = self
+            .embedded_fonts
+            .iter()
+            .map(|font| {
+                let id = font.write(&mut font_objects).map_err(|error| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", error))
+                })?;
+                Ok((font.pdf_key.clone().into_bytes(), Object::from(id)))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        // The file header says PDF 1.5, but embedding OpenType font files requires 1.6.
+        // The catalog’s `Version` entry overrides the header.
+        let embeds_opentype = self
+            .embedded_fonts
+            .iter()
+            .any(|font| font.font.outline_format() == OutlineFormat::Cff);
+        let version_1_6 = key_value_pairs! {
+            "Version" => "1.6",
+        };
         self.pdf.write(w, &BasicObjects {
             page_tree: dictionary! {
                 "Type" => "Pages",
                 "Count" => self.page_ids.len(),
                 "Kids" => &*self.page_ids,
                 "Resources" => dictionary! {
-                    "Font" => Object::DictionaryWithOwnedKeys(&self.font_resources),
+                    "Font" => Object::DictionaryWithOwnedKeys(&font_resources),
                     "ExtGState" => Object::DictionaryWithOwnedKeys(&self.extended_graphics_states),
                 },
             },
-            catalog: dictionary! {
-                "Type" => "Catalog",
-                "Pages" => PAGE_TREE_ID,
+            catalog: Dictionary {
+                prev: Some(&dictionary! {
+                    "Type" => "Catalog",
+                    "Pages" => PAGE_TREE_ID,
+                }),
+                pairs: if embeds_opentype { version_1_6 } else { &[] },
             },
             info: dictionary! {
                 "Producer" => Object::LiteralString(b"Victor <https://github.com/SimonSapin/victor>"),
             },
-        })
+        }, &font_objects)
     }
 }
 
@@ -103,7 +132,7 @@ impl<'a> Drop for InProgressPage<'a> {
         let content_id = self
             .doc
             .pdf
-            .add_stream(dictionary!{}, self.operations.as_slice().into());
+            .add_stream(dictionary! {}, self.operations.as_slice().into());
         let page_id = self.doc.pdf.add_dictionary(dictionary! {
             "Type" => "Page",
             "Parent" => PAGE_TREE_ID,
@@ -119,6 +148,21 @@ impl<'a> Drop for InProgressPage<'a> {
     }
 }
 
+/// Glyphs shown with one text-showing operator, on the same baseline
+/// or on the same vertical line
+struct GlyphRun {
+    /// Relative to the origin of the text, in ems
+    origin: Vector<Em>,
+
+    /// Two bytes per glyph, split where the position of the next glyph is adjusted,
+    /// for example by kerning.
+    glyph_codes: Vec<Vec<u8>>,
+
+    /// The adjustment after each item of `glyph_codes` except the last,
+    /// along the direction of the text
+    adjustments: Vec<Length<Em>>,
+}
+
 struct GraphicsState {
     non_stroking_color_rgb: (f32, f32, f32),
     alpha: f32,
@@ -191,29 +235,128 @@ impl<'a> InProgressPage<'a> {
             ref font_size,
             ref origin,
         } = *text;
-        let font_key = self.add_font(&segment.font)?;
+        if segment.glyphs.is_empty() {
+            return Ok(())
+        }
+        let vertical = segment.direction == Direction::TopToBottom;
+        let font = &segment.font;
+        let font_index = self.add_font(font, vertical)?;
+        let embedded_font = &mut self.doc.embedded_fonts[font_index];
+        for (i, &GlyphId(id)) in segment.glyphs.iter().enumerate() {
+            if let btree_map::Entry::Vacant(entry) = embedded_font.glyph_widths.entry(id) {
+                let width = font.glyph_width(GlyphId(id))?;
+                entry.insert(width);
+                let cid = match font.outline_format() {
+                    OutlineFormat::TrueType => id,
+                    OutlineFormat::Cff => font.cff_cid(GlyphId(id))?,
+                };
+                embedded_font.cids.insert(id, cid);
+                if vertical {
+                    let metrics = VerticalMetrics {
+                        advance: font.glyph_vertical_advance(GlyphId(id))?,
+                        origin: Vector::from_lengths(
+                            width / 2.,
+                            font.glyph_vertical_origin(GlyphId(id))?,
+                        ),
+                    };
+                    embedded_font.vertical_metrics.insert(id, metrics);
+                }
+            }
+            // All characters missing from the font use the same glyph, skip it.
+            if id == 0 {
+                continue
+            }
+            if let Some(text) = segment.cluster_text(i) {
+                embedded_font
+                    .glyph_text
+                    .entry(id)
+                    .or_insert_with(|| text.to_owned());
+            }
+        }
+        let font_key = embedded_font.pdf_key.clone();
         // flip the Y axis in to compensate the same flip at the page level.
         let x_scale = font_size.0;
         let y_scale = -font_size.0;
-        let mut glyph_codes = Vec::with_capacity(segment.glyphs.len() * 2);
-        for &GlyphId(id) in &segment.glyphs {
-            // Big-endian
-            glyph_codes.push((id >> 8) as u8);
-            glyph_codes.push(id as u8);
+        // Glyphs are shown in runs that each start at a given offset from the origin.
+        // A glyph drawn above or below the baseline, such as some combining marks,
+        // is in a run of its own.
+        // In vertical text, the origin is the vertical origin of the first glyph
+        // and glyphs are offset to the left or right of the line instead.
+        let mut runs: Vec<GlyphRun> = Vec::new();
+        let mut pen = Length::new(0.);
+        let mut continue_run = false;
+        let mut previous_adjustment = Length::new(0.);
+        for (&GlyphId(id), position) in segment.glyphs.iter().zip(&segment.positions) {
+            // Offsets along the line, positive in the direction of the text
+            let (inline_offset, has_cross_offset) = if vertical {
+                (-position.offset.y_typed(), position.offset.x != 0.)
+            } else {
+                (position.offset.x_typed(), position.offset.y != 0.)
+            };
+            if continue_run && !has_cross_offset {
+                let run = runs.last_mut().unwrap();
+                let adjustment = previous_adjustment + inline_offset;
+                if adjustment != Length::new(0.) {
+                    run.adjustments.push(adjustment);
+                    run.glyph_codes.push(Vec::new())
+                }
+            } else {
+                let pen_position = if vertical {
+                    Vector::from_lengths(Length::new(0.), -pen)
+                } else {
+                    Vector::from_lengths(pen, Length::new(0.))
+                };
+                runs.push(GlyphRun {
+                    origin: pen_position + position.offset,
+                    glyph_codes: vec![Vec::with_capacity(segment.glyphs.len() * 2)],
+                    adjustments: Vec::new(),
+                })
+            }
+            let codes = runs.last_mut().unwrap().glyph_codes.last_mut().unwrap();
+            // The CID, big-endian
+            let cid = embedded_font.cids[&id];
+            codes.push((cid >> 8) as u8);
+            codes.push(cid as u8);
+            // After showing a glyph, the current position moves by the glyph’s default width,
+            // or down by its default advance height in vertical writing mode.
+            let default_advance = if vertical {
+                font.glyph_vertical_advance(GlyphId(id))?
+            } else {
+                font.glyph_width(GlyphId(id))?
+            };
+            previous_adjustment = position.advance - default_advance - inline_offset;
+            pen += position.advance;
+            continue_run = !has_cross_offset
         }
         op!(self, BEGIN_TEXT);
         op!(self, TEXT_FONT_AND_SIZE, &*font_key, 1);
-        op!(
-            self,
-            TEXT_MATRIX,
-            x_scale,
-            0,
-            0,
-            y_scale,
-            origin.x,
-            origin.y
-        );
-        op!(self, SHOW_TEXT, Object::HexString(&glyph_codes));
+        for run in &runs {
+            op!(
+                self,
+                TEXT_MATRIX,
+                x_scale,
+                0,
+                0,
+                y_scale,
+                origin.x + run.origin.x * x_scale,
+                origin.y + run.origin.y * y_scale
+            );
+            if run.adjustments.is_empty() {
+                op!(self, SHOW_TEXT, Object::HexString(&run.glyph_codes[0]));
+            } else {
+                let mut array = Vec::with_capacity(run.glyph_codes.len() + run.adjustments.len());
+                for (i, codes) in run.glyph_codes.iter().enumerate() {
+                    array.push(Object::HexString(codes));
+                    if let Some(&adjustment) = run.adjustments.get(i) {
+                        // In thousandths of text space, subtracted from the current position.
+                        // In vertical writing mode this is its Y coordinate, which points up.
+                        let adjustment = if vertical { adjustment } else { -adjustment };
+                        array.push(Object::from(adjustment))
+                    }
+                }
+                op!(self, SHOW_TEXT_WITH_POSITIONING, &*array);
+            }
+        }
         op!(self, END_TEXT);
 
         // https://www.adobe.com/content/dam/acom/en/devnet/pdf/PDF32000_2008.pdf#G8.1910927
@@ -224,13 +367,10 @@ impl<'a> InProgressPage<'a> {
         // Td op: place current text position (origin of text space) in user space
         // glyph displacement vector translates text space when showing a glyph, based on font metrics
         // writing mode: 0 is horizontal, 1 is vertical
-        //      vertical: no “vhea” and “vmtx” tables, DW2 and W2 entries in a CIDFont dict
+        //      vertical: Identity-V encoding, DW2 and W2 entries in a CIDFont dict
+        //      from the “vmtx” and “VORG” tables
         // more than 1 byte per glyph ID: composite fonts
         // Embedded font stream dictionary: /Length1 decoded TrueType size
-        // TrueType tables required:
-        // “head”, “hhea”, “loca”, “maxp”, “cvt”, “prep”, “glyf”, “hmtx”, and “fpgm”
-        // Subset: prefix /BaseFont name with 6 upper case letters
-        //   (identifying this subset) and "+"
 
         // Probably won’t use:
         // Word spacing = character spacing for ASCII space 0x20 single-byte code
@@ -265,22 +405,145 @@ impl<'a> InProgressPage<'a> {
         }
     }
 
-    fn add_font(&mut self, font: &Arc<Font>) -> Result<String, FontError> {
-        let next_id = self.doc.fonts.len();
-        let vacant_entry = match self.doc.fonts.entry(ByAddress(font.clone())) {
-            Entry::Occupied(entry) => return Ok(entry.get().clone()),
+    fn add_font(&mut self, font: &Arc<Font>, vertical: bool) -> Result<usize, FontError> {
+        let index = self.doc.embedded_fonts.len();
+        let vacant_entry = match self.doc.fonts.entry((ByAddress(font.clone()), vertical)) {
+            Entry::Occupied(entry) => return Ok(*entry.get()),
             Entry::Vacant(entry) => entry,
         };
-        let font_bytes = font.bytes();
-        let truetype_id = self.doc.pdf.add_stream(
-            dictionary! {
-                "Length1" => font_bytes.len(),
-            },
-            font_bytes.into(),
-        );
-        let font_descriptor_id = self.doc.pdf.add_dictionary(dictionary! {
+        let mut code_points = Vec::new();
+        font.each_code_point(|ch, GlyphId(glyph_id)| code_points.push((glyph_id, ch)))?;
+        self.doc.embedded_fonts.push(EmbeddedFont {
+            font: font.clone(),
+            pdf_key: format!("f{}", index),
+            vertical,
+            code_points,
+            glyph_widths: BTreeMap::new(),
+            cids: BTreeMap::new(),
+            vertical_metrics: BTreeMap::new(),
+            glyph_text: BTreeMap::new(),
+        });
+        vacant_entry.insert(index);
+        Ok(index)
+    }
+}
+
+struct EmbeddedFont {
+    font: Arc<Font>,
+    pdf_key: String,
+
+    /// Whether this font is used in vertical writing mode
+    vertical: bool,
+
+    /// Glyph IDs and code points from the font’s character map
+    code_points: Vec<(u16, char)>,
+
+    /// The advance width of each glyph shown with this font.
+    /// These glyphs are kept when subsetting the font.
+    glyph_widths: BTreeMap<u16, Length<Em>>,
+
+    /// The CID of the same glyphs, which is also their character code.
+    /// It is the glyph ID except in CID-keyed CFF fonts, whose charset maps one to the other.
+    cids: BTreeMap<u16, u16>,
+
+    /// For vertical writing mode, the metrics of the same glyphs as `glyph_widths`
+    vertical_metrics: BTreeMap<u16, VerticalMetrics>,
+
+    /// The source text of glyphs shown with this font, indexed by glyph ID.
+    /// In the `ToUnicode` map this takes precedence over `code_points`,
+    /// and maps ligatures to multiple characters.
+    glyph_text: BTreeMap<u16, String>,
+}
+
+/// How a glyph is positioned in vertical writing mode
+#[derive(Copy, Clone, PartialEq)]
+struct VerticalMetrics {
+    /// How much the current position moves down after the glyph
+    advance: Length<Em>,
+
+    /// From the glyph’s horizontal origin to its vertical origin
+    origin: Vector<Em>,
+}
+
+/// An item of the `/W` or `/W2` array of a CIDFont
+enum GlyphMetrics<T> {
+    /// The first and last CIDs of a range of glyphs with the same metrics
+    Range(u16, u16, T),
+
+    /// The first and last CIDs of consecutive glyphs, and their metrics
+    List(u16, u16, Vec<Object<'static>>),
+}
+
+/// Glyph metrics written as numbers in `/W` or `/W2`
+trait ToNumbers: Copy + PartialEq {
+    fn push_numbers(self, array: &mut Vec<Object>);
+}
+
+impl ToNumbers for Length<Em> {
+    fn push_numbers(self, array: &mut Vec<Object>) {
+        array.push(Object::from(self))
+    }
+}
+
+impl ToNumbers for VerticalMetrics {
+    /// The vertical displacement, which is negative since the Y axis points up,
+    /// and the position vector
+    fn push_numbers(self, array: &mut Vec<Object>) {
+        array.push(Object::from(-self.advance));
+        array.push(Object::from(self.origin.x_typed()));
+        array.push(Object::from(self.origin.y_typed()))
+    }
+}
+
+impl EmbeddedFont {
+    /// Glyphs can be shown until the document is written,
+    /// so this is when the font’s objects are created.
+    fn write(&self, pdf: &mut PdfFile) -> Result<IndirectObjectId, FontError> {
+        let font = &*self.font;
+        let glyphs = self.glyph_widths.keys().map(|&id| GlyphId(id));
+        let subset = match font.outline_format() {
+            // Embedded whole, an instance of a variable font would show the default instance.
+            OutlineFormat::TrueType if font.is_variation_instance() => Some(font.subset(glyphs)?),
+            OutlineFormat::TrueType => font.subset(glyphs).ok(),
+            // FIXME: subset CFF outlines too
+            OutlineFormat::Cff => None,
+        };
+        let (font_bytes, name) = match subset {
+            Some(subset) => {
+                let name = format!("{}+{}", self.subset_tag(), font.postscript_name());
+                (Cow::Owned(subset), Cow::Owned(name))
+            }
+            // Fonts that can’t be subset are embedded whole,
+            // or only their face for a collection.
+            None => (font.face_bytes()?, Cow::Borrowed(font.postscript_name())),
+        };
+        // https://www.adobe.com/content/dam/acom/en/devnet/pdf/PDF32000_2008.pdf#G8.1910662
+        let (font_file_key, font_file_id, cid_font_subtype) = match font.outline_format() {
+            OutlineFormat::TrueType => (
+                "FontFile2",
+                pdf.add_stream(
+                    dictionary! {
+                        "Length1" => font_bytes.len(),
+                    },
+                    font_bytes,
+                ),
+                "CIDFontType2",
+            ),
+            // The font program selects glyphs by CID through its charset.
+            OutlineFormat::Cff => (
+                "FontFile3",
+                pdf.add_stream(
+                    dictionary! {
+                        "Subtype" => "OpenType",
+                    },
+                    font_bytes,
+                ),
+                "CIDFontType0",
+            ),
+        };
+        let font_descriptor_id = pdf.add_dictionary(dictionary! {
             "Type" => "FontDescriptor",
-            "FontName" => font.postscript_name(),
+            "FontName" => &*name,
             "FontBBox" => array![
                 font.min_x(),
                 font.min_y(),
@@ -289,7 +552,7 @@ impl<'a> InProgressPage<'a> {
             ],
             "Ascent" => font.ascender(),
             "Descent" => font.descender(),
-            "FontFile2" => truetype_id,
+            font_file_key => font_file_id,
 
             // These seem somewhat arbitrary, they’re copied from cairo:
             "ItalicAngle" => 0,
@@ -312,34 +575,30 @@ impl<'a> InProgressPage<'a> {
             endcodespacerange\n\
         "
         .to_vec();
-        {
-            let mut write_bfchar = |chars: &[char], glyph_ids: &[u16]| {
-                write!(to_unicode_cmap, "{} beginbfchar\n", chars.len()).unwrap();
-                for (ch, glyph_id) in chars.iter().zip(glyph_ids) {
-                    write!(to_unicode_cmap, "<{:04x}> <", glyph_id).unwrap();
-                    for code_unit in ch.encode_utf16(&mut [0, 0]) {
-                        write!(to_unicode_cmap, "{:04x}", code_unit).unwrap()
-                    }
-                    to_unicode_cmap.extend(b">\n");
-                }
-                to_unicode_cmap.extend(b"endbfchar\n");
-            };
-            // Max 100 entries per beginbfchar operator
-            let mut chars = ['\0'; 100];
-            let mut glyph_ids = [0_u16; 100];
-            let mut i = 0;
-            font.each_code_point(|ch, GlyphId(glyph_id)| {
-                if i >= 100 {
-                    write_bfchar(&chars, &glyph_ids);
-                    i = 0
+        let mut entries: Vec<(u16, String)> = self
+            .code_points
+            .iter()
+            .filter(|&(glyph_id, _)| {
+                self.glyph_widths.contains_key(glyph_id) && !self.glyph_text.contains_key(glyph_id)
+            })
+            .map(|&(glyph_id, ch)| (self.cids[&glyph_id], ch.to_string()))
+            .collect();
+        entries.extend(
+            self.glyph_text
+                .iter()
+                .map(|(glyph_id, text)| (self.cids[glyph_id], text.clone())),
+        );
+        // Max 100 entries per beginbfchar operator
+        for chunk in entries.chunks(100) {
+            write!(to_unicode_cmap, "{} beginbfchar\n", chunk.len()).unwrap();
+            for (cid, text) in chunk {
+                write!(to_unicode_cmap, "<{:04x}> <", cid).unwrap();
+                for code_unit in text.encode_utf16() {
+                    write!(to_unicode_cmap, "{:04x}", code_unit).unwrap()
                 }
-                chars[i] = ch;
-                glyph_ids[i] = glyph_id;
-                i += 1;
-            })?;
-            if i > 0 {
-                write_bfchar(&chars[..i], &glyph_ids[..i])
+                to_unicode_cmap.extend(b">\n");
             }
+            to_unicode_cmap.extend(b"endbfchar\n");
         }
         to_unicode_cmap.extend(
             b"\
@@ -350,51 +609,162 @@ impl<'a> InProgressPage<'a> {
         "
             .as_ref(),
         );
-        let to_unicode_id = self
-            .doc
-            .pdf
-            .add_stream(dictionary!{}, to_unicode_cmap.into());
+        let (default_width, glyph_widths) = compact_metrics(&self.by_cid(&self.glyph_widths));
+        let default_width = default_width.unwrap_or(Length::new(0.));
+        let widths = metrics_array(&glyph_widths);
+        let (default_vertical_metrics, glyph_vertical_metrics) =
+            compact_metrics(&self.by_cid(&self.vertical_metrics));
+        let default_vertical_metrics = default_vertical_metrics.map_or(Vec::new(), |metrics| {
+            vec![
+                Object::from(metrics.origin.y_typed()),
+                Object::from(-metrics.advance),
+            ]
+        });
+        let vertical_metrics = metrics_array(&glyph_vertical_metrics);
+        let to_unicode_id = pdf.add_stream(dictionary! {}, to_unicode_cmap.into());
+        let cid_font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => cid_font_subtype,
+            "BaseFont" => &*name,
+            "CIDSystemInfo" => dictionary! {
+                "Registry" => Object::LiteralString(b"Adobe"),
+                "Ordering" => Object::LiteralString(b"Identity"),
+                "Supplement" => 0,
+            },
+            "FontDescriptor" => font_descriptor_id,
+            "DW" => default_width,
+            "W" => &*widths,
+        };
+        let vertical_pairs = key_value_pairs! {
+            "DW2" => &*default_vertical_metrics,
+            "W2" => &*vertical_metrics,
+        };
         // Type 0 Font Dictionaries
         // https://www.adobe.com/content/dam/acom/en/devnet/pdf/PDF32000_2008.pdf#G8.1859105
 
         // FIXME: revert to direct object
-        let mut glyph_widths = Vec::with_capacity(font.glyph_count() as usize);
-        for i in 0..font.glyph_count() {
-            glyph_widths.push(Object::from(font.glyph_width(GlyphId(i))?));
-        }
-        let font_dict_id = self.doc.pdf.add_dictionary(dictionary! {
+        Ok(pdf.add_dictionary(dictionary! {
             "Type" => "Font",
             "Subtype" => "Type0",
-            "BaseFont" => font.postscript_name(),
+            "BaseFont" => &*name,
             "ToUnicode" => to_unicode_id,
 
-            // 2-bytes big-endian char codes, horizontal writing mode:
-            "Encoding" => "Identity-H",
-
-            "DescendantFonts" => array![dictionary! {
-                "Type" => "Font",
-                "Subtype" => "CIDFontType2",
-                "BaseFont" => font.postscript_name(),
-                "CIDSystemInfo" => dictionary! {
-                    "Registry" => Object::LiteralString(b"Adobe"),
-                    "Ordering" => Object::LiteralString(b"Identity"),
-                    "Supplement" => 0,
-                },
-                "FontDescriptor" => font_descriptor_id,
-                "W" => array![
-                    0,  // start CID
-                    &*glyph_widths,
-                ],
+            // 2-bytes big-endian char codes, horizontal or vertical writing mode:
+            "Encoding" => if self.vertical { "Identity-V" } else { "Identity-H" },
+
+            "DescendantFonts" => array![Dictionary {
+                prev: Some(&cid_font),
+                pairs: if self.vertical { vertical_pairs } else { &[] },
             }],
-        });
+        }))
+    }
 
-        let pdf_key = format!("f{}", next_id);
-        self.doc
-            .font_resources
-            .push((pdf_key.clone().into_bytes(), font_dict_id.into()));
-        vacant_entry.insert(pdf_key.clone());
-        Ok(pdf_key)
+    /// Metrics indexed by CID instead of glyph ID
+    fn by_cid<T: Copy>(&self, metrics: &BTreeMap<u16, T>) -> BTreeMap<u16, T> {
+        metrics
+            .iter()
+            .map(|(glyph_id, &value)| (self.cids[glyph_id], value))
+            .collect()
+    }
+
+    /// Six upper-case letters that identify the subset of the font, based on its glyphs
+    /// and for a variable font on the axis values of its instance
+    fn subset_tag(&self) -> String {
+        // FNV-1a hash
+        let mut hash: u32 = 0x_811C_9DC5;
+        let mut add = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash = (hash ^ u32::from(byte)).wrapping_mul(0x_0100_0193)
+            }
+        };
+        for &id in self.glyph_widths.keys() {
+            add(&id.to_be_bytes())
+        }
+        for &coordinate in self.font.variation_coordinates() {
+            add(&coordinate.to_bits().to_be_bytes())
+        }
+        (0..6)
+            .map(|_| {
+                let letter = b'A' + (hash % 26) as u8;
+                hash /= 26;
+                letter as char
+            })
+            .collect()
+    }
+}
+
+/// The most common metrics of glyphs shown with a font, as the CIDFont’s default,
+/// and the other metrics for `/W` or `/W2`.
+/// Consecutive glyphs with the same metrics are a range.
+///
+/// <https://www.adobe.com/content/dam/acom/en/devnet/pdf/PDF32000_2008.pdf#G8.1850211>
+fn compact_metrics<T: ToNumbers>(metrics: &BTreeMap<u16, T>) -> (Option<T>, Vec<GlyphMetrics<T>>) {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    for &value in metrics.values() {
+        match counts.iter_mut().find(|&&mut (v, _)| v == value) {
+            Some(&mut (_, ref mut count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    let default = counts
+        .iter()
+        .max_by_key(|&&(_, count)| count)
+        .map(|&(value, _)| value);
+
+    // Runs of consecutive CIDs with the same metrics, other than the default
+    let mut runs: Vec<(u16, u16, T)> = Vec::new();
+    for (&id, &value) in metrics {
+        if Some(value) == default {
+            continue
+        }
+        match runs.last_mut() {
+            Some(&mut (_, ref mut last, run_value))
+                if u32::from(*last) + 1 == u32::from(id) && run_value == value =>
+            {
+                *last = id
+            }
+            _ => runs.push((id, id, value)),
+        }
+    }
+    let mut items = Vec::new();
+    for (first, last, value) in runs {
+        if first != last {
+            items.push(GlyphMetrics::Range(first, last, value));
+            continue
+        }
+        // A single glyph extends the list of the glyph just before it, if any.
+        if let Some(&mut GlyphMetrics::List(_, ref mut list_last, ref mut list)) = items.last_mut()
+        {
+            if u32::from(*list_last) + 1 == u32::from(first) {
+                value.push_numbers(list);
+                *list_last = first;
+                continue
+            }
+        }
+        let mut list = Vec::new();
+        value.push_numbers(&mut list);
+        items.push(GlyphMetrics::List(first, first, list))
+    }
+    (default, items)
+}
+
+/// The `/W` or `/W2` array of a CIDFont
+fn metrics_array<T: ToNumbers>(items: &[GlyphMetrics<T>]) -> Vec<Object> {
+    let mut array = Vec::new();
+    for item in items {
+        match *item {
+            GlyphMetrics::Range(first, last, value) => {
+                array.push(Object::from(usize::from(first)));
+                array.push(Object::from(usize::from(last)));
+                value.push_numbers(&mut array)
+            }
+            GlyphMetrics::List(first, _, ref list) => {
+                array.push(Object::from(usize::from(first)));
+                array.push(Object::from(&**list))
+            }
+        }
     }
+    array
 }
 
 macro_rules! operators {
@@ -429,4 +799,5 @@ operators! {
     TEXT_FONT_AND_SIZE = "Tf",
     TEXT_MATRIX = "Tm",
     SHOW_TEXT = "Tj",
+    SHOW_TEXT_WITH_POSITIONING = "TJ",
 }
@@ -37,10 +37,12 @@ impl Document {
     }
 
     /// Encode this document to PDF and return a vector of bytes
+    ///
+    /// Panics if a font cannot be embedded, see `write_to_pdf`.
     pub fn write_to_pdf_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
-        // Unwrap io::Result because <io::Write for Vec<u8>> never emits errors.
-        self.write_to_pdf(&mut bytes).unwrap();
+        // <io::Write for Vec<u8>> never emits errors, so an error can only come from a font.
+        self.write_to_pdf(&mut bytes).expect("failed to embed a font");
         bytes
     }
 
@@ -50,6 +52,9 @@ impl Document {
     /// If a stream is backed by costly system calls (such as `File` or `TcpStream`),
     /// this method will likely perform better with that stream wrapped in `BufWriter`.
     ///
+    /// A font that cannot be embedded, for example with a malformed table directory,
+    /// causes an error of kind `InvalidData`.
+    ///
     /// See also the `write_to_pdf_file` and `write_to_pdf_bytes` methods.
     pub fn write_to_pdf<W: Write>(&self, stream: &mut W) -> Result<(), io::Error> {
         Ok(self.in_progress.write(stream)?)
@@ -55,6 +55,17 @@ impl PdfFile {
         self.add_indirect_object(obj)
     }
 
+    /// Return an empty file whose objects have IDs that follow those of this one.
+    ///
+    /// This is for objects that can only be created when writing this file,
+    /// see the `deferred` parameter of `write`.
+    pub fn continuation(&self) -> Self {
+        PdfFile {
+            indirect_objects: Vec::new(),
+            next_id: self.next_id,
+        }
+    }
+
     pub fn add_indirect_object(&mut self, serialized_contents: Vec<u8>) -> IndirectObjectId {
         self.indirect_objects.push(serialized_contents);
         let id = self.next_id;
@@ -62,9 +73,22 @@ impl PdfFile {
         id
     }
 
-    pub fn write<W: Write>(&self, w: &mut W, basic_objects: &BasicObjects) -> io::Result<()> {
-        let total_indirect_object_count =
-            (FIRST_AVAILABLE_ID.0 - FIRST_ID.0) as usize + self.indirect_objects.len();
+    /// Write this file, followed by the objects of `deferred`,
+    /// which must have been created by `self.continuation()`.
+    pub fn write<W: Write>(
+        &self,
+        w: &mut W,
+        basic_objects: &BasicObjects,
+        deferred: &PdfFile,
+    ) -> io::Result<()> {
+        assert_eq!(
+            self.next_id.0 as usize + deferred.indirect_objects.len(),
+            deferred.next_id.0 as usize,
+            "deferred objects are not a continuation of this file"
+        );
+        let total_indirect_object_count = (FIRST_AVAILABLE_ID.0 - FIRST_ID.0) as usize
+            + self.indirect_objects.len()
+            + deferred.indirect_objects.len();
         let mut indirect_object_offsets = Vec::with_capacity(total_indirect_object_count);
         let startxref;
         {
@@ -92,7 +116,11 @@ impl PdfFile {
                 w.write_all(b"\nendobj\n")?;
             }
             assert_eq!(next_object_id, FIRST_AVAILABLE_ID);
-            for contents in &self.indirect_objects {
+            for contents in self
+                .indirect_objects
+                .iter()
+                .chain(&deferred.indirect_objects)
+            {
                 let object_id = next_object_id;
                 next_object_id.0 += 1;
 
@@ -7,6 +7,7 @@ pub use euclid::point2 as point;
 pub use euclid::rect;
 pub type Length<U> = euclid::Length<f32, U>;
 pub type Point<U> = euclid::TypedPoint2D<f32, U>;
+pub type Vector<U> = euclid::TypedVector2D<f32, U>;
 pub type Size<U> = euclid::TypedSize2D<f32, U>;
 pub type Rect<U> = euclid::TypedRect<f32, U>;
 pub type SideOffsets<U> = euclid::TypedSideOffsets2D<f32, U>;
@@ -6,4 +6,6 @@ mod style_set;
 pub(crate) mod values;
 
 pub(crate) use self::properties::ComputedValues;
-pub(crate) use self::style_set::{cascade, StyleSet, StyleSetBuilder};
+pub(crate) use self::rules::PageMarginBox;
+pub(crate) use self::style_set::{cascade, cascade_page, Device, PageStyles};
+pub(crate) use self::style_set::{StyleSet, StyleSetBuilder};
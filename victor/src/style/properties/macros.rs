@@ -1,5 +1,5 @@
 use crate::style::errors::PropertyParseError;
-use crate::style::values::{CssWideKeyword, Parse, ToComputedValue};
+use crate::style::values::{CascadeContext, CssWideKeyword, Parse, ToComputedValue};
 use cssparser::Parser;
 use std::rc::Rc;
 
@@ -41,7 +41,7 @@ macro_rules! properties {
                 &self,
                 keyword: CssWideKeyword,
                 computed: &mut ComputedValues,
-                inherited: &ComputedValues,
+                context: &CascadeContext,
             ) -> () {
                 match *self {
                     $($(
@@ -59,7 +59,7 @@ macro_rules! properties {
                             if is_initial {
                                 $initial_value
                             } else {
-                                inherited.$struct_name.$ident.clone()
+                                context.inherited.$struct_name.$ident.clone()
                             };
                         }
                     )+)+
@@ -80,17 +80,30 @@ macro_rules! properties {
             pub fn cascade_into(
                 &self,
                 computed: &mut ComputedValues,
-                inherited: &ComputedValues,
+                context: &CascadeContext,
             ) -> () {
                 match *self {
                     $($(
                         LonghandDeclaration::$ident(ref value) => {
                             Rc::make_mut(&mut computed.$struct_name).$ident =
-                                ToComputedValue::to_computed(value)
+                                ToComputedValue::to_computed(value, context)
                         }
                     )+)+
                     LonghandDeclaration::CssWide(ref longhand, ref keyword) => {
-                        longhand.cascade_css_wide_keyword_into(*keyword, computed, inherited)
+                        longhand.cascade_css_wide_keyword_into(*keyword, computed, context)
+                    }
+                }
+            }
+
+            pub fn id(&self) -> LonghandId {
+                match *self {
+                    $($(
+                        LonghandDeclaration::$ident(ref _value) => {
+                            LonghandId::$ident
+                        }
+                    )+)+
+                    LonghandDeclaration::CssWide(ref longhand, ref _keyword) => {
+                        *longhand
                     }
                 }
             }
@@ -1,5 +1,17 @@
+use crate::primitives::RGBA;
+use crate::style::values::border::{Border, BorderSide, BorderStyle, LineWidth};
+use crate::style::values::color::{Color, ForegroundColor};
+use crate::style::values::content::Content;
+use crate::style::values::font::{
+    FontFamily, FontFeatureSettings, FontSize, FontStretch, FontStyle, FontVariantLigatures,
+    FontVariationSettings, FontWeight,
+};
 use crate::style::values::generic::FourSides;
-use crate::style::values::length::{Length, PxLength};
+use crate::style::values::length::{
+    LengthOrPercentageOrAuto, LengthPercentage, LengthPercentageOrAuto, LengthPercentageOrNone,
+    NonNegativeLengthOrPercentage, NonNegativeLengthOrPercentageOrAuto,
+    NonNegativeLengthOrPercentageOrNone, Percentage, PxLength,
+};
 use crate::style::values::*;
 
 // `include` rather than `mod` so that macro definition and use are in the same scope,
@@ -12,16 +24,140 @@ properties! {
     inherited struct font {
         font_size {
             "font-size",
-            Length,
+            FontSize,
             initial = PxLength::new(16.)
         }
+        font_family { "font-family", FontFamily, initial = FontFamily::sans_serif() }
+        font_weight { "font-weight", FontWeight, initial = 400 }
+        font_style { "font-style", FontStyle, initial = FontStyle::Normal }
+        font_stretch {
+            "font-stretch",
+            FontStretch,
+            initial = FontStretch(Percentage(1.))
+        }
+        font_feature_settings {
+            "font-feature-settings",
+            FontFeatureSettings,
+            initial = FontFeatureSettings::normal()
+        }
+        font_variant_ligatures {
+            "font-variant-ligatures",
+            FontVariantLigatures,
+            initial = FontVariantLigatures::default()
+        }
+        font_variation_settings {
+            "font-variation-settings",
+            FontVariationSettings,
+            initial = FontVariationSettings::normal()
+        }
+    }
+
+    inherited struct writing_modes {
+        direction { "direction", Direction, initial = Direction::Ltr }
+        writing_mode { "writing-mode", WritingMode, initial = WritingMode::HorizontalTb }
+    }
+
+    inherited struct color {
+        color { "color", ForegroundColor, initial = RGBA(0., 0., 0., 1.) }
+    }
+
+    reset struct background {
+        background_color { "background-color", Color, initial = Color::transparent() }
     }
 
     reset struct margin {
-        margin_top { "margin-top", Length, initial = PxLength::new(0.) }
-        margin_left { "margin-left", Length, initial = PxLength::new(0.) }
-        margin_bottom { "margin-bottom", Length, initial = PxLength::new(0.) }
-        margin_right { "margin-right", Length, initial = PxLength::new(0.) }
+        margin_top {
+            "margin-top",
+            LengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::zero()
+        }
+        margin_left {
+            "margin-left",
+            LengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::zero()
+        }
+        margin_bottom {
+            "margin-bottom",
+            LengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::zero()
+        }
+        margin_right {
+            "margin-right",
+            LengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::zero()
+        }
+    }
+
+    reset struct padding {
+        padding_top {
+            "padding-top",
+            NonNegativeLengthOrPercentage,
+            initial = LengthPercentage::zero()
+        }
+        padding_right {
+            "padding-right",
+            NonNegativeLengthOrPercentage,
+            initial = LengthPercentage::zero()
+        }
+        padding_bottom {
+            "padding-bottom",
+            NonNegativeLengthOrPercentage,
+            initial = LengthPercentage::zero()
+        }
+        padding_left {
+            "padding-left",
+            NonNegativeLengthOrPercentage,
+            initial = LengthPercentage::zero()
+        }
+    }
+
+    reset struct border {
+        border_top_width { "border-top-width", LineWidth, initial = PxLength::new(3.) }
+        border_right_width { "border-right-width", LineWidth, initial = PxLength::new(3.) }
+        border_bottom_width { "border-bottom-width", LineWidth, initial = PxLength::new(3.) }
+        border_left_width { "border-left-width", LineWidth, initial = PxLength::new(3.) }
+        border_top_style { "border-top-style", BorderStyle, initial = BorderStyle::None }
+        border_right_style { "border-right-style", BorderStyle, initial = BorderStyle::None }
+        border_bottom_style { "border-bottom-style", BorderStyle, initial = BorderStyle::None }
+        border_left_style { "border-left-style", BorderStyle, initial = BorderStyle::None }
+        border_top_color { "border-top-color", Color, initial = Color::current_color() }
+        border_right_color { "border-right-color", Color, initial = Color::current_color() }
+        border_bottom_color { "border-bottom-color", Color, initial = Color::current_color() }
+        border_left_color { "border-left-color", Color, initial = Color::current_color() }
+    }
+
+    reset struct size {
+        width {
+            "width",
+            NonNegativeLengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::Auto
+        }
+        height {
+            "height",
+            NonNegativeLengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::Auto
+        }
+        min_width {
+            "min-width",
+            NonNegativeLengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::Auto
+        }
+        min_height {
+            "min-height",
+            NonNegativeLengthOrPercentageOrAuto,
+            initial = LengthPercentageOrAuto::Auto
+        }
+        max_width {
+            "max-width",
+            NonNegativeLengthOrPercentageOrNone,
+            initial = LengthPercentageOrNone::None
+        }
+        max_height {
+            "max-height",
+            NonNegativeLengthOrPercentageOrNone,
+            initial = LengthPercentageOrNone::None
+        }
+        box_sizing { "box-sizing", BoxSizing, initial = BoxSizing::ContentBox }
     }
 
     reset struct display {
@@ -35,6 +171,20 @@ properties! {
         }
     }
 
+    reset struct fragmentation {
+        break_before { "break-before", BreakBetween, initial = BreakBetween::Auto }
+        break_after { "break-after", BreakBetween, initial = BreakBetween::Auto }
+        break_inside { "break-inside", BreakWithin, initial = BreakWithin::Auto }
+    }
+
+    reset struct content {
+        content { "content", Content, initial = Content::Normal }
+    }
+
+    reset struct text {
+        unicode_bidi { "unicode-bidi", UnicodeBidi, initial = UnicodeBidi::Normal }
+    }
+
     @shorthands {
         "margin" => FourSides {
             top: margin_top,
@@ -42,5 +192,75 @@ properties! {
             bottom: margin_bottom,
             right: margin_right,
         }
+        "padding" => FourSides {
+            top: padding_top,
+            left: padding_left,
+            bottom: padding_bottom,
+            right: padding_right,
+        }
+        "border-width" => FourSides {
+            top: border_top_width,
+            left: border_left_width,
+            bottom: border_bottom_width,
+            right: border_right_width,
+        }
+        "border-style" => FourSides {
+            top: border_top_style,
+            left: border_left_style,
+            bottom: border_bottom_style,
+            right: border_right_style,
+        }
+        "border-color" => FourSides {
+            top: border_top_color,
+            left: border_left_color,
+            bottom: border_bottom_color,
+            right: border_right_color,
+        }
+        "border-top" => BorderSide {
+            width: border_top_width,
+            style: border_top_style,
+            color: border_top_color,
+        }
+        "border-right" => BorderSide {
+            width: border_right_width,
+            style: border_right_style,
+            color: border_right_color,
+        }
+        "border-bottom" => BorderSide {
+            width: border_bottom_width,
+            style: border_bottom_style,
+            color: border_bottom_color,
+        }
+        "border-left" => BorderSide {
+            width: border_left_width,
+            style: border_left_style,
+            color: border_left_color,
+        }
+        "border" => Border {
+            top_width: border_top_width,
+            top_style: border_top_style,
+            top_color: border_top_color,
+            right_width: border_right_width,
+            right_style: border_right_style,
+            right_color: border_right_color,
+            bottom_width: border_bottom_width,
+            bottom_style: border_bottom_style,
+            bottom_color: border_bottom_color,
+            left_width: border_left_width,
+            left_style: border_left_style,
+            left_color: border_left_color,
+        }
+    }
+}
+
+impl LonghandId {
+    /// Whether this property is cascaded before others,
+    /// because their computed values can depend on it.
+    pub fn is_early(self) -> bool {
+        match self {
+            // For `em` units
+            LonghandId::font_size => true,
+            _ => false,
+        }
     }
 }
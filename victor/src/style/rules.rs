@@ -1,9 +1,14 @@
+use crate::fonts::{Font, FontCollection, FontFace};
 use crate::style::errors::{PropertyParseErrorKind, RuleParseErrorKind};
 use crate::style::properties::{property_data_by_name, LonghandDeclaration};
 use crate::style::selectors::{self, SelectorList};
+use crate::style::values::font::{FamilyName, FontFaceSource, FontStretch, FontStyle, FontWeight};
+use crate::style::values::page::PageSize;
 use crate::style::values::{CssWideKeyword, Parse};
-use cssparser::{AtRuleParser, ParseError, Parser, QualifiedRuleParser, SourceLocation};
-use cssparser::{CowRcStr, DeclarationListParser, DeclarationParser};
+use cssparser::{AtRuleParser, AtRuleType, BasicParseErrorKind, CowRcStr};
+use cssparser::{DeclarationListParser, DeclarationParser, ParseError, Parser};
+use cssparser::{QualifiedRuleParser, SourceLocation};
+use std::path::Path;
 use std::rc::Rc;
 
 pub enum CssRule {
@@ -17,6 +22,82 @@ pub enum CssRule {
         // Use `Rc` to enable having multiple references to the `Vec` without cloning it.
         declarations: Rc<Vec<LonghandDeclaration>>,
     },
+    PageRule(PageRule),
+    FontFaceRule(FontFaceRule),
+}
+
+/// <https://drafts.csswg.org/css-page-3/#at-page-rule>
+pub struct PageRule {
+    pub size: Option<PageSize>,
+
+    /// Declarations for the page context, such as page margins
+    pub declarations: Vec<LonghandDeclaration>,
+
+    pub margin_rules: Vec<MarginRule>,
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-face-rule>
+///
+/// Descriptors that are not specified are taken from the font file.
+pub struct FontFaceRule {
+    pub family: String,
+    pub sources: Vec<FontFaceSource>,
+    pub weight: Option<u16>,
+    pub style: Option<FontStyle>,
+    pub stretch: Option<FontStretch>,
+}
+
+/// <https://drafts.csswg.org/css-page-3/#margin-at-rule>
+pub struct MarginRule {
+    pub margin_box: PageMarginBox,
+    pub declarations: Vec<LonghandDeclaration>,
+}
+
+/// <https://drafts.csswg.org/css-page-3/#margin-boxes>
+///
+/// In painting order.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PageMarginBox {
+    TopLeftCorner,
+    TopLeft,
+    TopCenter,
+    TopRight,
+    TopRightCorner,
+    RightTop,
+    RightMiddle,
+    RightBottom,
+    BottomRightCorner,
+    BottomRight,
+    BottomCenter,
+    BottomLeft,
+    BottomLeftCorner,
+    LeftBottom,
+    LeftMiddle,
+    LeftTop,
+}
+
+impl PageMarginBox {
+    fn from_at_rule_name(name: &str) -> Option<Self> {
+        Some(match_ignore_ascii_case!(name,
+            "top-left-corner" => PageMarginBox::TopLeftCorner,
+            "top-left" => PageMarginBox::TopLeft,
+            "top-center" => PageMarginBox::TopCenter,
+            "top-right" => PageMarginBox::TopRight,
+            "top-right-corner" => PageMarginBox::TopRightCorner,
+            "right-top" => PageMarginBox::RightTop,
+            "right-middle" => PageMarginBox::RightMiddle,
+            "right-bottom" => PageMarginBox::RightBottom,
+            "bottom-right-corner" => PageMarginBox::BottomRightCorner,
+            "bottom-right" => PageMarginBox::BottomRight,
+            "bottom-center" => PageMarginBox::BottomCenter,
+            "bottom-left" => PageMarginBox::BottomLeft,
+            "bottom-left-corner" => PageMarginBox::BottomLeftCorner,
+            "left-bottom" => PageMarginBox::LeftBottom,
+            "left-middle" => PageMarginBox::LeftMiddle,
+            "left-top" => PageMarginBox::LeftTop,
+            _ => return None
+        ))
+    }
 }
 
 pub struct RulesParser;
@@ -63,11 +144,105 @@ impl<'i> QualifiedRuleParser<'i> for RulesParser {
     }
 }
 
+pub enum AtRulePrelude {
+    Page,
+    FontFace,
+}
+
 impl<'i> AtRuleParser<'i> for RulesParser {
     type PreludeNoBlock = ();
-    type PreludeBlock = ();
+    type PreludeBlock = AtRulePrelude;
     type AtRule = CssRule;
     type Error = RuleParseErrorKind<'i>;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<AtRuleType<(), AtRulePrelude>, ParseError<'i, Self::Error>> {
+        match_ignore_ascii_case!(&name,
+            "page" => {
+                // FIXME: page selectors like `:first`.
+                // Until then, rules with a non-empty prelude are dropped.
+                parser.expect_exhausted()?;
+                Ok(AtRuleType::WithBlock(AtRulePrelude::Page))
+            },
+            "font-face" => {
+                parser.expect_exhausted()?;
+                Ok(AtRuleType::WithBlock(AtRulePrelude::FontFace))
+            },
+            _ => Err(parser.new_error(BasicParseErrorKind::AtRuleInvalid(name)))
+        )
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: AtRulePrelude,
+        _location: SourceLocation,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<CssRule, ParseError<'i, Self::Error>> {
+        match prelude {
+            AtRulePrelude::Page => {
+                let mut iter = DeclarationListParser::new(
+                    parser,
+                    PageDeclarationParser {
+                        size: None,
+                        longhands: LonghandDeclarationParser {
+                            declarations: Vec::new(),
+                        },
+                        margin_rules: Vec::new(),
+                    },
+                );
+                while let Some(result) = iter.next() {
+                    let previous_len = iter.parser.longhands.declarations.len();
+                    match result {
+                        Ok(()) => {}
+                        Err(_) => {
+                            iter.parser.longhands.declarations.truncate(previous_len);
+                            // FIXME error reporting
+                        }
+                    }
+                }
+                Ok(CssRule::PageRule(PageRule {
+                    size: iter.parser.size,
+                    declarations: iter.parser.longhands.declarations,
+                    margin_rules: iter.parser.margin_rules,
+                }))
+            }
+            AtRulePrelude::FontFace => {
+                let mut iter = DeclarationListParser::new(
+                    parser,
+                    FontFaceDeclarationParser {
+                        family: None,
+                        sources: None,
+                        weight: None,
+                        style: None,
+                        stretch: None,
+                    },
+                );
+                while let Some(result) = iter.next() {
+                    match result {
+                        Ok(()) => {}
+                        Err(_) => {
+                            // FIXME error reporting
+                        }
+                    }
+                }
+                let descriptors = iter.parser;
+                match (descriptors.family, descriptors.sources) {
+                    (Some(family), Some(sources)) => Ok(CssRule::FontFaceRule(FontFaceRule {
+                        family,
+                        sources,
+                        weight: descriptors.weight,
+                        style: descriptors.style,
+                        stretch: descriptors.stretch,
+                    })),
+                    // Both descriptors are required
+                    _ => Err(parser.new_error(BasicParseErrorKind::QualifiedRuleInvalid)),
+                }
+            }
+        }
+    }
 }
 
 pub struct LonghandDeclarationParser {
@@ -105,3 +280,198 @@ impl<'i> AtRuleParser<'i> for LonghandDeclarationParser {
     type AtRule = ();
     type Error = PropertyParseErrorKind<'i>;
 }
+
+/// Parses the `size` descriptor, properties that apply to the page context,
+/// and margin at-rules.
+struct PageDeclarationParser {
+    size: Option<PageSize>,
+    longhands: LonghandDeclarationParser,
+    margin_rules: Vec<MarginRule>,
+}
+
+impl<'i> DeclarationParser<'i> for PageDeclarationParser {
+    type Declaration = ();
+    type Error = PropertyParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<Self::Declaration, ParseError<'i, Self::Error>> {
+        if name.eq_ignore_ascii_case("size") {
+            self.size = Some(PageSize::parse(parser)?);
+            Ok(())
+        } else {
+            self.longhands.parse_value(name, parser)
+        }
+    }
+}
+
+impl<'i> AtRuleParser<'i> for PageDeclarationParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = PageMarginBox;
+    type AtRule = ();
+    type Error = PropertyParseErrorKind<'i>;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<AtRuleType<(), PageMarginBox>, ParseError<'i, Self::Error>> {
+        match PageMarginBox::from_at_rule_name(&name) {
+            Some(margin_box) => {
+                parser.expect_exhausted()?;
+                Ok(AtRuleType::WithBlock(margin_box))
+            }
+            None => Err(parser.new_error(BasicParseErrorKind::AtRuleInvalid(name))),
+        }
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        margin_box: PageMarginBox,
+        _location: SourceLocation,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<(), ParseError<'i, Self::Error>> {
+        let mut iter = DeclarationListParser::new(
+            parser,
+            LonghandDeclarationParser {
+                declarations: Vec::new(),
+            },
+        );
+        while let Some(result) = iter.next() {
+            let previous_len = iter.parser.declarations.len();
+            match result {
+                Ok(()) => {}
+                Err(_) => {
+                    iter.parser.declarations.truncate(previous_len);
+                    // FIXME error reporting
+                }
+            }
+        }
+        self.margin_rules.push(MarginRule {
+            margin_box,
+            declarations: iter.parser.declarations,
+        });
+        Ok(())
+    }
+}
+
+/// Parses the descriptors of `@font-face`
+struct FontFaceDeclarationParser {
+    family: Option<String>,
+    sources: Option<Vec<FontFaceSource>>,
+    weight: Option<u16>,
+    style: Option<FontStyle>,
+    stretch: Option<FontStretch>,
+}
+
+impl<'i> DeclarationParser<'i> for FontFaceDeclarationParser {
+    type Declaration = ();
+    type Error = PropertyParseErrorKind<'i>;
+
+    fn parse_value<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        parser: &mut Parser<'i, 't>,
+    ) -> Result<Self::Declaration, ParseError<'i, Self::Error>> {
+        match_ignore_ascii_case!(&name,
+            "font-family" => match FamilyName::parse(parser)? {
+                FamilyName::Named(family) => self.family = Some(family),
+                FamilyName::Generic(_) => {
+                    return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+                }
+            },
+            "src" => self.sources = Some(parser.parse_comma_separated(FontFaceSource::parse)?),
+            "font-weight" => match FontWeight::parse(parser)? {
+                FontWeight::Absolute(weight) => self.weight = Some(weight),
+                // Relative keywords are only valid for the property
+                FontWeight::Bolder | FontWeight::Lighter => {
+                    return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+                }
+            },
+            "font-style" => self.style = Some(FontStyle::parse(parser)?),
+            "font-stretch" => self.stretch = Some(FontStretch::parse(parser)?),
+            _ => return Err(parser.new_custom_error(PropertyParseErrorKind::UnknownProperty(name)))
+        );
+        parser.expect_exhausted()?;
+        Ok(())
+    }
+}
+
+impl<'i> AtRuleParser<'i> for FontFaceDeclarationParser {
+    type PreludeNoBlock = ();
+    type PreludeBlock = ();
+    type AtRule = ();
+    type Error = PropertyParseErrorKind<'i>;
+}
+
+impl FontFaceRule {
+    /// Load the first source that is available and in a supported format.
+    ///
+    /// Relative URLs are resolved against `base_path`, or the current directory if it is `None`.
+    /// `local()` sources are looked up in `fonts`.
+    pub fn load(&self, base_path: Option<&Path>, fonts: &FontCollection) -> Option<FontFace> {
+        let font = self.sources.iter().find_map(|source| match source {
+            FontFaceSource::Url { url, format_hints } => {
+                let supported = |hint: &String| {
+                    ["truetype", "opentype", "collection", "woff", "woff2"]
+                        .iter()
+                        .any(|format| hint.eq_ignore_ascii_case(format))
+                };
+                if !format_hints.is_empty() && !format_hints.iter().any(supported) {
+                    return None
+                }
+                let path = local_path(url)?;
+                let path = match base_path {
+                    Some(base_path) => base_path.join(path),
+                    None => path.to_owned(),
+                };
+                let bytes = std::fs::read(path).ok()?;
+                Font::parse(bytes).ok()
+            }
+            FontFaceSource::Local(name) => fonts
+                .faces()
+                .iter()
+                .find(|face| {
+                    let font = &face.font;
+                    let full_name = format!("{} {}", font.family_name(), font.subfamily_name());
+                    font.postscript_name() == name || full_name.eq_ignore_ascii_case(name)
+                })
+                .map(|face| face.font.clone()),
+        })?;
+        let mut face = FontFace::new(font);
+        face.family_name = self.family.clone();
+        if let Some(weight) = self.weight {
+            face.weight = weight
+        }
+        if let Some(style) = self.style {
+            face.is_italic = style != FontStyle::Normal
+        }
+        if let Some(FontStretch(stretch)) = self.stretch {
+            face.stretch = stretch.0
+        }
+        Some(face)
+    }
+}
+
+/// Return the file path for a relative URL or a `file:` URL.
+///
+/// FIXME: percent-decoding
+fn local_path(url: &str) -> Option<&Path> {
+    if let Some(colon) = url.find(':') {
+        let scheme = &url[..colon];
+        // Not a drive letter in a Windows path
+        if scheme.len() > 1 {
+            if !scheme.eq_ignore_ascii_case("file") {
+                return None
+            }
+            let path = &url[colon + 1..];
+            return Some(Path::new(
+                path.trim_start_matches("//localhost")
+                    .trim_start_matches("//"),
+            ))
+        }
+    }
+    Some(Path::new(url))
+}
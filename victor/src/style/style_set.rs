@@ -1,7 +1,10 @@
 use crate::dom;
+use crate::primitives::{CssPx, SideOffsets, Size};
 use crate::style::properties::{ComputedValues, LonghandDeclaration};
-use crate::style::rules::{CssRule, RulesParser};
+use crate::style::rules::{CssRule, FontFaceRule, PageMarginBox, PageRule, RulesParser};
 use crate::style::selectors::{self, Selector};
+use crate::style::values::length::{LengthPercentage, LengthPercentageOrAuto, PxLength};
+use crate::style::values::CascadeContext;
 use cssparser::{Parser, ParserInput, RuleListParser};
 use std::rc::Rc;
 
@@ -9,6 +12,8 @@ pub struct StyleSetBuilder(StyleSet);
 
 pub struct StyleSet {
     rules: Vec<(Selector, Rc<Vec<LonghandDeclaration>>)>,
+    page_rules: Vec<PageRule>,
+    font_face_rules: Vec<FontFaceRule>,
 }
 
 // XXX: if we ever replace Rc with Arc for style structs,
@@ -23,7 +28,11 @@ thread_local! {
 
 impl StyleSetBuilder {
     pub fn new() -> Self {
-        StyleSetBuilder(StyleSet { rules: Vec::new() })
+        StyleSetBuilder(StyleSet {
+            rules: Vec::new(),
+            page_rules: Vec::new(),
+            font_face_rules: Vec::new(),
+        })
     }
 
     pub fn add_stylesheet(&mut self, css: &str) {
@@ -39,6 +48,8 @@ impl StyleSetBuilder {
                         self.0.rules.push((selector, declarations.clone()));
                     }
                 }
+                Ok(CssRule::PageRule(rule)) => self.0.page_rules.push(rule),
+                Ok(CssRule::FontFaceRule(rule)) => self.0.font_face_rules.push(rule),
                 Err(_) => {
                     // FIXME: error reporting
                 }
@@ -56,34 +67,174 @@ impl StyleSetBuilder {
 }
 
 impl StyleSet {
-    fn cascade_into(
+    /// In document order
+    pub(crate) fn font_face_rules(&self) -> &[FontFaceRule] {
+        &self.font_face_rules
+    }
+
+    fn push_matching_declarations(
         &self,
         document: &dom::Document,
         node: dom::NodeId,
-        computed: &mut ComputedValues,
-        inherited: &ComputedValues,
+        matching: &mut Vec<Rc<Vec<LonghandDeclaration>>>,
     ) {
         for &(ref selector, ref declarations) in &self.rules {
             if selectors::matches(selector, document, node) {
-                for declaration in declarations.iter() {
-                    declaration.cascade_into(computed, inherited)
-                }
+                matching.push(declarations.clone())
             }
         }
     }
 }
 
+/// The output medium, and document-wide values that relative lengths are resolved against
+pub(crate) struct Device {
+    pub(crate) viewport_size: Size<CssPx>,
+
+    /// The computed `font-size` of the root element,
+    /// or the initial `font-size` while computing the root element’s style.
+    pub(crate) root_font_size: PxLength,
+}
+
+impl Device {
+    pub(crate) fn new(viewport_size: Size<CssPx>) -> Self {
+        Device {
+            viewport_size,
+            root_font_size: ComputedValues::initial().font.font_size,
+        }
+    }
+}
+
 pub(crate) fn cascade(
     author: &StyleSet,
     document: &dom::Document,
     node: dom::NodeId,
     parent_style: Option<&ComputedValues>,
+    device: &Device,
 ) -> Rc<ComputedValues> {
     assert!(document[node].as_element().is_some());
+    let mut matching = Vec::new();
+    USER_AGENT_STYLESHEET.with(|ua| ua.push_matching_declarations(document, node, &mut matching));
+    author.push_matching_declarations(document, node, &mut matching);
+
     let initial = ComputedValues::initial();
     let inherited = parent_style.unwrap_or(&*initial);
     let mut computed = ComputedValues::new_inheriting_from(inherited, &*initial);
-    USER_AGENT_STYLESHEET.with(|ua| ua.cascade_into(document, node, &mut computed, inherited));
-    author.cascade_into(document, node, &mut computed, inherited);
+    let declarations = matching.iter().flat_map(|declarations| declarations.iter());
+    cascade_declarations(declarations, &mut computed, inherited, device);
     Rc::new(computed)
 }
+
+/// Apply declarations in order of increasing precedence,
+/// except that early properties are applied first.
+fn cascade_declarations<'a, I>(
+    declarations: I,
+    computed: &mut ComputedValues,
+    inherited: &ComputedValues,
+    device: &Device,
+) where
+    I: Iterator<Item = &'a LonghandDeclaration> + Clone,
+{
+    let mut context = CascadeContext {
+        inherited,
+        font_size: inherited.font.font_size,
+        root_font_size: device.root_font_size,
+        viewport_size: device.viewport_size,
+    };
+    for declaration in declarations.clone() {
+        if declaration.id().is_early() {
+            declaration.cascade_into(computed, &context)
+        }
+    }
+    context.font_size = computed.font.font_size;
+    for declaration in declarations {
+        if !declaration.id().is_early() {
+            declaration.cascade_into(computed, &context)
+        }
+    }
+}
+
+/// Computed styles for pages
+pub(crate) struct PageStyles {
+    /// The size of page boxes
+    pub(crate) size: Size<CssPx>,
+
+    pub(crate) page_context: Rc<ComputedValues>,
+
+    /// Margin boxes for which at least one margin rule exists, in painting order.
+    pub(crate) margin_boxes: Vec<(PageMarginBox, Rc<ComputedValues>)>,
+}
+
+/// `default_size` and `default_margins` stand in for a user agent `@page` rule.
+///
+/// <https://drafts.csswg.org/css-page-3/#page-model>
+pub(crate) fn cascade_page(
+    author: &StyleSet,
+    device: &Device,
+    default_size: Size<CssPx>,
+    default_margins: SideOffsets<CssPx>,
+) -> PageStyles {
+    let initial = ComputedValues::initial();
+    let mut computed = ComputedValues::new_inheriting_from(&*initial, &*initial);
+    {
+        let margin = Rc::make_mut(&mut computed.margin);
+        let length = |length| {
+            LengthPercentageOrAuto::LengthPercentage(LengthPercentage::from_length(length))
+        };
+        margin.margin_top = length(default_margins.top_typed());
+        margin.margin_right = length(default_margins.right_typed());
+        margin.margin_bottom = length(default_margins.bottom_typed());
+        margin.margin_left = length(default_margins.left_typed());
+    }
+    // All `@page` rules without selectors have the same specificity,
+    // so they apply in document order.
+    let declarations = author
+        .page_rules
+        .iter()
+        .flat_map(|rule| rule.declarations.iter());
+    cascade_declarations(declarations, &mut computed, &*initial, device);
+    let size = match author.page_rules.iter().rev().find_map(|rule| rule.size) {
+        Some(size) => {
+            let context = CascadeContext {
+                inherited: &*initial,
+                font_size: computed.font.font_size,
+                root_font_size: device.root_font_size,
+                viewport_size: device.viewport_size,
+            };
+            size.resolve(default_size, &context)
+        }
+        None => default_size,
+    };
+    let page_context = Rc::new(computed);
+
+    let mut generated_boxes: Vec<_> = author
+        .page_rules
+        .iter()
+        .flat_map(|rule| {
+            rule.margin_rules
+                .iter()
+                .map(|margin_rule| margin_rule.margin_box)
+        })
+        .collect();
+    generated_boxes.sort();
+    generated_boxes.dedup();
+    let margin_boxes = generated_boxes
+        .into_iter()
+        .map(|margin_box| {
+            let declarations = author
+                .page_rules
+                .iter()
+                .flat_map(|rule| rule.margin_rules.iter())
+                .filter(move |margin_rule| margin_rule.margin_box == margin_box)
+                .flat_map(|margin_rule| margin_rule.declarations.iter());
+            let mut computed = ComputedValues::new_inheriting_from(&page_context, &initial);
+            cascade_declarations(declarations, &mut computed, &page_context, device);
+            (margin_box, Rc::new(computed))
+        })
+        .collect();
+
+    PageStyles {
+        size,
+        page_context,
+        margin_boxes,
+    }
+}
@@ -0,0 +1,160 @@
+use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
+use crate::style::values::color::Color;
+use crate::style::values::length::{Length, PxLength};
+use crate::style::values::{CascadeContext, Parse, ToComputedValue};
+use cssparser::Parser;
+
+/// <https://drafts.csswg.org/css-backgrounds/#typedef-line-width>
+#[derive(Copy, Clone)]
+pub enum LineWidth {
+    Thin,
+    Medium,
+    Thick,
+    Length(Length),
+}
+
+/// <https://drafts.csswg.org/css-backgrounds/#typedef-line-style>
+#[derive(Copy, Clone, PartialEq, Parse, ComputedAsSpecified)]
+pub enum BorderStyle {
+    None,
+    Hidden,
+    Dotted,
+    Dashed,
+    Solid,
+    Double,
+    Groove,
+    Ridge,
+    Inset,
+    Outset,
+}
+
+/// The `border-top`, `border-right`, `border-bottom`, and `border-left` shorthands
+///
+/// <https://drafts.csswg.org/css-backgrounds/#border-shorthands>
+#[derive(Clone)]
+pub struct BorderSide {
+    pub width: LineWidth,
+    pub style: BorderStyle,
+    pub color: Color,
+}
+
+/// The `border` shorthand, which sets the same values on all four sides
+pub struct Border {
+    pub top_width: LineWidth,
+    pub top_style: BorderStyle,
+    pub top_color: Color,
+    pub right_width: LineWidth,
+    pub right_style: BorderStyle,
+    pub right_color: Color,
+    pub bottom_width: LineWidth,
+    pub bottom_style: BorderStyle,
+    pub bottom_color: Color,
+    pub left_width: LineWidth,
+    pub left_style: BorderStyle,
+    pub left_color: Color,
+}
+
+impl BorderStyle {
+    /// Whether this style has no border, so that the used border width is zero
+    pub fn is_none_or_hidden(self) -> bool {
+        match self {
+            BorderStyle::None | BorderStyle::Hidden => true,
+            _ => false,
+        }
+    }
+}
+
+impl Parse for LineWidth {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(length) = parser.r#try(Length::parse) {
+            if length.is_negative() {
+                return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+            }
+            return Ok(LineWidth::Length(length))
+        }
+        let ident = parser.expect_ident()?;
+        match_ignore_ascii_case!(&**ident,
+            "thin" => Ok(LineWidth::Thin),
+            "medium" => Ok(LineWidth::Medium),
+            "thick" => Ok(LineWidth::Thick),
+            _ => {
+                let token = cssparser::Token::Ident(ident.clone());
+                Err(parser.new_unexpected_token_error(token))
+            }
+        )
+    }
+}
+
+impl ToComputedValue for LineWidth {
+    type Computed = PxLength;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        match self {
+            LineWidth::Thin => PxLength::new(1.),
+            LineWidth::Medium => PxLength::new(3.),
+            LineWidth::Thick => PxLength::new(5.),
+            LineWidth::Length(length) => length.to_computed(context),
+        }
+    }
+}
+
+impl Parse for BorderSide {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        // Components can be in any order, and default to their initial values.
+        let mut width = None;
+        let mut style = None;
+        let mut color = None;
+        loop {
+            if width.is_none() {
+                if let Ok(value) = parser.r#try(LineWidth::parse) {
+                    width = Some(value);
+                    continue
+                }
+            }
+            if style.is_none() {
+                if let Ok(value) = parser.r#try(BorderStyle::parse) {
+                    style = Some(value);
+                    continue
+                }
+            }
+            if color.is_none() {
+                if let Ok(value) = parser.r#try(Color::parse) {
+                    color = Some(value);
+                    continue
+                }
+            }
+            break
+        }
+        if width.is_none() && style.is_none() && color.is_none() {
+            return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+        }
+        Ok(BorderSide {
+            width: width.unwrap_or(LineWidth::Medium),
+            style: style.unwrap_or(BorderStyle::None),
+            color: color.unwrap_or(Color::current_color()),
+        })
+    }
+}
+
+impl Parse for Border {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        let BorderSide {
+            width,
+            style,
+            color,
+        } = BorderSide::parse(parser)?;
+        Ok(Border {
+            top_width: width,
+            top_style: style,
+            top_color: color,
+            right_width: width,
+            right_style: style,
+            right_color: color,
+            bottom_width: width,
+            bottom_style: style,
+            bottom_color: color,
+            left_width: width,
+            left_style: style,
+            left_color: color,
+        })
+    }
+}
@@ -0,0 +1,300 @@
+//! Math functions: `calc()`, `min()`, `max()`, and `clamp()`
+//!
+//! <https://drafts.csswg.org/css-values-4/#math>
+
+use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
+use crate::style::values::length::{Length, Percentage, PxLength};
+use cssparser::{Parser, Token};
+
+/// A math function whose result is a `<length-percentage>`.
+///
+/// `L` is the type of lengths: `Length` when specified, `PxLength` when computed.
+/// Numbers are folded into sums and products while parsing,
+/// so the other variants are never numbers.
+#[derive(Clone)]
+pub enum CalcNode<L> {
+    Number(f32),
+    Length(L),
+    Percentage(Percentage),
+    Sum(Vec<CalcNode<L>>),
+    Product(Box<CalcNode<L>>, f32),
+    Min(Vec<CalcNode<L>>),
+    Max(Vec<CalcNode<L>>),
+    Clamp(Box<[CalcNode<L>; 3]>),
+}
+
+impl CalcNode<Length> {
+    /// Parse a math function, including its name.
+    pub fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        let node = parse_function(parser)?;
+        match node {
+            CalcNode::Number(_) => Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+            node => Ok(node),
+        }
+    }
+}
+
+impl<L> CalcNode<L> {
+    fn is_number(&self) -> bool {
+        match self {
+            CalcNode::Number(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn map_lengths<M>(&self, f: &impl Fn(&L) -> M) -> CalcNode<M> {
+        let map_all =
+            |nodes: &[CalcNode<L>]| nodes.iter().map(|node| node.map_lengths(f)).collect();
+        match self {
+            CalcNode::Number(value) => CalcNode::Number(*value),
+            CalcNode::Length(length) => CalcNode::Length(f(length)),
+            CalcNode::Percentage(percentage) => CalcNode::Percentage(*percentage),
+            CalcNode::Sum(terms) => CalcNode::Sum(map_all(terms)),
+            CalcNode::Product(node, factor) => {
+                CalcNode::Product(Box::new(node.map_lengths(f)), *factor)
+            }
+            CalcNode::Min(arguments) => CalcNode::Min(map_all(arguments)),
+            CalcNode::Max(arguments) => CalcNode::Max(map_all(arguments)),
+            CalcNode::Clamp(arguments) => {
+                let [min, center, max] = &**arguments;
+                CalcNode::Clamp(Box::new([
+                    min.map_lengths(f),
+                    center.map_lengths(f),
+                    max.map_lengths(f),
+                ]))
+            }
+        }
+    }
+}
+
+impl CalcNode<PxLength> {
+    /// Simplify to the sum of a length and a percentage, if possible.
+    ///
+    /// This is not possible for `min()`, `max()`, or `clamp()` with percentages in arguments.
+    pub fn to_sum(&self) -> Option<(PxLength, Option<Percentage>)> {
+        self.evaluate(None)
+    }
+
+    /// `basis` is what percentages are relative to.
+    pub fn resolve(&self, basis: PxLength) -> PxLength {
+        match self.evaluate(Some(basis)) {
+            Some((length, _)) => length,
+            None => unreachable!(),
+        }
+    }
+
+    /// Percentages are resolved against `basis` if there is one, or kept in the result.
+    fn evaluate(&self, basis: Option<PxLength>) -> Option<(PxLength, Option<Percentage>)> {
+        // Arguments of comparison functions need to be resolved to a single length.
+        let argument = |node: &CalcNode<PxLength>| match node.evaluate(basis)? {
+            (length, None) => Some(length.0),
+            (_, Some(_)) => None,
+        };
+        let compare = |arguments: &[CalcNode<PxLength>], f: fn(f32, f32) -> f32| {
+            let mut lengths = arguments.iter().map(argument);
+            let first = lengths.next()??;
+            lengths.try_fold(first, |result, length| Some(f(result, length?)))
+        };
+        let length = match self {
+            CalcNode::Number(value) => *value,
+            CalcNode::Length(length) => length.0,
+            CalcNode::Percentage(percentage) => match basis {
+                Some(basis) => basis.0 * percentage.0,
+                None => return Some((PxLength::new(0.), Some(*percentage))),
+            },
+            CalcNode::Sum(terms) => {
+                let mut sum = (PxLength::new(0.), None);
+                for term in terms {
+                    let (length, percentage) = term.evaluate(basis)?;
+                    sum.0 = sum.0 + length;
+                    sum.1 = match (sum.1, percentage) {
+                        (Some(Percentage(a)), Some(Percentage(b))) => Some(Percentage(a + b)),
+                        (a, None) => a,
+                        (None, b) => b,
+                    };
+                }
+                return Some(sum)
+            }
+            CalcNode::Product(node, factor) => {
+                let (length, percentage) = node.evaluate(basis)?;
+                let percentage = percentage.map(|Percentage(p)| Percentage(p * factor));
+                return Some((length * *factor, percentage))
+            }
+            CalcNode::Min(arguments) => compare(arguments, f32::min)?,
+            CalcNode::Max(arguments) => compare(arguments, f32::max)?,
+            CalcNode::Clamp(arguments) => {
+                let [min, center, max] = &**arguments;
+                let (min, center, max) = (argument(min)?, argument(center)?, argument(max)?);
+                // If `min` is larger than `max`, `min` wins.
+                center.min(max).max(min)
+            }
+        };
+        Some((PxLength::new(length), None))
+    }
+}
+
+fn parse_function<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<CalcNode<Length>, PropertyParseError<'i>> {
+    let name = parser.expect_function()?.clone();
+    parser.parse_nested_block(|parser| {
+        match_ignore_ascii_case!(&name,
+            "calc" => parse_sum(parser),
+            "min" => {
+                let arguments = parse_comparison_arguments(parser)?;
+                Ok(fold_comparison(arguments, f32::min, CalcNode::Min))
+            },
+            "max" => {
+                let arguments = parse_comparison_arguments(parser)?;
+                Ok(fold_comparison(arguments, f32::max, CalcNode::Max))
+            },
+            "clamp" => {
+                let mut arguments = parse_comparison_arguments(parser)?;
+                if arguments.len() != 3 {
+                    return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+                }
+                let max = arguments.pop().unwrap();
+                let center = arguments.pop().unwrap();
+                let min = arguments.pop().unwrap();
+                Ok(match (&min, &center, &max) {
+                    (&CalcNode::Number(min), &CalcNode::Number(center), &CalcNode::Number(max)) => {
+                        CalcNode::Number(center.min(max).max(min))
+                    }
+                    _ => CalcNode::Clamp(Box::new([min, center, max])),
+                })
+            },
+            _ => Err(parser.new_unexpected_token_error(Token::Function(name.clone())))
+        )
+    })
+}
+
+/// Arguments of `min()`, `max()`, or `clamp()`, which must all be numbers or all be lengths.
+fn parse_comparison_arguments<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<Vec<CalcNode<Length>>, PropertyParseError<'i>> {
+    let arguments = parser.parse_comma_separated(parse_sum)?;
+    let numbers = arguments
+        .iter()
+        .filter(|argument| argument.is_number())
+        .count();
+    if numbers != 0 && numbers != arguments.len() {
+        return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+    }
+    Ok(arguments)
+}
+
+fn fold_comparison(
+    arguments: Vec<CalcNode<Length>>,
+    f: fn(f32, f32) -> f32,
+    variant: fn(Vec<CalcNode<Length>>) -> CalcNode<Length>,
+) -> CalcNode<Length> {
+    let mut numbers = Vec::new();
+    for argument in &arguments {
+        match argument {
+            CalcNode::Number(value) => numbers.push(*value),
+            _ => return variant(arguments),
+        }
+    }
+    let first = numbers[0];
+    CalcNode::Number(numbers.into_iter().fold(first, f))
+}
+
+/// <https://drafts.csswg.org/css-values-4/#typedef-calc-sum>
+fn parse_sum<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<CalcNode<Length>, PropertyParseError<'i>> {
+    let mut terms = vec![parse_product(parser)?];
+    loop {
+        let state = parser.state();
+        let token = parser.next().map(|token| token.clone());
+        match token {
+            Ok(Token::Delim('+')) => terms.push(parse_product(parser)?),
+            Ok(Token::Delim('-')) => {
+                terms.push(multiply(parse_product(parser)?, CalcNode::Number(-1.)).unwrap())
+            }
+            _ => {
+                parser.reset(&state);
+                break
+            }
+        }
+    }
+    if terms.len() == 1 {
+        return Ok(terms.pop().unwrap())
+    }
+    let numbers: Vec<f32> = terms
+        .iter()
+        .filter_map(|term| match term {
+            CalcNode::Number(value) => Some(*value),
+            _ => None,
+        })
+        .collect();
+    if numbers.is_empty() {
+        Ok(CalcNode::Sum(terms))
+    } else if numbers.len() == terms.len() {
+        Ok(CalcNode::Number(numbers.into_iter().sum()))
+    } else {
+        // Adding a number to a length
+        Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+    }
+}
+
+/// <https://drafts.csswg.org/css-values-4/#typedef-calc-product>
+fn parse_product<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<CalcNode<Length>, PropertyParseError<'i>> {
+    let mut node = parse_value(parser)?;
+    loop {
+        let state = parser.state();
+        let token = parser.next().map(|token| token.clone());
+        let product = match token {
+            Ok(Token::Delim('*')) => multiply(node, parse_value(parser)?),
+            Ok(Token::Delim('/')) => match parse_value(parser)? {
+                CalcNode::Number(divisor) if divisor != 0. => {
+                    multiply(node, CalcNode::Number(1. / divisor))
+                }
+                _ => None,
+            },
+            _ => {
+                parser.reset(&state);
+                return Ok(node)
+            }
+        };
+        node = match product {
+            Some(product) => product,
+            None => return Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+        }
+    }
+}
+
+/// Return `None` if neither side is a number.
+fn multiply(a: CalcNode<Length>, b: CalcNode<Length>) -> Option<CalcNode<Length>> {
+    match (a, b) {
+        (CalcNode::Number(a), CalcNode::Number(b)) => Some(CalcNode::Number(a * b)),
+        (CalcNode::Number(factor), node) | (node, CalcNode::Number(factor)) => {
+            Some(CalcNode::Product(Box::new(node), factor))
+        }
+        _ => None,
+    }
+}
+
+/// <https://drafts.csswg.org/css-values-4/#typedef-calc-value>
+fn parse_value<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<CalcNode<Length>, PropertyParseError<'i>> {
+    if let Ok(node) = parser.r#try(parse_function) {
+        return Ok(node)
+    }
+    let token = parser.next()?.clone();
+    match token {
+        Token::Number { value, .. } => Ok(CalcNode::Number(value)),
+        Token::Percentage { unit_value, .. } => Ok(CalcNode::Percentage(Percentage(unit_value))),
+        Token::Dimension {
+            value, ref unit, ..
+        } => match Length::parse_dimension(value, unit) {
+            Ok(length) => Ok(CalcNode::Length(length)),
+            Err(()) => Err(parser.new_unexpected_token_error(token.clone())),
+        },
+        Token::ParenthesisBlock => parser.parse_nested_block(parse_sum),
+        _ => Err(parser.new_unexpected_token_error(token)),
+    }
+}
@@ -0,0 +1,58 @@
+use crate::primitives::RGBA;
+use crate::style::errors::PropertyParseError;
+use crate::style::values::{CascadeContext, Parse, ToComputedValue};
+use cssparser::Parser;
+
+/// <https://drafts.csswg.org/css-color/#typedef-color>
+///
+/// `currentColor` is kept in computed values, and resolved when painting.
+#[derive(Copy, Clone, ComputedAsSpecified)]
+pub struct Color(cssparser::Color);
+
+/// The value of the `color` property, where `currentColor` is the inherited color.
+///
+/// <https://drafts.csswg.org/css-color/#the-color-property>
+#[derive(Copy, Clone)]
+pub struct ForegroundColor(Color);
+
+impl Color {
+    pub fn current_color() -> Self {
+        Color(cssparser::Color::CurrentColor)
+    }
+
+    pub fn transparent() -> Self {
+        Color(cssparser::Color::RGBA(cssparser::RGBA::transparent()))
+    }
+
+    /// `current_color` is the computed value of the `color` property.
+    pub fn resolve(&self, current_color: RGBA) -> RGBA {
+        match self.0 {
+            cssparser::Color::CurrentColor => current_color,
+            cssparser::Color::RGBA(rgba) => RGBA(
+                rgba.red_f32(),
+                rgba.green_f32(),
+                rgba.blue_f32(),
+                rgba.alpha_f32(),
+            ),
+        }
+    }
+}
+
+impl Parse for Color {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        Ok(Color(cssparser::Color::parse(parser)?))
+    }
+}
+
+impl Parse for ForegroundColor {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        Color::parse(parser).map(ForegroundColor)
+    }
+}
+
+impl ToComputedValue for ForegroundColor {
+    type Computed = RGBA;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        self.0.resolve(context.inherited.color.color)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
+use crate::style::values::Parse;
+use cssparser::Parser;
+use std::rc::Rc;
+
+/// <https://drafts.csswg.org/css-content/#content-property>
+///
+/// Only used in page margin boxes, since pseudo-elements are not supported.
+#[derive(Clone, ComputedAsSpecified)]
+pub enum Content {
+    Normal,
+    None,
+    Items(Rc<Vec<ContentItem>>),
+}
+
+pub enum ContentItem {
+    String(String),
+
+    /// The name of a counter, rendered as a decimal number.
+    ///
+    /// <https://drafts.csswg.org/css-lists/#counter-functions>
+    Counter(String),
+}
+
+impl Parse for Content {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser.r#try(|p| p.expect_ident_matching("normal")).is_ok() {
+            return Ok(Content::Normal)
+        }
+        if parser.r#try(|p| p.expect_ident_matching("none")).is_ok() {
+            return Ok(Content::None)
+        }
+        let mut items = vec![ContentItem::parse(parser)?];
+        while let Ok(item) = parser.r#try(ContentItem::parse) {
+            items.push(item)
+        }
+        Ok(Content::Items(Rc::new(items)))
+    }
+}
+
+impl Parse for ContentItem {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(string) = parser.r#try(|p| p.expect_string().map(|s| s.to_string())) {
+            return Ok(ContentItem::String(string))
+        }
+        parser.expect_function_matching("counter")?;
+        parser.parse_nested_block(|parser| {
+            let name = parser.expect_ident()?.to_string();
+            // `decimal` is the only supported counter style.
+            if parser.r#try(|p| p.expect_comma()).is_ok() {
+                parser.expect_ident_matching("decimal")?
+            }
+            if name.eq_ignore_ascii_case("none") {
+                return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+            }
+            Ok(ContentItem::Counter(name))
+        })
+    }
+}
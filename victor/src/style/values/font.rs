@@ -0,0 +1,406 @@
+use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
+use crate::style::values::length::{LengthOrPercentage, Percentage, PxLength};
+use crate::style::values::{CascadeContext, Parse, ToComputedValue};
+use cssparser::{Parser, Token};
+use std::rc::Rc;
+
+/// <https://drafts.csswg.org/css-fonts/#font-family-prop>
+#[derive(Clone, ComputedAsSpecified)]
+pub struct FontFamily(pub Rc<Vec<FamilyName>>);
+
+#[derive(Clone)]
+pub enum FamilyName {
+    Named(String),
+    Generic(GenericFamily),
+}
+
+/// <https://drafts.csswg.org/css-fonts/#generic-family-value>
+#[derive(Copy, Clone, Parse)]
+pub enum GenericFamily {
+    Serif,
+    SansSerif,
+    Cursive,
+    Fantasy,
+    Monospace,
+    SystemUi,
+}
+
+/// One of the comma-separated values of the `src` descriptor in `@font-face`
+///
+/// <https://drafts.csswg.org/css-fonts/#src-desc>
+pub enum FontFaceSource {
+    Url {
+        url: String,
+
+        /// From `format()`, empty if there is none
+        format_hints: Vec<String>,
+    },
+
+    /// The full name or PostScript name of an already-available font face
+    Local(String),
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-weight-prop>
+///
+/// Computes to a number from 1 to 1000.
+#[derive(Copy, Clone)]
+pub enum FontWeight {
+    Absolute(u16),
+    Bolder,
+    Lighter,
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-style-prop>
+///
+/// FIXME: parse and honor the angle of `oblique`.
+#[derive(Copy, Clone, PartialEq, Parse, ComputedAsSpecified)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-stretch-prop>
+///
+/// Keywords are converted to percentages when parsing.
+#[derive(Copy, Clone, ComputedAsSpecified)]
+pub struct FontStretch(pub Percentage);
+
+/// <https://drafts.csswg.org/css-fonts/#font-feature-settings-prop>
+///
+/// OpenType feature tags and their values, in specified order. Empty for `normal`.
+#[derive(Clone, ComputedAsSpecified)]
+pub struct FontFeatureSettings(pub Rc<Vec<([u8; 4], u32)>>);
+
+/// <https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def>
+///
+/// Variation axis tags and their values, in specified order. Empty for `normal`.
+#[derive(Clone, ComputedAsSpecified)]
+pub struct FontVariationSettings(pub Rc<Vec<([u8; 4], f32)>>);
+
+/// <https://drafts.csswg.org/css-fonts/#font-variant-ligatures-prop>
+///
+/// For each kind of ligatures, `Some(true)` or `Some(false)` if it is explicitly
+/// enabled or disabled, and `None` to use the default behavior.
+/// `normal` is all `None`, and `none` is all `Some(false)`.
+#[derive(Copy, Clone, Default, ComputedAsSpecified)]
+pub struct FontVariantLigatures {
+    pub common: Option<bool>,
+    pub discretionary: Option<bool>,
+    pub historical: Option<bool>,
+    pub contextual: Option<bool>,
+}
+
+/// <https://drafts.csswg.org/css-fonts/#font-size-prop>
+#[derive(Clone)]
+pub enum FontSize {
+    /// `xx-small` to `xxx-large`, as a multiple of `medium`
+    Absolute(f32),
+
+    /// `larger` or `smaller`, as a multiple of the parent’s font size
+    Relative(f32),
+
+    /// Percentages are relative to the parent’s font size.
+    LengthOrPercentage(LengthOrPercentage),
+}
+
+impl Parse for FontSize {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(value) = parser.r#try(LengthOrPercentage::parse) {
+            if value.is_negative() {
+                return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+            }
+            return Ok(FontSize::LengthOrPercentage(value))
+        }
+        let ident = parser.expect_ident()?;
+        // https://drafts.csswg.org/css-fonts/#absolute-size-mapping
+        match_ignore_ascii_case!(&**ident,
+            "xx-small" => Ok(FontSize::Absolute(3. / 5.)),
+            "x-small" => Ok(FontSize::Absolute(3. / 4.)),
+            "small" => Ok(FontSize::Absolute(8. / 9.)),
+            "medium" => Ok(FontSize::Absolute(1.)),
+            "large" => Ok(FontSize::Absolute(6. / 5.)),
+            "x-large" => Ok(FontSize::Absolute(3. / 2.)),
+            "xx-large" => Ok(FontSize::Absolute(2.)),
+            "xxx-large" => Ok(FontSize::Absolute(3.)),
+            "larger" => Ok(FontSize::Relative(1.2)),
+            "smaller" => Ok(FontSize::Relative(1. / 1.2)),
+            _ => {
+                let token = Token::Ident(ident.clone());
+                Err(parser.new_unexpected_token_error(token))
+            }
+        )
+    }
+}
+
+impl ToComputedValue for FontSize {
+    type Computed = PxLength;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        let parent_font_size = context.inherited.font.font_size;
+        match self {
+            FontSize::Absolute(factor) => PxLength::new(16.) * *factor,
+            FontSize::Relative(factor) => parent_font_size * *factor,
+            FontSize::LengthOrPercentage(value) => {
+                let font_size = value.to_computed(context).resolve(parent_font_size);
+                // `calc()` can be negative, and is clamped to the allowed range.
+                PxLength::new(font_size.0.max(0.))
+            }
+        }
+    }
+}
+
+impl FontFamily {
+    pub fn sans_serif() -> Self {
+        FontFamily(Rc::new(vec![FamilyName::Generic(GenericFamily::SansSerif)]))
+    }
+}
+
+impl Parse for FontFamily {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        parser
+            .parse_comma_separated(FamilyName::parse)
+            .map(|names| FontFamily(Rc::new(names)))
+    }
+}
+
+impl Parse for FamilyName {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(name) = parser.r#try(|parser| parser.expect_string().map(|s| s.to_string())) {
+            return Ok(FamilyName::Named(name))
+        }
+        // A single keyword is a generic family,
+        // but quoted names or sequences of identifiers are not.
+        let generic = parser.r#try(|parser| -> Result<_, PropertyParseError> {
+            let generic = GenericFamily::parse(parser)?;
+            parser.expect_exhausted()?;
+            Ok(generic)
+        });
+        if let Ok(generic) = generic {
+            return Ok(FamilyName::Generic(generic))
+        }
+        let mut name = parser.expect_ident()?.to_string();
+        while let Ok(ident) = parser.r#try(|parser| parser.expect_ident().cloned()) {
+            name.push(' ');
+            name.push_str(&ident)
+        }
+        Ok(FamilyName::Named(name))
+    }
+}
+
+impl Parse for FontWeight {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(number) = parser.r#try(|parser| parser.expect_number()) {
+            if !(1. ..=1000.).contains(&number) {
+                return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+            }
+            return Ok(FontWeight::Absolute(number.round() as u16))
+        }
+        let ident = parser.expect_ident()?;
+        match_ignore_ascii_case!(&**ident,
+            "normal" => Ok(FontWeight::Absolute(400)),
+            "bold" => Ok(FontWeight::Absolute(700)),
+            "bolder" => Ok(FontWeight::Bolder),
+            "lighter" => Ok(FontWeight::Lighter),
+            _ => {
+                let token = Token::Ident(ident.clone());
+                Err(parser.new_unexpected_token_error(token))
+            }
+        )
+    }
+}
+
+impl ToComputedValue for FontWeight {
+    type Computed = u16;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        // https://drafts.csswg.org/css-fonts/#relative-weights
+        let parent_weight = context.inherited.font.font_weight;
+        match *self {
+            FontWeight::Absolute(weight) => weight,
+            FontWeight::Bolder => match parent_weight {
+                0..=349 => 400,
+                350..=549 => 700,
+                550..=899 => 900,
+                _ => parent_weight,
+            },
+            FontWeight::Lighter => match parent_weight {
+                0..=99 => parent_weight,
+                100..=549 => 100,
+                550..=749 => 400,
+                _ => 700,
+            },
+        }
+    }
+}
+
+impl Parse for FontStretch {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(percentage) = parser.r#try(|parser| parser.expect_percentage()) {
+            if percentage < 0. {
+                return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+            }
+            return Ok(FontStretch(Percentage(percentage)))
+        }
+        let ident = parser.expect_ident()?;
+        // https://drafts.csswg.org/css-fonts/#font-stretch-prop
+        let percentage = match_ignore_ascii_case!(&**ident,
+            "ultra-condensed" => 0.5,
+            "extra-condensed" => 0.625,
+            "condensed" => 0.75,
+            "semi-condensed" => 0.875,
+            "normal" => 1.,
+            "semi-expanded" => 1.125,
+            "expanded" => 1.25,
+            "extra-expanded" => 1.5,
+            "ultra-expanded" => 2.,
+            _ => {
+                let token = Token::Ident(ident.clone());
+                return Err(parser.new_unexpected_token_error(token))
+            }
+        );
+        Ok(FontStretch(Percentage(percentage)))
+    }
+}
+
+impl Parse for FontFaceSource {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(url) = parser.r#try(|parser| parser.expect_url().map(|url| url.to_string())) {
+            let format_hints = parser
+                .r#try(|parser| -> Result<_, PropertyParseError> {
+                    parser.expect_function_matching("format")?;
+                    parser.parse_nested_block(|parser| {
+                        parser
+                            .parse_comma_separated(|parser| Ok(parser.expect_string()?.to_string()))
+                    })
+                })
+                .unwrap_or_default();
+            return Ok(FontFaceSource::Url { url, format_hints })
+        }
+        parser.expect_function_matching("local")?;
+        parser.parse_nested_block(|parser| match FamilyName::parse(parser)? {
+            FamilyName::Named(name) => Ok(FontFaceSource::Local(name)),
+            FamilyName::Generic(_) => Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+        })
+    }
+}
+
+impl FontFeatureSettings {
+    pub fn normal() -> Self {
+        FontFeatureSettings(Rc::new(Vec::new()))
+    }
+}
+
+impl Parse for FontFeatureSettings {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser
+            .r#try(|parser| parser.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(FontFeatureSettings::normal())
+        }
+        parser
+            .parse_comma_separated(|parser| {
+                let tag = parser.expect_string()?;
+                let tag = match tag.as_bytes() {
+                    &[a, b, c, d] if tag.bytes().all(|byte| (0x20..=0x7E).contains(&byte)) => {
+                        [a, b, c, d]
+                    }
+                    _ => return Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+                };
+                if let Ok(value) = parser.r#try(|parser| parser.expect_integer()) {
+                    if value < 0 {
+                        return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+                    }
+                    return Ok((tag, value as u32))
+                }
+                let value = parser.r#try(|parser| -> Result<_, PropertyParseError> {
+                    let ident = parser.expect_ident()?;
+                    match_ignore_ascii_case!(&**ident,
+                        "on" => Ok(1),
+                        "off" => Ok(0),
+                        _ => {
+                            let token = Token::Ident(ident.clone());
+                            Err(parser.new_unexpected_token_error(token))
+                        }
+                    )
+                });
+                Ok((tag, value.unwrap_or(1)))
+            })
+            .map(|settings| FontFeatureSettings(Rc::new(settings)))
+    }
+}
+
+impl FontVariationSettings {
+    pub fn normal() -> Self {
+        FontVariationSettings(Rc::new(Vec::new()))
+    }
+}
+
+impl Parse for FontVariationSettings {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser
+            .r#try(|parser| parser.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(FontVariationSettings::normal())
+        }
+        parser
+            .parse_comma_separated(|parser| {
+                let tag = parser.expect_string()?;
+                let tag = match tag.as_bytes() {
+                    &[a, b, c, d] if tag.bytes().all(|byte| (0x20..=0x7E).contains(&byte)) => {
+                        [a, b, c, d]
+                    }
+                    _ => return Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+                };
+                Ok((tag, parser.expect_number()?))
+            })
+            .map(|settings| FontVariationSettings(Rc::new(settings)))
+    }
+}
+
+impl Parse for FontVariantLigatures {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        let mut ligatures = FontVariantLigatures::default();
+        if parser
+            .r#try(|parser| parser.expect_ident_matching("normal"))
+            .is_ok()
+        {
+            return Ok(ligatures)
+        }
+        if parser
+            .r#try(|parser| parser.expect_ident_matching("none"))
+            .is_ok()
+        {
+            return Ok(FontVariantLigatures {
+                common: Some(false),
+                discretionary: Some(false),
+                historical: Some(false),
+                contextual: Some(false),
+            })
+        }
+        // Each kind of ligatures can be specified at most once, in any order.
+        let mut any = false;
+        while let Ok(ident) = parser.r#try(|parser| parser.expect_ident().cloned()) {
+            let (kind, enabled) = match_ignore_ascii_case!(&*ident,
+                "common-ligatures" => (&mut ligatures.common, true),
+                "no-common-ligatures" => (&mut ligatures.common, false),
+                "discretionary-ligatures" => (&mut ligatures.discretionary, true),
+                "no-discretionary-ligatures" => (&mut ligatures.discretionary, false),
+                "historical-ligatures" => (&mut ligatures.historical, true),
+                "no-historical-ligatures" => (&mut ligatures.historical, false),
+                "contextual" => (&mut ligatures.contextual, true),
+                "no-contextual" => (&mut ligatures.contextual, false),
+                _ => return Err(parser.new_unexpected_token_error(Token::Ident(ident)))
+            );
+            if kind.is_some() {
+                return Err(parser.new_unexpected_token_error(Token::Ident(ident)))
+            }
+            *kind = Some(enabled);
+            any = true
+        }
+        if !any {
+            return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+        }
+        Ok(ligatures)
+    }
+}
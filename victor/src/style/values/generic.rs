@@ -9,6 +9,8 @@ pub struct FourSides<T> {
     pub right: T,
 }
 
+/// <https://drafts.csswg.org/css-backgrounds/#shorthands>:
+/// one to four values for top, right, bottom, and left.
 impl<T> Parse for FourSides<T>
 where
     T: Parse + Clone,
@@ -16,8 +18,8 @@ where
     fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
         let top = T::parse(parser)?;
 
-        let left = if let Ok(left) = parser.r#try(T::parse) {
-            left
+        let right = if let Ok(right) = parser.r#try(T::parse) {
+            right
         } else {
             return Ok(FourSides {
                 top: top.clone(),
@@ -32,20 +34,20 @@ where
         } else {
             return Ok(FourSides {
                 top: top.clone(),
-                left: left.clone(),
+                left: right.clone(),
                 bottom: top,
-                right: left,
+                right: right,
             })
         };
 
-        let right = if let Ok(right) = parser.r#try(T::parse) {
-            right
+        let left = if let Ok(left) = parser.r#try(T::parse) {
+            left
         } else {
             return Ok(FourSides {
                 top: top,
-                left: left.clone(),
+                left: right.clone(),
                 bottom: bottom,
-                right: left,
+                right: right,
             })
         };
 
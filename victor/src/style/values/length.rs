@@ -1,14 +1,110 @@
 use crate::primitives::{CssPx, Length as EuclidLength};
 use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
-use crate::style::values::{Parse, ToComputedValue};
+use crate::style::values::calc::CalcNode;
+use crate::style::values::{CascadeContext, Parse, ToComputedValue};
 use cssparser::{Parser, Token};
+use std::rc::Rc;
 
 pub type PxLength = EuclidLength<CssPx>;
 
 /// <https://drafts.csswg.org/css-values/#lengths>
+///
+/// Absolute lengths are converted to pixels when parsing.
 #[derive(Copy, Clone)]
 pub enum Length {
     Px(PxLength),
+    Em(f32),
+    Rem(f32),
+    Vw(f32),
+    Vh(f32),
+    Vmin(f32),
+    Vmax(f32),
+}
+
+/// <https://drafts.csswg.org/css-values/#percentages>
+///
+/// `1.` means 100%.
+#[derive(Copy, Clone)]
+pub struct Percentage(pub f32);
+
+/// <https://drafts.csswg.org/css-values/#typedef-length-percentage>
+#[derive(Clone)]
+pub enum LengthOrPercentage {
+    Length(Length),
+    Percentage(Percentage),
+    Calc(Box<CalcNode<Length>>),
+}
+
+/// A `<length-percentage>` where negative values are invalid, such as in `padding`
+#[derive(Clone)]
+pub struct NonNegativeLengthOrPercentage(pub LengthOrPercentage);
+
+/// `<length-percentage> | auto`, such as in `margin`
+#[derive(Clone)]
+pub enum LengthOrPercentageOrAuto {
+    LengthOrPercentage(LengthOrPercentage),
+    Auto,
+}
+
+/// `<length-percentage [0,∞]> | auto`, such as in `width`
+#[derive(Clone)]
+pub struct NonNegativeLengthOrPercentageOrAuto(pub LengthOrPercentageOrAuto);
+
+/// `<length-percentage [0,∞]> | none`, in `max-width` and `max-height`
+#[derive(Clone)]
+pub enum NonNegativeLengthOrPercentageOrNone {
+    LengthOrPercentage(LengthOrPercentage),
+    None,
+}
+
+/// The computed value of `<length-percentage>`.
+///
+/// Percentages are kept until layout, which resolves them against the relevant basis,
+/// usually the width of the containing block.
+#[derive(Clone)]
+pub enum LengthPercentage {
+    Sum {
+        length: PxLength,
+        percentage: Option<Percentage>,
+    },
+
+    /// `min()`, `max()`, or `clamp()` with percentages in arguments,
+    /// which cannot be simplified to a sum before the basis is known.
+    Calc(Rc<CalcNode<PxLength>>),
+}
+
+impl Length {
+    pub fn is_negative(&self) -> bool {
+        match *self {
+            Length::Px(px) => px.0 < 0.,
+            Length::Em(value)
+            | Length::Rem(value)
+            | Length::Vw(value)
+            | Length::Vh(value)
+            | Length::Vmin(value)
+            | Length::Vmax(value) => value < 0.,
+        }
+    }
+
+    pub(super) fn parse_dimension(value: f32, unit: &str) -> Result<Self, ()> {
+        let px = |px_per_unit: f32| Ok(Length::Px(PxLength::new(value * px_per_unit)));
+        match_ignore_ascii_case!(unit,
+            "px" => px(1.),
+            "in" => px(96.),
+            "cm" => px(96. / 2.54),
+            "mm" => px(96. / 25.4),
+            "q" => px(96. / 25.4 / 4.),
+            "pt" => px(96. / 72.),
+            "pc" => px(96. / 6.),
+            "em" => Ok(Length::Em(value)),
+            "rem" => Ok(Length::Rem(value)),
+            "vw" => Ok(Length::Vw(value)),
+            "vh" => Ok(Length::Vh(value)),
+            "vmin" => Ok(Length::Vmin(value)),
+            "vmax" => Ok(Length::Vmax(value)),
+            _ => Err(())
+        )
+    }
 }
 
 impl Parse for Length {
@@ -16,21 +112,248 @@ impl Parse for Length {
         match *parser.next()? {
             Token::Dimension {
                 value, ref unit, ..
-            } => match_ignore_ascii_case!(unit,
-                "px" => return Ok(Length::Px(PxLength::new(value))),
-                _ => {}
-            ),
+            } => {
+                if let Ok(length) = Length::parse_dimension(value, unit) {
+                    return Ok(length)
+                }
+            }
+            // Unitless zero
+            Token::Number { value, .. } if value == 0. => return Ok(Length::Px(PxLength::new(0.))),
             _ => {}
         }
         Err(parser.new_custom_error(PropertyParseErrorKind::Other))
     }
 }
 
+impl Parse for LengthOrPercentage {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if let Ok(length) = parser.r#try(Length::parse) {
+            return Ok(LengthOrPercentage::Length(length))
+        }
+        if let Ok(calc) = parser.r#try(CalcNode::parse) {
+            return Ok(LengthOrPercentage::Calc(Box::new(calc)))
+        }
+        let unit_value = parser.expect_percentage()?;
+        Ok(LengthOrPercentage::Percentage(Percentage(unit_value)))
+    }
+}
+
+impl Parse for NonNegativeLengthOrPercentage {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        let value = LengthOrPercentage::parse(parser)?;
+        if value.is_negative() {
+            return Err(parser.new_custom_error(PropertyParseErrorKind::Other))
+        }
+        Ok(NonNegativeLengthOrPercentage(value))
+    }
+}
+
+impl Parse for LengthOrPercentageOrAuto {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser.r#try(|p| p.expect_ident_matching("auto")).is_ok() {
+            return Ok(LengthOrPercentageOrAuto::Auto)
+        }
+        LengthOrPercentage::parse(parser).map(LengthOrPercentageOrAuto::LengthOrPercentage)
+    }
+}
+
+impl Parse for NonNegativeLengthOrPercentageOrAuto {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser.r#try(|p| p.expect_ident_matching("auto")).is_ok() {
+            return Ok(NonNegativeLengthOrPercentageOrAuto(
+                LengthOrPercentageOrAuto::Auto,
+            ))
+        }
+        let NonNegativeLengthOrPercentage(value) = NonNegativeLengthOrPercentage::parse(parser)?;
+        Ok(NonNegativeLengthOrPercentageOrAuto(
+            LengthOrPercentageOrAuto::LengthOrPercentage(value),
+        ))
+    }
+}
+
+impl Parse for NonNegativeLengthOrPercentageOrNone {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser.r#try(|p| p.expect_ident_matching("none")).is_ok() {
+            return Ok(NonNegativeLengthOrPercentageOrNone::None)
+        }
+        let NonNegativeLengthOrPercentage(value) = NonNegativeLengthOrPercentage::parse(parser)?;
+        Ok(NonNegativeLengthOrPercentageOrNone::LengthOrPercentage(
+            value,
+        ))
+    }
+}
+
 impl ToComputedValue for Length {
     type Computed = PxLength;
-    fn to_computed(&self) -> Self::Computed {
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        let viewport = context.viewport_size;
+        let vw = viewport.width / 100.;
+        let vh = viewport.height / 100.;
         match *self {
             Length::Px(px) => px,
+            Length::Em(value) => context.font_size * value,
+            Length::Rem(value) => context.root_font_size * value,
+            Length::Vw(value) => PxLength::new(value * vw),
+            Length::Vh(value) => PxLength::new(value * vh),
+            Length::Vmin(value) => PxLength::new(value * vw.min(vh)),
+            Length::Vmax(value) => PxLength::new(value * vw.max(vh)),
         }
     }
 }
+
+impl LengthOrPercentage {
+    /// Whether this is known to be negative when parsing.
+    ///
+    /// `calc()` is never rejected, its computed value is clamped instead.
+    pub fn is_negative(&self) -> bool {
+        match self {
+            LengthOrPercentage::Length(length) => length.is_negative(),
+            LengthOrPercentage::Percentage(percentage) => percentage.0 < 0.,
+            LengthOrPercentage::Calc(_) => false,
+        }
+    }
+}
+
+impl ToComputedValue for LengthOrPercentage {
+    type Computed = LengthPercentage;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        match self {
+            LengthOrPercentage::Length(length) => {
+                LengthPercentage::from_length(length.to_computed(context))
+            }
+            LengthOrPercentage::Percentage(percentage) => LengthPercentage::Sum {
+                length: PxLength::new(0.),
+                percentage: Some(*percentage),
+            },
+            LengthOrPercentage::Calc(calc) => {
+                let calc = calc.map_lengths(&|length| length.to_computed(context));
+                match calc.to_sum() {
+                    Some((length, percentage)) => LengthPercentage::Sum { length, percentage },
+                    None => LengthPercentage::Calc(Rc::new(calc)),
+                }
+            }
+        }
+    }
+}
+
+impl ToComputedValue for NonNegativeLengthOrPercentage {
+    type Computed = LengthPercentage;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        // FIXME: clamp negative `calc()` results when resolving percentages
+        self.0.to_computed(context)
+    }
+}
+
+impl ToComputedValue for LengthOrPercentageOrAuto {
+    type Computed = LengthPercentageOrAuto;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        match self {
+            LengthOrPercentageOrAuto::LengthOrPercentage(value) => {
+                LengthPercentageOrAuto::LengthPercentage(value.to_computed(context))
+            }
+            LengthOrPercentageOrAuto::Auto => LengthPercentageOrAuto::Auto,
+        }
+    }
+}
+
+impl ToComputedValue for NonNegativeLengthOrPercentageOrAuto {
+    type Computed = LengthPercentageOrAuto;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        self.0.to_computed(context)
+    }
+}
+
+impl ToComputedValue for NonNegativeLengthOrPercentageOrNone {
+    type Computed = LengthPercentageOrNone;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed {
+        match self {
+            NonNegativeLengthOrPercentageOrNone::LengthOrPercentage(value) => {
+                LengthPercentageOrNone::LengthPercentage(value.to_computed(context))
+            }
+            NonNegativeLengthOrPercentageOrNone::None => LengthPercentageOrNone::None,
+        }
+    }
+}
+
+impl LengthPercentage {
+    pub fn zero() -> Self {
+        LengthPercentage::from_length(PxLength::new(0.))
+    }
+
+    pub fn from_length(length: PxLength) -> Self {
+        LengthPercentage::Sum {
+            length,
+            percentage: None,
+        }
+    }
+
+    pub fn has_percentage(&self) -> bool {
+        match self {
+            LengthPercentage::Sum { percentage, .. } => percentage.is_some(),
+            LengthPercentage::Calc(_) => true,
+        }
+    }
+
+    /// `basis` is what percentages are relative to.
+    pub fn resolve(&self, basis: PxLength) -> PxLength {
+        match self {
+            LengthPercentage::Sum {
+                length,
+                percentage: Some(Percentage(unit_value)),
+            } => *length + basis * *unit_value,
+            LengthPercentage::Sum {
+                length,
+                percentage: None,
+            } => *length,
+            LengthPercentage::Calc(calc) => calc.resolve(basis),
+        }
+    }
+}
+
+/// The computed value of `<length-percentage> | auto`
+#[derive(Clone)]
+pub enum LengthPercentageOrAuto {
+    LengthPercentage(LengthPercentage),
+    Auto,
+}
+
+/// The computed value of `<length-percentage> | none`
+#[derive(Clone)]
+pub enum LengthPercentageOrNone {
+    LengthPercentage(LengthPercentage),
+    None,
+}
+
+impl LengthPercentageOrAuto {
+    pub fn zero() -> Self {
+        LengthPercentageOrAuto::LengthPercentage(LengthPercentage::zero())
+    }
+
+    /// `basis` is what percentages are relative to, if it is definite.
+    /// Return `None` for `auto`, and for percentages without a basis.
+    pub fn resolve(&self, basis: Option<PxLength>) -> Option<PxLength> {
+        match self {
+            LengthPercentageOrAuto::LengthPercentage(value) => resolve_definite(value, basis),
+            LengthPercentageOrAuto::Auto => None,
+        }
+    }
+}
+
+impl LengthPercentageOrNone {
+    /// `basis` is what percentages are relative to, if it is definite.
+    /// Return `None` for `none`, and for percentages without a basis.
+    pub fn resolve(&self, basis: Option<PxLength>) -> Option<PxLength> {
+        match self {
+            LengthPercentageOrNone::LengthPercentage(value) => resolve_definite(value, basis),
+            LengthPercentageOrNone::None => None,
+        }
+    }
+}
+
+fn resolve_definite(value: &LengthPercentage, basis: Option<PxLength>) -> Option<PxLength> {
+    match basis {
+        Some(basis) => Some(value.resolve(basis)),
+        None if value.has_percentage() => None,
+        None => Some(value.resolve(PxLength::new(0.))),
+    }
+}
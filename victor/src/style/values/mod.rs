@@ -1,8 +1,17 @@
+use crate::primitives::{CssPx, Size};
 use crate::style::errors::PropertyParseError;
+use crate::style::values::length::PxLength;
+use crate::style::ComputedValues;
 use cssparser::Parser;
 
+pub mod border;
+pub mod calc;
+pub mod color;
+pub mod content;
+pub mod font;
 pub mod generic;
 pub mod length;
+pub mod page;
 
 pub trait Parse: Sized {
     fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>>;
@@ -10,7 +19,26 @@ pub trait Parse: Sized {
 
 pub trait ToComputedValue {
     type Computed;
-    fn to_computed(&self) -> Self::Computed;
+    fn to_computed(&self, context: &CascadeContext) -> Self::Computed;
+}
+
+/// What specified values are computed against, other than themselves
+///
+/// <https://drafts.csswg.org/css-cascade/#computed>
+pub struct CascadeContext<'a> {
+    /// The parent element’s computed values, or initial values for the root element
+    pub inherited: &'a ComputedValues,
+
+    /// What `em` units are relative to:
+    /// the parent’s `font-size` while computing `font-size` itself,
+    /// and the element’s own otherwise.
+    pub font_size: PxLength,
+
+    /// What `rem` units are relative to
+    pub root_font_size: PxLength,
+
+    /// What `vw`, `vh`, `vmin`, and `vmax` units are relative to
+    pub viewport_size: Size<CssPx>,
 }
 
 #[derive(Copy, Clone, Parse)]
@@ -61,3 +89,82 @@ impl Parse for Display {
         }
     }
 }
+
+/// <https://drafts.csswg.org/css-sizing/#box-sizing>
+#[derive(Copy, Clone, Parse, ComputedAsSpecified)]
+pub enum BoxSizing {
+    ContentBox,
+    BorderBox,
+}
+
+/// <https://drafts.csswg.org/css-break/#break-between>
+#[derive(Copy, Clone, Parse, ComputedAsSpecified)]
+pub enum BreakBetween {
+    Auto,
+    Avoid,
+    AvoidPage,
+    Page,
+    Left,
+    Right,
+    Recto,
+    Verso,
+}
+
+impl BreakBetween {
+    /// Whether this value forces a page break
+    pub fn is_forced(self) -> bool {
+        match self {
+            BreakBetween::Auto | BreakBetween::Avoid | BreakBetween::AvoidPage => false,
+            BreakBetween::Page
+            | BreakBetween::Left
+            | BreakBetween::Right
+            | BreakBetween::Recto
+            | BreakBetween::Verso => true,
+        }
+    }
+
+    pub fn is_avoid(self) -> bool {
+        match self {
+            BreakBetween::Avoid | BreakBetween::AvoidPage => true,
+            _ => false,
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/css-break/#break-within>
+#[derive(Copy, Clone, Parse, ComputedAsSpecified)]
+pub enum BreakWithin {
+    Auto,
+    Avoid,
+    AvoidPage,
+}
+
+/// <https://drafts.csswg.org/css-writing-modes/#block-flow>
+///
+/// Layout only applies `vertical-rl` to the lines of an inline formatting context,
+/// which become columns. Block-level boxes are still stacked from top to bottom.
+///
+/// FIXME: `vertical-lr` and the `sideways-*` values
+#[derive(Copy, Clone, PartialEq, Parse, ComputedAsSpecified)]
+pub enum WritingMode {
+    HorizontalTb,
+    VerticalRl,
+}
+
+/// <https://drafts.csswg.org/css-writing-modes/#direction>
+#[derive(Copy, Clone, PartialEq, Parse, ComputedAsSpecified)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+/// <https://drafts.csswg.org/css-writing-modes/#unicode-bidi>
+#[derive(Copy, Clone, PartialEq, Parse, ComputedAsSpecified)]
+pub enum UnicodeBidi {
+    Normal,
+    Embed,
+    Isolate,
+    BidiOverride,
+    IsolateOverride,
+    Plaintext,
+}
@@ -0,0 +1,113 @@
+use crate::primitives::{CssPx, Size};
+use crate::style::errors::{PropertyParseError, PropertyParseErrorKind};
+use crate::style::values::length::Length;
+use crate::style::values::{CascadeContext, Parse, ToComputedValue};
+use crate::text_plain::css_units::{In, Mm, Px};
+use cssparser::Parser;
+
+/// The `size` descriptor of `@page` rules
+///
+/// <https://drafts.csswg.org/css-page-3/#page-size-prop>
+#[derive(Copy, Clone)]
+pub enum PageSize {
+    Auto,
+    Orientation(Orientation),
+    Explicit { width: Length, height: Length },
+}
+
+impl PageSize {
+    /// `default` is used for `auto` and when only an orientation is specified.
+    pub fn resolve(&self, default: Size<CssPx>, context: &CascadeContext) -> Size<CssPx> {
+        match *self {
+            PageSize::Auto => default,
+            PageSize::Orientation(orientation) => {
+                let (short, long) = if default.width <= default.height {
+                    (default.width, default.height)
+                } else {
+                    (default.height, default.width)
+                };
+                match orientation {
+                    Orientation::Portrait => Size::new(short, long),
+                    Orientation::Landscape => Size::new(long, short),
+                }
+            }
+            PageSize::Explicit { width, height } => {
+                Size::from_lengths(width.to_computed(context), height.to_computed(context))
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Parse)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Parse for PageSize {
+    fn parse<'i, 't>(parser: &mut Parser<'i, 't>) -> Result<Self, PropertyParseError<'i>> {
+        if parser.r#try(|p| p.expect_ident_matching("auto")).is_ok() {
+            return Ok(PageSize::Auto)
+        }
+        if let Ok(width) = parser.r#try(Length::parse) {
+            let height = parser.r#try(Length::parse).unwrap_or(width);
+            return Ok(PageSize::Explicit { width, height })
+        }
+        let orientation = parser.r#try(Orientation::parse).ok();
+        let named = match parser.r#try(parse_page_size_name) {
+            Ok(named) => named,
+            Err(_) => match orientation {
+                Some(orientation) => return Ok(PageSize::Orientation(orientation)),
+                None => return Err(parser.new_custom_error(PropertyParseErrorKind::Other)),
+            },
+        };
+        let orientation = match orientation {
+            Some(orientation) => orientation,
+            None => parser
+                .r#try(Orientation::parse)
+                .unwrap_or(Orientation::Portrait),
+        };
+        let (width, height) = match orientation {
+            Orientation::Portrait => named,
+            Orientation::Landscape => (named.1, named.0),
+        };
+        Ok(PageSize::Explicit { width, height })
+    }
+}
+
+/// <https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size>
+fn parse_page_size_name<'i, 't>(
+    parser: &mut Parser<'i, 't>,
+) -> Result<(Length, Length), PropertyParseError<'i>> {
+    let mm = |width, height| {
+        let size = Size::<Mm>::new(width, height) * Px::per_mm();
+        (
+            Length::Px(size.width_typed()),
+            Length::Px(size.height_typed()),
+        )
+    };
+    let inches = |width, height| {
+        let size = Size::<In>::new(width, height) * Px::per_in();
+        (
+            Length::Px(size.width_typed()),
+            Length::Px(size.height_typed()),
+        )
+    };
+    let ident = parser.expect_ident()?;
+    Ok(match_ignore_ascii_case!(&**ident,
+        "a5" => mm(148., 210.),
+        "a4" => mm(210., 297.),
+        "a3" => mm(297., 420.),
+        "b5" => mm(176., 250.),
+        "b4" => mm(250., 353.),
+        "jis-b5" => mm(182., 257.),
+        "jis-b4" => mm(257., 364.),
+        "letter" => inches(8.5, 11.),
+        "legal" => inches(8.5, 14.),
+        "ledger" => inches(11., 17.),
+        _ => {
+            let token = cssparser::Token::Ident(ident.clone());
+            return Err(parser.new_unexpected_token_error(token))
+        }
+    ))
+}
@@ -0,0 +1,40 @@
+//! The [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/)
+
+use crate::text::Direction;
+use std::ops::Range;
+
+pub(crate) use unicode_bidi::{BidiInfo, Level};
+
+impl Direction {
+    /// Even embedding levels are left-to-right, odd ones are right-to-left.
+    pub(crate) fn of_level(level: Level) -> Self {
+        if level.is_rtl() {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+}
+
+/// Split `range` of a text into maximal runs of bytes with the same embedding level.
+///
+/// `levels` has one item per byte of the text, as in `BidiInfo::levels`.
+pub(crate) fn level_runs(levels: &[Level], range: Range<usize>) -> Vec<(Range<usize>, Level)> {
+    let mut runs: Vec<(Range<usize>, Level)> = Vec::new();
+    for index in range {
+        let level = levels[index];
+        match runs.last_mut() {
+            Some((run, run_level)) if *run_level == level => run.end = index + 1,
+            _ => runs.push((index..index + 1, level)),
+        }
+    }
+    runs
+}
+
+/// Given the embedding levels of the items of a line in logical order,
+/// return the indices of these items in visual order from left to right.
+///
+/// <https://www.unicode.org/reports/tr9/#L2>
+pub(crate) fn visual_order(levels: &[Level]) -> Vec<usize> {
+    BidiInfo::reorder_visual(levels)
+}
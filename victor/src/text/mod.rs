@@ -0,0 +1,191 @@
+//! Text shaping: converting a string to positioned glyphs of a given font
+
+pub(crate) mod bidi;
+mod naive;
+mod opentype;
+
+use crate::fonts::{Em, Font, FontError, FontFeatures, GlyphId};
+use crate::primitives::{CssPx, Length, Vector};
+use std::sync::Arc;
+
+pub use self::naive::NaiveShaper;
+pub use self::opentype::OpenTypeShaper;
+
+/// An implementation of text shaping
+pub trait Shaper {
+    /// Shape `text` with a single font.
+    ///
+    /// Characters that the font does not support use its `.notdef` glyph.
+    /// `direction` is that of the text’s bidi embedding level:
+    /// the glyphs of right-to-left text are in reverse order.
+    /// For vertical text it is `TopToBottom`, and advances are vertical.
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<Font>,
+        features: &FontFeatures,
+        direction: Direction,
+    ) -> Result<ShapedSegment, FontError>;
+
+    /// Like `shape`, but each character uses the first font in `fonts`
+    /// that has a glyph for it.
+    ///
+    /// Return one segment for each run of consecutive characters that use the same font,
+    /// in logical order even for right-to-left text.
+    /// Characters that no font supports stay in the current run,
+    /// or use the first font at the start of the text.
+    fn shape_with_fallback(
+        &self,
+        text: &str,
+        fonts: &[Arc<Font>],
+        features: &FontFeatures,
+        direction: Direction,
+    ) -> Result<Vec<ShapedSegment>, FontError> {
+        assert!(
+            !fonts.is_empty(),
+            "shape_with_fallback needs at least one font"
+        );
+        // The start index in `text` and the font of each run
+        let mut runs: Vec<(usize, &Arc<Font>)> = Vec::new();
+        for (index, ch) in text.char_indices() {
+            let mut font = None;
+            for candidate in fonts {
+                if candidate.has_glyph(ch)? {
+                    font = Some(candidate);
+                    break
+                }
+            }
+            let same_as_previous = match (font, runs.last()) {
+                (Some(font), Some(&(_, previous))) => Arc::ptr_eq(font, previous),
+                (None, Some(_)) => true,
+                (_, None) => false,
+            };
+            if !same_as_previous {
+                runs.push((index, font.unwrap_or(&fonts[0])))
+            }
+        }
+        let mut segments = Vec::with_capacity(runs.len());
+        for (i, &(start, font)) in runs.iter().enumerate() {
+            let end = runs
+                .get(i + 1)
+                .map_or(text.len(), |&(next_start, _)| next_start);
+            segments.push(self.shape(&text[start..end], font.clone(), features, direction)?)
+        }
+        Ok(segments)
+    }
+}
+
+/// The direction in which consecutive characters are laid out
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+
+    /// Vertical text with upright glyphs
+    TopToBottom,
+}
+
+#[derive(Clone)]
+pub struct ShapedSegment {
+    pub(crate) font: Arc<Font>,
+
+    /// In visual order, from left to right or from top to bottom
+    pub(crate) glyphs: Vec<GlyphId>,
+
+    /// The position of the glyph at the same index in `glyphs`
+    pub(crate) positions: Vec<GlyphPosition>,
+
+    /// The sum of the advances in `positions`, which is a height for vertical text
+    pub(crate) advance_width: Length<Em>,
+
+    /// The source text that was shaped
+    pub(crate) text: String,
+
+    /// For each glyph, the byte index in `text` where the cluster of characters
+    /// that it was shaped from starts.
+    /// A ligature is one glyph for a cluster of several characters.
+    /// In right-to-left text, this is in decreasing order.
+    pub(crate) clusters: Vec<usize>,
+
+    pub(crate) direction: Direction,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct GlyphPosition {
+    /// How much the pen moves after this glyph, including adjustments such as kerning.
+    /// In vertical text, it moves down.
+    pub advance: Length<Em>,
+
+    /// Where the glyph is drawn relative to the pen, for example for combining marks.
+    /// The Y axis points up.
+    /// In vertical text, the pen is at the glyph’s vertical origin.
+    pub offset: Vector<Em>,
+}
+
+impl ShapedSegment {
+    /// Shape with `NaiveShaper` and default font features.
+    pub fn naive_shape(text: &str, font: Arc<Font>) -> Result<Self, FontError> {
+        NaiveShaper.shape(text, font, &FontFeatures::default(), Direction::LeftToRight)
+    }
+
+    /// Shape with `NaiveShaper` and default font features, with font fallback.
+    /// See `Shaper::shape_with_fallback`.
+    pub fn naive_shape_with_fallback(
+        text: &str,
+        fonts: &[Arc<Font>],
+    ) -> Result<Vec<Self>, FontError> {
+        NaiveShaper.shape_with_fallback(
+            text,
+            fonts,
+            &FontFeatures::default(),
+            Direction::LeftToRight,
+        )
+    }
+
+    /// The sum of the advance widths of this segment’s glyphs, for a given font size.
+    /// For vertical text, this is the sum of their advance heights.
+    pub fn advance_width(&self, font_size: Length<CssPx>) -> Length<CssPx> {
+        self.advance_width * (font_size / Length::<Em>::new(1.))
+    }
+
+    /// Return the source text of the cluster of the glyph at `index` in `glyphs`,
+    /// or `None` if that glyph is not the first of its cluster.
+    pub(crate) fn cluster_text(&self, index: usize) -> Option<&str> {
+        let start = self.clusters[index];
+        if index > 0 && self.clusters[index - 1] == start {
+            return None
+        }
+        let end = self
+            .clusters
+            .iter()
+            .cloned()
+            .filter(|&cluster| cluster > start)
+            .min()
+            .unwrap_or(self.text.len());
+        Some(&self.text[start..end])
+    }
+
+    /// Add `other`, which must use the same font and direction, logically after this segment:
+    /// its glyphs go to the right of those of this segment in left-to-right text,
+    /// to the left in right-to-left text, or below in vertical text.
+    pub(crate) fn append(&mut self, other: ShapedSegment) {
+        debug_assert!(Arc::ptr_eq(&self.font, &other.font));
+        debug_assert_eq!(self.direction, other.direction);
+        let offset = self.text.len();
+        let other_clusters = other.clusters.iter().map(|cluster| cluster + offset);
+        match self.direction {
+            Direction::LeftToRight | Direction::TopToBottom => {
+                self.glyphs.extend(other.glyphs);
+                self.positions.extend(other.positions);
+                self.clusters.extend(other_clusters)
+            }
+            Direction::RightToLeft => {
+                self.glyphs.splice(0..0, other.glyphs);
+                self.positions.splice(0..0, other.positions);
+                self.clusters.splice(0..0, other_clusters);
+            }
+        }
+        self.advance_width += other.advance_width;
+        self.text.push_str(&other.text)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::fonts::{Font, FontError, FontFeatures};
+use crate::primitives::{Length, Vector};
+use crate::text::{Direction, GlyphPosition, ShapedSegment, Shaper};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// Simplistic text shaping:
+///
+/// * One glyph per character from the font’s character map
+/// * No support for complex scripts.
+///   Right-to-left text is reversed, but characters such as parentheses are not mirrored.
+/// * Only some types of `GSUB` substitutions, such as ligatures
+/// * Kerning of pairs of glyphs only, in horizontal text
+/// * Vertical text with upright glyphs and vertical alternates (`vert`), such as for punctuation
+pub struct NaiveShaper;
+
+impl Shaper for NaiveShaper {
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<Font>,
+        features: &FontFeatures,
+        direction: Direction,
+    ) -> Result<ShapedSegment, FontError> {
+        let vertical = direction == Direction::TopToBottom;
+        // Vertical alternates are enabled by default in vertical text.
+        let mut features = Cow::Borrowed(features);
+        if vertical && !features.iter().any(|(tag, _)| tag == *b"vert") {
+            features.to_mut().set(*b"vert", 1)
+        }
+        let mut glyphs = Vec::new();
+        let mut clusters = Vec::new();
+        for (index, ch) in text.char_indices() {
+            glyphs.push(font.glyph_id(ch)?);
+            clusters.push(index)
+        }
+        font.substitute(&features, &mut glyphs, &mut clusters)?;
+        if direction == Direction::RightToLeft {
+            glyphs.reverse();
+            clusters.reverse()
+        }
+
+        let kerning = features.is_enabled(*b"kern") && !vertical;
+        let mut positions = Vec::with_capacity(glyphs.len());
+        let mut advance_width = Length::new(0.);
+        for (i, &id) in glyphs.iter().enumerate() {
+            let adjustment = match glyphs.get(i + 1) {
+                Some(&next) if kerning => font.kerning(id, next)?,
+                _ => Length::new(0.),
+            };
+            let advance = if vertical {
+                font.glyph_vertical_advance(id)?
+            } else {
+                font.glyph_width(id)? + adjustment
+            };
+            advance_width += advance;
+            positions.push(GlyphPosition {
+                advance,
+                offset: Vector::zero(),
+            })
+        }
+        Ok(ShapedSegment {
+            font,
+            glyphs,
+            positions,
+            advance_width,
+            text: text.to_owned(),
+            clusters,
+            direction,
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use crate::fonts::{Font, FontError, FontFeatures, GlyphId};
+use crate::primitives::{Length, Vector};
+use crate::text::{Direction, GlyphPosition, ShapedSegment, Shaper};
+use std::sync::Arc;
+
+/// Text shaping with the [rustybuzz](https://github.com/RazrFalcon/rustybuzz) engine,
+/// a port of HarfBuzz.
+///
+/// It supports complex scripts such as Arabic or Devanagari, right-to-left text,
+/// and all of the `GSUB` and `GPOS` tables.
+/// The script is guessed from the text.
+/// Features that a script requires, such as the positional forms of Arabic letters,
+/// are applied regardless of `FontFeatures`.
+/// So are vertical alternates (`vert`) in vertical text, unless disabled explicitly.
+pub struct OpenTypeShaper;
+
+impl Shaper for OpenTypeShaper {
+    fn shape(
+        &self,
+        text: &str,
+        font: Arc<Font>,
+        features: &FontFeatures,
+        direction: Direction,
+    ) -> Result<ShapedSegment, FontError> {
+        let mut face = rustybuzz::Face::from_slice(font.bytes(), font.face_index())
+            .ok_or(FontError::UnsupportedFormat)?;
+        if !font.variation_axes().is_empty() {
+            let variations: Vec<_> = font
+                .variation_axes()
+                .iter()
+                .zip(font.variation_coordinates())
+                .map(|(axis, &value)| rustybuzz::Variation {
+                    tag: rustybuzz::Tag::from_bytes(&axis.tag),
+                    value,
+                })
+                .collect();
+            face.set_variations(&variations);
+        }
+        // Like `FontFeatures`, rustybuzz enables some features by default.
+        // Disable them explicitly if `features` does not enable them.
+        let disabled_defaults = FontFeatures::default()
+            .iter()
+            .filter(|&(tag, _)| !features.is_enabled(tag))
+            .map(|(tag, _)| (tag, 0))
+            .collect::<Vec<_>>();
+        let features: Vec<_> = disabled_defaults
+            .into_iter()
+            .chain(features.iter())
+            .map(|(tag, value)| {
+                rustybuzz::Feature::new(rustybuzz::Tag::from_bytes(&tag), value, ..)
+            })
+            .collect();
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(text);
+        buffer.set_direction(match direction {
+            Direction::LeftToRight => rustybuzz::Direction::LeftToRight,
+            Direction::RightToLeft => rustybuzz::Direction::RightToLeft,
+            Direction::TopToBottom => rustybuzz::Direction::TopToBottom,
+        });
+        let output = rustybuzz::shape(&face, &features, buffer);
+
+        let mut glyphs = Vec::with_capacity(output.len());
+        let mut positions = Vec::with_capacity(output.len());
+        let mut clusters = Vec::with_capacity(output.len());
+        let mut advance_width = Length::new(0.);
+        for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+            // Clusters are byte indices in `text`, since it was added with `push_str`.
+            clusters.push(info.cluster as usize);
+            glyphs.push(GlyphId(info.glyph_id as u16));
+            let position = if direction == Direction::TopToBottom {
+                // rustybuzz’s offsets in vertical text are relative to its own vertical origin,
+                // which can differ from `Font::glyph_vertical_origin`.
+                // FIXME: keep adjustments from `GPOS`, such as for `vpal`
+                GlyphPosition {
+                    advance: -font.design_units_to_ems(position.y_advance),
+                    offset: Vector::zero(),
+                }
+            } else {
+                GlyphPosition {
+                    advance: font.design_units_to_ems(position.x_advance),
+                    offset: Vector::from_lengths(
+                        font.design_units_to_ems(position.x_offset),
+                        font.design_units_to_ems(position.y_offset),
+                    ),
+                }
+            };
+            advance_width += position.advance;
+            positions.push(position)
+        }
+        Ok(ShapedSegment {
+            font,
+            glyphs,
+            positions,
+            advance_width,
+            text: text.to_owned(),
+            clusters,
+            direction,
+        })
+    }
+}
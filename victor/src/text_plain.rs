@@ -1,8 +1,9 @@
 use self::css_units::*;
-use crate::fonts::{Em, Font, FontError};
+use crate::fonts::{Em, Font, FontError, FontFeatures};
 use crate::pdf::Document;
 use crate::primitives::{Length, Point, Rect, SideOffsets, Size, TextRun};
-use crate::text::ShapedSegment;
+use crate::text::bidi::{level_runs, visual_order, BidiInfo, Level};
+use crate::text::{Direction, OpenTypeShaper, ShapedSegment, Shaper};
 use std::sync::Arc;
 use xi_unicode::LineBreakIterator;
 
@@ -37,7 +38,20 @@ pub struct Style {
     pub justify: bool,
 }
 
+/// Lay out text into pages, shaped with `OpenTypeShaper`.
+///
+/// The base direction of each paragraph is that of its first strong character,
+/// and right-to-left paragraphs are aligned to the right.
 pub fn layout(text: &str, style: &Style) -> Result<Document, FontError> {
+    layout_with_shaper(text, style, &OpenTypeShaper)
+}
+
+/// Like `layout`, with the given implementation of text shaping.
+pub fn layout_with_shaper(
+    text: &str,
+    style: &Style,
+    shaper: &dyn Shaper,
+) -> Result<Document, FontError> {
     let page_size = style.page_size * Px::per_mm();
     let page_margin = SideOffsets::from_length_all_same(style.page_margin * Px::per_mm());
     let page = Rect::new(Point::origin(), page_size);
@@ -58,18 +72,37 @@ pub fn layout(text: &str, style: &Style) -> Result<Document, FontError> {
     let baseline_y = baseline_y * px_per_em;
 
     let mut pdf_doc = Document::new();
-    let mut line_segments = Vec::new();
+    let mut line_words = Vec::new();
 
+    let features = FontFeatures::default();
+    let bidi = BidiInfo::new(text, None);
     let mut previous_break_position = 0;
-    let mut segments = Rewind::new(LineBreakIterator::new(text).map(
-        |(position, is_hard_break)| {
-            let range = previous_break_position..position;
+    let mut words = Rewind::new(
+        LineBreakIterator::new(text).map(|(position, is_hard_break)| {
+            let start = previous_break_position;
             previous_break_position = position;
-            let text_segment = text[range].trim_right_matches('\n');
-            let segment = ShapedSegment::naive_shape(text_segment, style.font.clone())?;
-            Ok((segment, is_hard_break))
-        },
-    ));
+            let end = start + text[start..position].trim_right_matches('\n').len();
+            let mut runs = Vec::new();
+            let mut advance_width = Length::new(0.);
+            for (run, level) in level_runs(&bidi.levels, start..end) {
+                let direction = Direction::of_level(level);
+                let segment = shaper.shape(&text[run], style.font.clone(), &features, direction)?;
+                advance_width += segment.advance_width;
+                runs.push((segment, level))
+            }
+            let paragraph_level = bidi
+                .paragraphs
+                .iter()
+                .find(|paragraph| paragraph.range.contains(&start))
+                .map_or(Level::ltr(), |paragraph| paragraph.level);
+            Ok(Word {
+                runs,
+                advance_width,
+                paragraph_level,
+                is_hard_break,
+            })
+        }),
+    );
 
     'pages: loop {
         let mut pdf_page = pdf_doc.add_page(page_size);
@@ -79,23 +112,24 @@ pub fn layout(text: &str, style: &Style) -> Result<Document, FontError> {
             let mut total_width = Length::new(0.);
             let justify;
             loop {
-                let (segment, is_hard_break) = match segments.next() {
+                let word = match words.next() {
                     Some(result) => result?,
                     // End of document
                     // FIXME: use 'return' when lifetimes are non-lexical
                     None => break 'pages,
                 };
 
-                let advance_width = segment.advance_width * px_per_em;
+                let advance_width = word.advance_width * px_per_em;
                 let next_total_width = total_width + advance_width;
                 if next_total_width > available_width && total_width > Length::new(0.) {
-                    // This segment doesn’t fit on this line, and isn’t the first on the line:
+                    // This word doesn’t fit on this line, and isn’t the first on the line:
                     // go to the next line.
-                    segments.rewind(Ok((segment, is_hard_break)));
+                    words.rewind(Ok(word));
                     justify = style.justify;
                     break
                 }
-                line_segments.push(segment);
+                let is_hard_break = word.is_hard_break;
+                line_words.push(word);
                 total_width = next_total_width;
                 if is_hard_break {
                     justify = false;
@@ -105,15 +139,37 @@ pub fn layout(text: &str, style: &Style) -> Result<Document, FontError> {
 
             let extra = available_width - total_width;
             let word_spacing = if justify && extra > Length::new(0.) {
-                extra / (line_segments.len() - 1) as f32
+                extra / (line_words.len() - 1) as f32
             } else {
                 Length::new(0.)
             };
+            let rtl = line_words[0].paragraph_level.is_rtl();
             let baseline = y + baseline_y;
-            let mut x = min_x;
-            for segment in line_segments.drain(..) {
+            let mut x = if rtl && !justify && extra > Length::new(0.) {
+                min_x + extra
+            } else {
+                min_x
+            };
+
+            // Reorder runs of each word by embedding level, across the entire line.
+            // Word spacing is added after the last run of each word in logical order.
+            let mut runs = Vec::new();
+            for word in line_words.drain(..) {
+                let last = word.runs.len().saturating_sub(1);
+                for (i, (segment, level)) in word.runs.into_iter().enumerate() {
+                    runs.push((Some(segment), level, i == last))
+                }
+            }
+            let levels: Vec<_> = runs.iter().map(|&(_, level, _)| level).collect();
+            for index in visual_order(&levels) {
+                let (segment, _, is_last_of_word) = &mut runs[index];
+                let segment = segment.take().unwrap();
                 let origin = Point::from_lengths(x, baseline);
-                x += segment.advance_width * px_per_em + word_spacing;
+                let mut advance = segment.advance_width * px_per_em;
+                if *is_last_of_word {
+                    advance += word_spacing
+                }
+                x += advance;
                 pdf_page.show_text(&TextRun {
                     segment,
                     font_size,
@@ -131,6 +187,15 @@ pub fn layout(text: &str, style: &Style) -> Result<Document, FontError> {
     Ok(pdf_doc)
 }
 
+/// The text between two line break opportunities,
+/// shaped in runs of consecutive characters with the same bidi embedding level
+struct Word {
+    runs: Vec<(ShapedSegment, Level)>,
+    advance_width: Length<Em>,
+    paragraph_level: Level,
+    is_hard_break: bool,
+}
+
 struct Rewind<I>
 where
     I: Iterator,